@@ -0,0 +1,78 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_mod_sprite3d::test_support::add_headless_batching;
+use bevy_mod_sprite3d::{Sprite3d, Sprite3dPlugin, SpriteMaterial3d};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const SPRITE_COUNTS: [usize; 3] = [10_000, 100_000, 1_000_000];
+const FEW_MATERIALS: usize = 4;
+const MANY_MATERIALS: usize = 256;
+
+/// Builds a headless app with the given number of sprites spread evenly across the given number of materials.
+fn create_app(sprite_count: usize, material_count: usize, moving: bool) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    add_headless_batching(&mut app, Sprite3dPlugin::<StandardMaterial>::default());
+    if moving {
+        app.add_systems(Update, spin);
+    }
+
+    let world = app.world_mut();
+    let image = world.resource_mut::<Assets<Image>>().add(Image::new_fill(
+        Extent3d { width: 32, height: 32, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[255, 255, 255, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    ));
+    let materials: Vec<Handle<StandardMaterial>> = {
+        let mut material_assets = world.resource_mut::<Assets<StandardMaterial>>();
+        (0..material_count)
+            .map(|_| material_assets.add(StandardMaterial {
+                base_color_texture: Some(image.clone()),
+                ..default()
+            }))
+            .collect()
+    };
+    let side = (sprite_count as f32).sqrt().ceil() as usize;
+    world.spawn_batch((0..sprite_count).map(move |i| {
+        let x = (i % side) as f32 * 32.0;
+        let y = (i / side) as f32 * 32.0;
+        (
+            SpriteMaterial3d(materials[i % material_count].clone()),
+            Sprite3d::default(),
+            Transform::from_xyz(x, y, 0.0),
+        )
+    }));
+
+    // Warms up the batches so that the benchmark only measures steady-state frames.
+    app.update();
+    app
+}
+
+fn spin(mut transforms: Query<&mut Transform, With<Sprite3d>>, time: Res<Time>) {
+    for mut transf in &mut transforms {
+        transf.rotate_y(TAU * time.delta_secs());
+    }
+}
+
+fn batching(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batching");
+    group.sample_size(10);
+    for sprite_count in SPRITE_COUNTS {
+        for (material_label, material_count) in [("few", FEW_MATERIALS), ("many", MANY_MATERIALS)] {
+            for (motion_label, moving) in [("static", false), ("moving", true)] {
+                let id = BenchmarkId::new(format!("{motion_label}_{material_label}_materials"), sprite_count);
+                let mut app = create_app(sprite_count, material_count, moving);
+                group.bench_function(id, |b| b.iter(|| app.update()));
+            }
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, batching);
+criterion_main!(benches);
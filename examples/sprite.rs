@@ -2,7 +2,7 @@ use std::f32::consts::{PI, TAU};
 
 use bevy::prelude::*;
 use bevy::sprite::Anchor;
-use bevy_mod_sprite3d::{Sprite3d, Sprite3dPlugin, SpriteMaterial3d};
+use bevy_mod_sprite3d::{Facing, Sprite3d, Sprite3dPlugin, SpriteMaterial3d};
 
 fn main() {
     App::new()
@@ -31,6 +31,12 @@ fn setup(
         double_sided: true,
         ..default()
     });
+    let pokey_culled_mat = materials.add(StandardMaterial {
+        base_color_texture: Some(assets.load("pokey.png")),
+        reflectance: 0.0,
+        perceptual_roughness: 1.0,
+        ..default()
+    });
     let health_mat = materials.add(StandardMaterial {
         base_color_texture: Some(assets.load("health.png")),
         reflectance: 0.0,
@@ -75,6 +81,14 @@ fn setup(
         Spinner,
     ));
 
+    // Pokey spinning with back-face culling, visible from both sides
+    commands.spawn((
+        SpriteMaterial3d(pokey_culled_mat.clone()),
+        Sprite3d { facing: Facing::Both, ..default() },
+        Transform::from_xyz(5.0 * 32.0, 0.0, 0.0),
+        Spinner,
+    ));
+
     // Health
     commands.spawn((
         SpriteMaterial3d(health_mat.clone()),
@@ -0,0 +1,86 @@
+use std::f32::consts::TAU;
+
+use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use bevy::prelude::*;
+use bevy_mod_sprite3d::{Sprite3d, Sprite3dPlugin, SpriteMaterial3d};
+
+/// Number of sprites spawned when no count is supplied on the command line.
+const DEFAULT_SPRITE_COUNT: usize = 100_000;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            FrameTimeDiagnosticsPlugin,
+            LogDiagnosticsPlugin::default(),
+            Sprite3dPlugin::<StandardMaterial>::default(),
+        ))
+        .add_systems(Startup, setup)
+        .add_systems(Update, spin)
+        .run();
+}
+
+/// Spawns a grid of spinning sprites.
+/// Usage: cargo run --release --example stress -- [sprite_count]
+fn setup(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    let sprite_count = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_SPRITE_COUNT);
+
+    let pokey_mat = materials.add(StandardMaterial {
+        base_color_texture: Some(assets.load("pokey.png")),
+        reflectance: 0.0,
+        perceptual_roughness: 1.0,
+        cull_mode: None,
+        double_sided: true,
+        ..default()
+    });
+    let health_mat = materials.add(StandardMaterial {
+        base_color_texture: Some(assets.load("health.png")),
+        reflectance: 0.0,
+        perceptual_roughness: 1.0,
+        cull_mode: None,
+        ..default()
+    });
+
+    // Sprites
+    let side = (sprite_count as f32).sqrt().ceil() as usize;
+    let half_extent = side as f32 * 32.0 / 2.0;
+    commands.spawn_batch((0..sprite_count).map(move |i| {
+        let x = (i % side) as f32 * 32.0 - half_extent;
+        let z = (i / side) as f32 * 32.0 - half_extent;
+        let material = if i % 2 == 0 { pokey_mat.clone() } else { health_mat.clone() };
+        (
+            SpriteMaterial3d(material),
+            Sprite3d::default(),
+            Transform::from_xyz(x, 0.0, z),
+            Spinner,
+        )
+    }));
+
+    // Light
+    commands.spawn(DirectionalLight {
+        illuminance: 5_000.0,
+        ..Default::default()
+    });
+
+    // Camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, half_extent, half_extent * 2.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+fn spin(mut spinners: Query<&mut Transform, With<Spinner>>, time: Res<Time>) {
+    for mut transf in &mut spinners {
+        transf.rotate_y(1.0 / 5.0 * TAU * time.delta_secs());
+    }
+}
+
+#[derive(Component)]
+struct Spinner;
@@ -0,0 +1,55 @@
+use bevy_math::{Rect, Vec2};
+use bevy_sprite::Anchor;
+
+/// Constructors for [`Anchor`]s relative to the region of the texture a sprite renders, ie: its `rect` when
+/// cropping an atlas, rather than the whole texture.
+/// Unlike [`Anchor::Custom`], which goes from -0.5 to 0.5 with Y up, these go from (0, 0) at the top left to (1, 1)
+/// at the bottom right, like UVs and pixel coordinates.
+pub trait Sprite3dAnchorExt {
+    /// Anchor at a fraction of the sprite's size, ie: `Anchor::percent(0.25, 0.9)` for a point a quarter of the way
+    /// from the left, near the bottom.
+    fn percent(x: f32, y: f32) -> Anchor;
+
+    /// Anchor at a pixel of the texture, relative to the region `rect` of the texture the sprite renders,
+    /// ie: the feet of a character, picked in the atlas it comes from.
+    fn from_pixel(pixel: Vec2, rect: Rect) -> Anchor;
+
+    /// Position of the anchor as a fraction of the sprite's size, from (0, 0) at the top left to (1, 1)
+    /// at the bottom right.
+    fn as_percent(&self) -> Vec2;
+}
+
+impl Sprite3dAnchorExt for Anchor {
+    fn percent(x: f32, y: f32) -> Anchor {
+        let anchor = Vec2::new(x - 0.5, 0.5 - y);
+        ANCHOR_PRESETS
+            .into_iter()
+            .find(|preset| preset.as_vec() == anchor)
+            .unwrap_or(Anchor::Custom(anchor))
+    }
+
+    fn from_pixel(pixel: Vec2, rect: Rect) -> Anchor {
+        let size = rect.size();
+        if size.x <= 0.0 || size.y <= 0.0 { return Anchor::Center };
+        let percent = (pixel - rect.min) / size;
+        Self::percent(percent.x, percent.y)
+    }
+
+    fn as_percent(&self) -> Vec2 {
+        let anchor = self.as_vec();
+        Vec2::new(anchor.x + 0.5, 0.5 - anchor.y)
+    }
+}
+
+/// Named anchors, so that percentages matching them don't become [`Anchor::Custom`].
+const ANCHOR_PRESETS: [Anchor; 9] = [
+    Anchor::Center,
+    Anchor::BottomLeft,
+    Anchor::BottomCenter,
+    Anchor::BottomRight,
+    Anchor::CenterLeft,
+    Anchor::CenterRight,
+    Anchor::TopLeft,
+    Anchor::TopCenter,
+    Anchor::TopRight,
+];
@@ -0,0 +1,572 @@
+use std::io::Cursor;
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_asset::io::Reader;
+use bevy_asset::{prelude::*, AssetLoader, LoadContext, RenderAssetUsages};
+use bevy_image::prelude::*;
+use bevy_math::{Rect, UVec2};
+use bevy_reflect::prelude::*;
+use bevy_render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::animation::init_clip_assets;
+use crate::{ClipFrame, Sprite3dClip};
+
+const GIF_EXTENSION: u8 = 0x21;
+const GIF_IMAGE: u8 = 0x2C;
+const GIF_TRAILER: u8 = 0x3B;
+const GIF_GRAPHIC_CONTROL: u8 = 0xF9;
+const GIF_APPLICATION: u8 = 0xFF;
+
+/// Frame duration of GIFs with a delay of 0, which browsers play at about this speed as well.
+const GIF_DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// Loads animated images (`.gif`, `.apng`) as sprite sheets, ie: simple animated decals, without preparing a
+/// sheet by hand. Animated PNGs need the `.apng` extension, as `.png` files are loaded as plain images.
+pub struct AnimatedImagePlugin;
+
+impl Plugin for AnimatedImagePlugin {
+    fn build(&self, app: &mut App) {
+        init_clip_assets(app);
+        app.init_asset::<AnimatedImage>();
+        app.register_asset_loader(AnimatedImageLoader);
+    }
+}
+
+/// Sprite sheet decoded from an animated image.
+/// Frames are composited (the way browsers display them) and laid out left to right in a single image, which is
+/// also available as the `image` labeled asset. The clip playing all frames is available as the `clip` labeled
+/// asset, ie: `"fire.gif#clip"`, to play with a [`Sprite3dAnimation`](crate::Sprite3dAnimation).
+#[derive(Asset, TypePath, Debug)]
+pub struct AnimatedImage {
+    pub image: Handle<Image>,
+    pub clip: Handle<Sprite3dClip>,
+    /// Size of a frame, in pixels.
+    pub frame_size: UVec2,
+    pub frames: Vec<ClipFrame>,
+    /// Number of times the animation plays. 0 for infinitely.
+    pub repeat: u32,
+}
+
+impl AnimatedImage {
+    /// Region of the image showing a frame, in pixels.
+    pub fn frame_rect(&self, index: usize) -> Option<Rect> {
+        self.frames.get(index).map(|frame| frame.rect)
+    }
+}
+
+#[derive(Default)]
+struct AnimatedImageLoader;
+
+impl AssetLoader for AnimatedImageLoader {
+    type Asset = AnimatedImage;
+    type Settings = ();
+    type Error = AnimatedImageLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<AnimatedImage, AnimatedImageLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let decoded = match bytes.starts_with(b"GIF") {
+            true => decode_gif(&bytes)?,
+            false => decode_apng(&bytes)?,
+        };
+        if decoded.frames.is_empty() {
+            return Err(AnimatedImageLoaderError::invalid("no frames"));
+        }
+
+        let frame_size = decoded.size;
+        let sheet_size = UVec2::new(frame_size.x * decoded.frames.len() as u32, frame_size.y);
+        let frame_bytes = frame_size.x as usize * 4;
+        let mut pixels = vec![0; (sheet_size.x * sheet_size.y * 4) as usize];
+        let mut frames = Vec::with_capacity(decoded.frames.len());
+        for (frame_index, (frame_pixels, duration)) in decoded.frames.iter().enumerate() {
+            let frame_offset = UVec2::new(frame_index as u32 * frame_size.x, 0);
+            for (y, row) in frame_pixels.chunks_exact(frame_bytes).enumerate() {
+                let start = (y * sheet_size.x as usize + frame_offset.x as usize) * 4;
+                pixels[start..start + frame_bytes].copy_from_slice(row);
+            }
+            frames.push(ClipFrame {
+                rect: Rect::from_corners(frame_offset.as_vec2(), (frame_offset + frame_size).as_vec2()),
+                duration: *duration,
+            });
+        }
+        let image = Image::new(
+            Extent3d { width: sheet_size.x, height: sheet_size.y, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            pixels,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+
+        Ok(AnimatedImage {
+            image: load_context.add_labeled_asset("image".to_string(), image),
+            clip: load_context.add_labeled_asset("clip".to_string(), Sprite3dClip::new(frames.clone())),
+            frame_size,
+            frames,
+            repeat: decoded.repeat,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gif", "apng"]
+    }
+}
+
+/// Composited RGBA8 frames of an animated image, with their durations.
+struct DecodedFrames {
+    size: UVec2,
+    frames: Vec<(Vec<u8>, Duration)>,
+    repeat: u32,
+}
+
+/// Region of a frame that a sub-image covers, in pixels.
+#[derive(Copy, Clone)]
+struct SubRect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl SubRect {
+    /// Indices of the pixels of the frame covered by the sub-image, row by row, clipped to the frame.
+    fn pixel_indices(self, size: UVec2) -> impl Iterator<Item = (usize, Option<usize>)> {
+        (0..self.height).flat_map(move |y| {
+            (0..self.width).map(move |x| {
+                let (frame_x, frame_y) = (self.x + x, self.y + y);
+                let sub_index = (y * self.width + x) as usize;
+                let inside = frame_x < size.x && frame_y < size.y;
+                (sub_index, inside.then(|| (frame_y * size.x + frame_x) as usize))
+            })
+        })
+    }
+
+    fn clear(self, canvas: &mut [u8], size: UVec2) {
+        for (_, index) in self.pixel_indices(size) {
+            let Some(index) = index else { continue };
+            canvas[index * 4..index * 4 + 4].fill(0);
+        }
+    }
+}
+
+fn decode_apng(bytes: &[u8]) -> Result<DecodedFrames, AnimatedImageLoaderError> {
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let info = reader.info();
+    let size = UVec2::new(info.width, info.height);
+    let repeat = info.animation_control.map_or(0, |control| control.num_plays);
+    let frame_count = info.animation_control.map_or(1, |control| control.num_frames as usize);
+    // The default image isn't part of the animation when it has no frame control of its own
+    let skip_default_image = info.animation_control.is_some() && info.frame_control.is_none();
+
+    let mut buffer = vec![0; reader.output_buffer_size().ok_or_else(|| AnimatedImageLoaderError::invalid("image too large"))?];
+    if skip_default_image {
+        reader.next_frame(&mut buffer)?;
+    }
+    let mut canvas = vec![0; (size.x * size.y * 4) as usize];
+    let mut frames = Vec::with_capacity(frame_count);
+    for _ in 0..frame_count {
+        let output = reader.next_frame(&mut buffer)?;
+        let control = reader.info().frame_control.unwrap_or_default();
+        let sub_rect = match reader.info().animation_control {
+            Some(_) => SubRect { x: control.x_offset, y: control.y_offset, width: output.width, height: output.height },
+            None => SubRect { x: 0, y: 0, width: output.width, height: output.height },
+        };
+        let previous = (control.dispose_op == png::DisposeOp::Previous).then(|| canvas.clone());
+        let samples = output.color_type.samples();
+        for (sub_index, index) in sub_rect.pixel_indices(size) {
+            let Some(index) = index else { continue };
+            let row = sub_index / sub_rect.width as usize;
+            let column = sub_index % sub_rect.width as usize;
+            let start = row * output.line_size + column * samples;
+            let pixel = &buffer[start..start + samples];
+            let color = match samples {
+                1 => [pixel[0], pixel[0], pixel[0], 255],
+                2 => [pixel[0], pixel[0], pixel[0], pixel[1]],
+                3 => [pixel[0], pixel[1], pixel[2], 255],
+                _ => [pixel[0], pixel[1], pixel[2], pixel[3]],
+            };
+            let dst = &mut canvas[index * 4..index * 4 + 4];
+            match control.blend_op {
+                png::BlendOp::Source => dst.copy_from_slice(&color),
+                png::BlendOp::Over => blend_over(dst, color),
+            }
+        }
+        let delay_den = if control.delay_den == 0 { 100 } else { control.delay_den };
+        let duration = Duration::from_secs_f64(control.delay_num as f64 / delay_den as f64);
+        frames.push((canvas.clone(), duration));
+        match control.dispose_op {
+            png::DisposeOp::None => {},
+            png::DisposeOp::Background => sub_rect.clear(&mut canvas, size),
+            png::DisposeOp::Previous => canvas = previous.unwrap_or(canvas),
+        }
+    }
+    Ok(DecodedFrames { size, frames, repeat })
+}
+
+/// Blends a color over an RGBA8 pixel, in straight (non-premultiplied) alpha.
+fn blend_over(dst: &mut [u8], src: [u8; 4]) {
+    let src_alpha = src[3] as u32;
+    if src_alpha == 0 { return };
+    let dst_alpha = dst[3] as u32 * (255 - src_alpha) / 255;
+    let out_alpha = src_alpha + dst_alpha;
+    for channel in 0..3 {
+        let blended = src[channel] as u32 * src_alpha + dst[channel] as u32 * dst_alpha;
+        dst[channel] = (blended / out_alpha) as u8;
+    }
+    dst[3] = out_alpha as u8;
+}
+
+/// Graphic control extension of a GIF, applying to the image that follows it.
+#[derive(Copy, Clone)]
+struct GifControl {
+    disposal: u8,
+    delay: Duration,
+    transparent_index: Option<u8>,
+}
+
+impl Default for GifControl {
+    fn default() -> Self {
+        Self { disposal: 0, delay: GIF_DEFAULT_DELAY, transparent_index: None }
+    }
+}
+
+fn decode_gif(bytes: &[u8]) -> Result<DecodedFrames, AnimatedImageLoaderError> {
+    let mut gif = ByteReader::new(bytes);
+    gif.skip(6)?;
+    let size = UVec2::new(gif.word()? as u32, gif.word()? as u32);
+    let flags = gif.byte()?;
+    gif.skip(2)?;
+    let global_palette = match flags & 0x80 != 0 {
+        true => gif.take_slice(3 << ((flags & 7) + 1))?,
+        false => &[],
+    };
+
+    let mut canvas = vec![0; (size.x * size.y * 4) as usize];
+    let mut frames = Vec::new();
+    let mut repeat = 1;
+    let mut control = GifControl::default();
+    loop {
+        match gif.byte()? {
+            GIF_EXTENSION => match gif.byte()? {
+                GIF_GRAPHIC_CONTROL => {
+                    let block = gif.sub_blocks()?;
+                    let mut block = ByteReader::new(&block);
+                    let flags = block.byte()?;
+                    let delay = block.word()?;
+                    let transparent_index = block.byte()?;
+                    control = GifControl {
+                        disposal: (flags >> 2) & 7,
+                        delay: match delay {
+                            0 => GIF_DEFAULT_DELAY,
+                            delay => Duration::from_millis(delay as u64 * 10),
+                        },
+                        transparent_index: (flags & 1 != 0).then_some(transparent_index),
+                    };
+                },
+                GIF_APPLICATION => {
+                    let block = gif.sub_blocks()?;
+                    // NETSCAPE2.0 looping extension: identifier, then a sub-block id of 1 and the loop count
+                    if block.starts_with(b"NETSCAPE2.0") && block.len() >= 14 && block[11] == 1 {
+                        repeat = u16::from_le_bytes([block[12], block[13]]) as u32;
+                    }
+                },
+                _ => { gif.sub_blocks()?; },
+            },
+            GIF_IMAGE => {
+                let sub_rect = SubRect {
+                    x: gif.word()? as u32,
+                    y: gif.word()? as u32,
+                    width: gif.word()? as u32,
+                    height: gif.word()? as u32,
+                };
+                let flags = gif.byte()?;
+                let palette = match flags & 0x80 != 0 {
+                    true => gif.take_slice(3 << ((flags & 7) + 1))?,
+                    false => global_palette,
+                };
+                let min_code_size = gif.byte()?;
+                let pixel_count = (sub_rect.width * sub_rect.height) as usize;
+                let mut indices = decode_lzw(&gif.sub_blocks()?, min_code_size, pixel_count)?;
+                if flags & 0x40 != 0 {
+                    indices = deinterlace(&indices, sub_rect.width as usize, sub_rect.height as usize);
+                }
+
+                let previous = (control.disposal == 3).then(|| canvas.clone());
+                for (sub_index, index) in sub_rect.pixel_indices(size) {
+                    let Some(index) = index else { continue };
+                    let color_index = indices[sub_index];
+                    if Some(color_index) == control.transparent_index { continue };
+                    let Some(color) = palette.get(color_index as usize * 3..color_index as usize * 3 + 3) else { continue };
+                    canvas[index * 4..index * 4 + 4].copy_from_slice(&[color[0], color[1], color[2], 255]);
+                }
+                frames.push((canvas.clone(), control.delay));
+                match control.disposal {
+                    2 => sub_rect.clear(&mut canvas, size),
+                    3 => canvas = previous.unwrap_or(canvas),
+                    _ => {},
+                }
+                control = GifControl::default();
+            },
+            GIF_TRAILER => break,
+            _ => return Err(AnimatedImageLoaderError::invalid("unknown GIF block")),
+        }
+    }
+    Ok(DecodedFrames { size, frames, repeat })
+}
+
+/// Color indices of a GIF image, from its LZW compressed data.
+/// Images with missing data are padded with the first color, rather than failing to load.
+fn decode_lzw(data: &[u8], min_code_size: u8, pixel_count: usize) -> Result<Vec<u8>, AnimatedImageLoaderError> {
+    if !(1..=11).contains(&min_code_size) {
+        return Err(AnimatedImageLoaderError::invalid("invalid LZW code size"));
+    }
+    let clear_code = 1u16 << min_code_size;
+    let end_code = clear_code + 1;
+    // Each code is a prefix code followed by a byte, down to single byte codes
+    let mut prefix = [0u16; 4096];
+    let mut suffix = [0u8; 4096];
+    let mut first = [0u8; 4096];
+    let mut length = [0u16; 4096];
+    for code in 0..clear_code {
+        suffix[code as usize] = code as u8;
+        first[code as usize] = code as u8;
+        length[code as usize] = 1;
+    }
+
+    let mut indices = Vec::with_capacity(pixel_count);
+    let mut code_size = min_code_size + 1;
+    let mut next_code = end_code + 1;
+    let mut previous: Option<u16> = None;
+    let (mut bits, mut bit_count, mut position) = (0u32, 0u8, 0);
+    while indices.len() < pixel_count {
+        while bit_count < code_size && position < data.len() {
+            bits |= (data[position] as u32) << bit_count;
+            bit_count += 8;
+            position += 1;
+        }
+        if bit_count < code_size { break };
+        let code = (bits & ((1 << code_size) - 1)) as u16;
+        bits >>= code_size;
+        bit_count -= code_size;
+
+        if code == clear_code {
+            code_size = min_code_size + 1;
+            next_code = end_code + 1;
+            previous = None;
+            continue;
+        }
+        if code == end_code { break };
+        let new_entry = match (previous, code < next_code) {
+            (Some(previous), true) => Some((previous, first[code as usize])),
+            (Some(previous), false) if code == next_code => Some((previous, first[previous as usize])),
+            (None, true) => None,
+            _ => return Err(AnimatedImageLoaderError::invalid("invalid LZW code")),
+        };
+        if let Some((previous, byte)) = new_entry {
+            if next_code < 4096 {
+                let next = next_code as usize;
+                prefix[next] = previous;
+                suffix[next] = byte;
+                first[next] = first[previous as usize];
+                length[next] = length[previous as usize] + 1;
+                next_code += 1;
+                if next_code == 1 << code_size && code_size < 12 {
+                    code_size += 1;
+                }
+            }
+        }
+
+        let start = indices.len();
+        indices.resize(start + length[code as usize] as usize, 0);
+        let mut entry = code as usize;
+        for index in (start..indices.len()).rev() {
+            indices[index] = suffix[entry];
+            entry = prefix[entry] as usize;
+        }
+        previous = Some(code);
+    }
+    indices.resize(pixel_count, 0);
+    Ok(indices)
+}
+
+/// Rows of an interlaced GIF image, in top to bottom order.
+fn deinterlace(indices: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut rows = indices.chunks_exact(width.max(1));
+    let mut deinterlaced = vec![0; indices.len()];
+    for (start, step) in [(0, 8), (4, 8), (2, 4), (1, 2)] {
+        for y in (start..height).step_by(step) {
+            let Some(row) = rows.next() else { break };
+            deinterlaced[y * width..(y + 1) * width].copy_from_slice(row);
+        }
+    }
+    deinterlaced
+}
+
+/// Reads the little-endian values GIF files are made of.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take_slice(&mut self, count: usize) -> Result<&'a [u8], AnimatedImageLoaderError> {
+        if self.bytes.len() < count {
+            return Err(AnimatedImageLoaderError::invalid("unexpected end of file"));
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), AnimatedImageLoaderError> {
+        self.take_slice(count).map(|_| ())
+    }
+
+    fn byte(&mut self) -> Result<u8, AnimatedImageLoaderError> {
+        Ok(self.take_slice(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<u16, AnimatedImageLoaderError> {
+        let bytes = self.take_slice(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Data of consecutive length-prefixed sub-blocks, up to the empty block ending them.
+    fn sub_blocks(&mut self) -> Result<Vec<u8>, AnimatedImageLoaderError> {
+        let mut data = Vec::new();
+        loop {
+            let len = self.byte()? as usize;
+            if len == 0 { return Ok(data) };
+            data.extend_from_slice(self.take_slice(len)?);
+        }
+    }
+}
+
+/// Error while loading an [`AnimatedImage`].
+#[derive(Debug)]
+pub enum AnimatedImageLoaderError {
+    Io(std::io::Error),
+    Png(png::DecodingError),
+    /// The file is malformed, or uses a feature that isn't supported.
+    Invalid(String),
+}
+
+impl AnimatedImageLoaderError {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self::Invalid(message.into())
+    }
+}
+
+impl std::fmt::Display for AnimatedImageLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read animated image: {err}"),
+            Self::Png(err) => write!(f, "invalid animated PNG: {err}"),
+            Self::Invalid(message) => write!(f, "invalid animated image: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for AnimatedImageLoaderError {}
+
+impl From<std::io::Error> for AnimatedImageLoaderError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<png::DecodingError> for AnimatedImageLoaderError {
+    fn from(err: png::DecodingError) -> Self {
+        Self::Png(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::test_utils::{fixture_app, load_fixture};
+
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    /// Loads one of the fixtures animating the same 4x4 frames:
+    /// - a red frame, kept.
+    /// - a green square in the top left corner, cleared to the background once shown.
+    /// - a blue square in the bottom right corner, reverted to the previous frame once shown.
+    /// - a green pixel in the top right corner.
+    fn load_flicker(extension: &str) -> (Vec<ClipFrame>, u32, Vec<Vec<[u8; 4]>>) {
+        let mut app = fixture_app("animated_image");
+        app.add_plugins(AnimatedImagePlugin);
+        let handle = load_fixture::<AnimatedImage>(&mut app, &format!("flicker.{extension}"));
+        let animated_image = app.world().resource::<Assets<AnimatedImage>>().get(&handle).unwrap();
+        assert_eq!(animated_image.frame_size, UVec2::splat(4));
+        let image = app.world().resource::<Assets<Image>>().get(&animated_image.image).unwrap();
+        let width = image.width() as usize;
+        let pixels = animated_image.frames
+            .iter()
+            .map(|frame| {
+                let (x, y) = (frame.rect.min.x as usize, frame.rect.min.y as usize);
+                (0..16)
+                    .map(|i| {
+                        let start = ((y + i / 4) * width + x + i % 4) * 4;
+                        image.data[start..start + 4].try_into().unwrap()
+                    })
+                    .collect()
+            })
+            .collect();
+        (animated_image.frames.clone(), animated_image.repeat, pixels)
+    }
+
+    /// Pixels of the frames of the flicker fixtures, row by row.
+    fn flicker_pixels() -> Vec<Vec<[u8; 4]>> {
+        let red_with = |pixels: &[(usize, usize, [u8; 4])]| {
+            let mut frame = vec![RED; 16];
+            for &(x, y, color) in pixels {
+                frame[y * 4 + x] = color;
+            }
+            frame
+        };
+        let top_left = |color| [(0, 0, color), (1, 0, color), (0, 1, color), (1, 1, color)];
+        let bottom_right = [(2, 2, BLUE), (3, 2, BLUE), (2, 3, BLUE), (3, 3, BLUE)];
+        vec![
+            red_with(&[]),
+            red_with(&top_left(GREEN)),
+            red_with(&[top_left(CLEAR), bottom_right].concat()),
+            red_with(&[top_left(CLEAR).as_slice(), &[(3, 0, GREEN)]].concat()),
+        ]
+    }
+
+    fn durations(frames: &[ClipFrame]) -> Vec<u128> {
+        frames.iter().map(|frame| frame.duration.as_millis()).collect()
+    }
+
+    #[test]
+    fn gif_frames_are_composited_with_their_delays() {
+        let (frames, repeat, pixels) = load_flicker("gif");
+        assert_eq!(durations(&frames), vec![100, 200, 100, 50]);
+        assert_eq!(repeat, 3);
+        assert_eq!(pixels, flicker_pixels());
+    }
+
+    #[test]
+    fn apng_frames_are_composited_with_their_delays() {
+        let (frames, repeat, pixels) = load_flicker("apng");
+        assert_eq!(durations(&frames), vec![100, 200, 50, 50]);
+        assert_eq!(repeat, 0);
+        assert_eq!(pixels, flicker_pixels());
+    }
+}
@@ -0,0 +1,500 @@
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::prelude::*;
+use bevy_math::Rect;
+use bevy_reflect::prelude::*;
+use bevy_time::prelude::*;
+use bevy_utils::HashMap;
+
+use crate::Sprite3d;
+
+/// Sequence of frames a sprite animates through, ie: a walk cycle.
+/// Each frame has its own duration, so clips imported from tools like Aseprite keep their intended timing.
+#[derive(Asset, Reflect, Clone, PartialEq, Default, Debug)]
+pub struct Sprite3dClip {
+    pub frames: Vec<ClipFrame>,
+    pub markers: Vec<ClipMarker>,
+}
+
+impl Sprite3dClip {
+    pub fn new(frames: Vec<ClipFrame>) -> Self {
+        Self { frames, markers: Vec::new() }
+    }
+
+    /// Adds a marker, sending a [`Sprite3dMarkerReached`] event whenever the frame becomes active.
+    pub fn with_marker(mut self, frame: usize, name: impl Into<String>) -> Self {
+        self.markers.push(ClipMarker { frame, name: name.into() });
+        self
+    }
+
+    /// Markers on a frame.
+    pub fn markers_at(&self, frame: usize) -> impl Iterator<Item = &ClipMarker> {
+        self.markers.iter().filter(move |marker| marker.frame == frame)
+    }
+
+    /// Clip whose frames all last the same time, at a given number of frames per second.
+    pub fn from_fps(rects: impl IntoIterator<Item = Rect>, fps: f32) -> Self {
+        let duration = Duration::from_secs_f32(1.0 / fps);
+        Self::new(rects.into_iter().map(|rect| ClipFrame { rect, duration }).collect())
+    }
+
+    /// Total duration of the frames.
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+}
+
+/// Frame of a [`Sprite3dClip`].
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct ClipFrame {
+    /// Region of the texture showing the frame, in pixels. Becomes the [`Sprite3d::rect`] of animated sprites.
+    pub rect: Rect,
+    pub duration: Duration,
+}
+
+/// Named frame of a [`Sprite3dClip`], ie: "hit" or "footstep", to synchronize gameplay and effects with animations.
+#[derive(Reflect, Clone, PartialEq, Debug)]
+pub struct ClipMarker {
+    pub frame: usize,
+    pub name: String,
+}
+
+/// Sent when a sprite's animation reaches a frame with a [`ClipMarker`], including the first frame
+/// when the animation starts.
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct Sprite3dMarkerReached {
+    pub entity: Entity,
+    pub name: String,
+    pub frame: usize,
+}
+
+/// Pause and speed of sprite animations, ie: for pausing the game or slow motion effects.
+/// Applies to [`Sprite3dAnimation`]s, [`Sprite3dCrossfade`]s and [`Sprite3dFade`](crate::Sprite3dFade)s.
+#[derive(Resource, Reflect, Clone, PartialEq, Debug)]
+#[reflect(Resource)]
+pub struct Sprite3dAnimationTime {
+    pub paused: bool,
+    /// Speed of all animations. 1 for real time.
+    pub scale: f32,
+    /// Speed of the animations of sprites in a [`Sprite3dAnimationGroup`], on top of `scale`.
+    /// Groups without a scale play at 1.
+    pub group_scales: HashMap<u32, f32>,
+}
+
+impl Default for Sprite3dAnimationTime {
+    fn default() -> Self {
+        Self { paused: false, scale: 1.0, group_scales: HashMap::new() }
+    }
+}
+
+impl Sprite3dAnimationTime {
+    pub fn set_group_scale(&mut self, group: u32, scale: f32) {
+        self.group_scales.insert(group, scale);
+    }
+
+    /// Time animations of a group advance by, given the time elapsed.
+    pub fn delta(&self, delta: Duration, group: Option<&Sprite3dAnimationGroup>) -> Duration {
+        if self.paused { return Duration::ZERO };
+        let group_scale = group.and_then(|group| self.group_scales.get(&group.0)).copied().unwrap_or(1.0);
+        delta.mul_f32((self.scale * group_scale).max(0.0))
+    }
+}
+
+/// Group the animations of a sprite belong to, so that they can be sped up, slowed down or frozen together,
+/// ie: everything but the player, using [`Sprite3dAnimationTime::group_scales`].
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+#[reflect(Component)]
+pub struct Sprite3dAnimationGroup(pub u32);
+
+/// Sent when a sprite's animation plays its last frame to the end, in the modes that finish.
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dAnimationFinished {
+    pub entity: Entity,
+}
+
+/// How a [`Sprite3dAnimation`] steps through the frames of its clip.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum PlaybackMode {
+    /// Plays the frames forward, over and over. Never finishes.
+    #[default]
+    Loop,
+    /// Plays the frames forward once, then finishes and holds the last frame.
+    Forward,
+    /// Plays the frames backward once, starting from the last frame, then finishes and holds the first frame.
+    Reverse,
+    /// Plays the frames backward, starting from the last frame, over and over. Never finishes.
+    LoopReverse,
+    /// Plays the frames forward and backward, over and over, without repeating the frames at either end.
+    /// Never finishes.
+    PingPong,
+}
+
+/// Despawns a sprite along with its descendants once its [`Sprite3dAnimation`] finishes, ie: for explosions and
+/// other one-shot effects.
+/// The sprite is despawned on the update after [`Sprite3dAnimationFinished`] is sent, so that systems reading the
+/// event can still use it.
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+#[reflect(Component)]
+pub struct Sprite3dDespawnOnFinish;
+
+/// Plays a [`Sprite3dClip`] on a sprite.
+/// The playback state is reflected, so that animations saved in scenes resume where they left off when loaded.
+/// Requires the [`Sprite3dAnimationPlugin`].
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+#[reflect(Component)]
+pub struct Sprite3dAnimation {
+    pub clip: Handle<Sprite3dClip>,
+    pub mode: PlaybackMode,
+    /// Index of the frame currently shown.
+    pub frame: usize,
+    /// Time the current frame has been shown for.
+    pub elapsed: Duration,
+    /// If true, a [`PlaybackMode::PingPong`] animation is on its way back.
+    pub backward: bool,
+    /// If true, the animation reached its end and stopped. See [`PlaybackMode`].
+    pub finished: bool,
+    /// Time the sprite takes to blend from its current frame when switching to another clip,
+    /// using a [`Sprite3dCrossfade`]. Zero to switch instantly.
+    pub crossfade: Duration,
+    /// If true, the clip started playing, and its first frame was reached.
+    /// Kept when saved, so that animations loaded from scenes don't start over.
+    started: bool,
+    /// Clip that was playing during the last update, to detect switches.
+    #[reflect(ignore)]
+    playing: Option<AssetId<Sprite3dClip>>,
+}
+
+impl Sprite3dAnimation {
+    pub fn new(clip: Handle<Sprite3dClip>) -> Self {
+        Self {
+            clip,
+            mode: PlaybackMode::default(),
+            frame: 0,
+            elapsed: Duration::ZERO,
+            backward: false,
+            finished: false,
+            crossfade: Duration::ZERO,
+            started: false,
+            playing: None,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: PlaybackMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_crossfade(mut self, crossfade: Duration) -> Self {
+        self.crossfade = crossfade;
+        self
+    }
+
+    /// Switches to another clip, from its start. Does nothing if the clip is already playing.
+    pub fn play(&mut self, clip: Handle<Sprite3dClip>) {
+        if clip == self.clip { return };
+        self.clip = clip;
+        self.rewind();
+    }
+
+    /// Plays the clip again from its start, ie: after it finished.
+    pub fn restart(&mut self) {
+        self.rewind();
+        self.playing = None;
+    }
+
+    fn rewind(&mut self) {
+        self.frame = 0;
+        self.elapsed = Duration::ZERO;
+        self.backward = false;
+        self.finished = false;
+        self.started = false;
+    }
+
+    /// Frame the clip starts from.
+    fn first_frame(&self, frame_count: usize) -> usize {
+        match self.mode {
+            PlaybackMode::Reverse | PlaybackMode::LoopReverse => frame_count - 1,
+            _ => 0,
+        }
+    }
+
+    /// Frame that follows the current one, or None if the animation finishes instead.
+    fn next_frame(&mut self, frame_count: usize) -> Option<usize> {
+        let last = frame_count - 1;
+        match self.mode {
+            PlaybackMode::Loop => Some((self.frame + 1) % frame_count),
+            PlaybackMode::Forward => (self.frame < last).then_some(self.frame + 1),
+            PlaybackMode::Reverse => self.frame.checked_sub(1),
+            PlaybackMode::LoopReverse => Some(self.frame.checked_sub(1).unwrap_or(last)),
+            PlaybackMode::PingPong if last == 0 => Some(0),
+            PlaybackMode::PingPong => {
+                if self.frame == 0 { self.backward = false };
+                if self.frame == last { self.backward = true };
+                Some(if self.backward { self.frame - 1 } else { self.frame + 1 })
+            },
+        }
+    }
+}
+
+/// Blends a sprite in from another frame, which is rendered as a second quad behind the sprite
+/// with the complementary alpha, so that state changes on large sprites don't pop.
+/// Inserted when a [`Sprite3dAnimation`] with a crossfade switches clips, and removed once complete.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+#[reflect(Component)]
+pub struct Sprite3dCrossfade {
+    /// Region of the texture the sprite blends from, in pixels. If None, the whole texture.
+    pub from: Option<Rect>,
+    pub duration: Duration,
+    /// Time since the crossfade started.
+    pub elapsed: Duration,
+}
+
+impl Sprite3dCrossfade {
+    pub fn new(duration: Duration, from: Option<Rect>) -> Self {
+        Self { from, duration, elapsed: Duration::ZERO }
+    }
+
+    /// Progress of the crossfade, from 0 to 1. The alpha the sprite is rendered with, on top of its color.
+    pub fn ratio(&self) -> f32 {
+        match self.duration.is_zero() {
+            true => 1.0,
+            false => (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Sprite blended from, rendered behind the sprite with the complementary alpha.
+    pub(crate) fn blended_sprite(&self, sprite: &Sprite3d) -> Sprite3d {
+        Sprite3d { rect: self.from, uv_rect: None, ..sprite.clone() }
+    }
+}
+
+/// Plays [`Sprite3dAnimation`]s and [`Sprite3dCrossfade`]s.
+pub struct Sprite3dAnimationPlugin;
+
+impl Plugin for Sprite3dAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        init_clip_assets(app);
+        app.init_resource::<Sprite3dAnimationTime>();
+        app.register_type::<Sprite3dAnimation>();
+        app.register_type::<Sprite3dCrossfade>();
+        app.register_type::<Sprite3dAnimationGroup>();
+        app.register_type::<Sprite3dAnimationTime>();
+        app.register_type::<Sprite3dDespawnOnFinish>();
+        app.add_event::<Sprite3dMarkerReached>();
+        app.add_event::<Sprite3dAnimationFinished>();
+        app.add_systems(Update, (animate_sprites, crossfade_sprites));
+        app.add_systems(Update, despawn_finished_sprites.before(animate_sprites));
+    }
+}
+
+/// Registers [`Sprite3dClip`] assets, unless another plugin already did, ie: a loader of clips.
+pub(crate) fn init_clip_assets(app: &mut App) {
+    if !app.world().contains_resource::<Assets<Sprite3dClip>>() {
+        app.init_asset::<Sprite3dClip>();
+    }
+}
+
+pub(crate) fn animate_sprites(
+    mut commands: Commands,
+    mut sprites: Query<(Entity, &mut Sprite3d, &mut Sprite3dAnimation, Option<&Sprite3dAnimationGroup>)>,
+    clips: Res<Assets<Sprite3dClip>>,
+    time: Res<Time>,
+    animation_time: Res<Sprite3dAnimationTime>,
+    mut markers: EventWriter<Sprite3dMarkerReached>,
+    mut finished: EventWriter<Sprite3dAnimationFinished>,
+) {
+    for (entity, mut sprite, mut animation, group) in &mut sprites {
+        let Some(clip) = clips.get(&animation.clip) else { continue };
+        if clip.frames.is_empty() { continue };
+        let mut reach = |frame: usize| {
+            markers.send_batch(clip.markers_at(frame).map(|marker| Sprite3dMarkerReached {
+                entity,
+                name: marker.name.clone(),
+                frame,
+            }));
+        };
+        let frame_count = clip.frames.len();
+        let animation = animation.as_mut();
+        if animation.playing.is_some_and(|playing| playing != animation.clip.id()) {
+            if !animation.crossfade.is_zero() {
+                commands.entity(entity).insert(Sprite3dCrossfade::new(animation.crossfade, sprite.rect));
+            }
+            animation.started = false;
+        }
+        animation.playing = Some(animation.clip.id());
+        if !animation.started {
+            animation.frame = animation.first_frame(frame_count);
+            animation.started = true;
+            reach(animation.frame);
+        }
+        animation.frame %= frame_count;
+        if !animation.finished {
+            animation.elapsed += animation_time.delta(time.delta(), group);
+        }
+
+        // Looping clips made only of zero-length frames would never stop advancing
+        let is_looping = matches!(
+            animation.mode,
+            PlaybackMode::Loop | PlaybackMode::LoopReverse | PlaybackMode::PingPong
+        );
+        let can_advance = !is_looping || !clip.duration().is_zero();
+        while can_advance
+            && !animation.finished
+            && animation.elapsed >= clip.frames[animation.frame].duration
+        {
+            animation.elapsed -= clip.frames[animation.frame].duration;
+            match animation.next_frame(frame_count) {
+                Some(frame) => {
+                    animation.frame = frame;
+                    reach(frame);
+                },
+                None => {
+                    animation.elapsed = clip.frames[animation.frame].duration;
+                    animation.finished = true;
+                    finished.send(Sprite3dAnimationFinished { entity });
+                },
+            }
+        }
+        let rect = Some(clip.frames[animation.frame].rect);
+        if sprite.rect != rect {
+            sprite.rect = rect;
+        }
+    }
+}
+
+pub(crate) fn despawn_finished_sprites(
+    mut commands: Commands,
+    sprites: Query<(Entity, &Sprite3dAnimation), With<Sprite3dDespawnOnFinish>>,
+) {
+    for (entity, animation) in &sprites {
+        if animation.finished {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub(crate) fn crossfade_sprites(
+    mut commands: Commands,
+    mut sprites: Query<(Entity, &mut Sprite3d, &mut Sprite3dCrossfade, Option<&Sprite3dAnimationGroup>)>,
+    time: Res<Time>,
+    animation_time: Res<Sprite3dAnimationTime>,
+) {
+    for (entity, mut sprite, mut crossfade, group) in &mut sprites {
+        crossfade.elapsed += animation_time.delta(time.delta(), group);
+        if !crossfade.is_complete() { continue };
+        commands.entity(entity).remove::<Sprite3dCrossfade>();
+
+        // Rebuilds the sprite without the quad it blended from
+        sprite.set_changed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+
+    use crate::test_utils::test_app;
+    use crate::*;
+
+    /// Frames a clip steps through after its first one, until it finishes or `steps` frames were played.
+    fn frames(mode: PlaybackMode, frame_count: usize, steps: usize) -> Vec<usize> {
+        let mut animation = Sprite3dAnimation::new(Handle::default()).with_mode(mode);
+        animation.frame = animation.first_frame(frame_count);
+        let mut frames = vec![animation.frame];
+        while frames.len() <= steps {
+            let Some(frame) = animation.next_frame(frame_count) else { break };
+            animation.frame = frame;
+            frames.push(frame);
+        }
+        frames
+    }
+
+    #[test]
+    fn frames_are_stepped_through_in_order() {
+        assert_eq!(frames(PlaybackMode::Loop, 3, 5), vec![0, 1, 2, 0, 1, 2]);
+        assert_eq!(frames(PlaybackMode::Forward, 3, 5), vec![0, 1, 2]);
+        assert_eq!(frames(PlaybackMode::Reverse, 3, 5), vec![2, 1, 0]);
+        assert_eq!(frames(PlaybackMode::LoopReverse, 3, 5), vec![2, 1, 0, 2, 1, 0]);
+        assert_eq!(frames(PlaybackMode::PingPong, 3, 6), vec![0, 1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn short_clips_are_stepped_through_in_order() {
+        assert_eq!(frames(PlaybackMode::PingPong, 1, 3), vec![0, 0, 0, 0]);
+        assert_eq!(frames(PlaybackMode::PingPong, 2, 4), vec![0, 1, 0, 1, 0]);
+        assert_eq!(frames(PlaybackMode::Loop, 1, 2), vec![0, 0, 0]);
+        assert_eq!(frames(PlaybackMode::LoopReverse, 2, 3), vec![1, 0, 1, 0]);
+        assert_eq!(frames(PlaybackMode::Forward, 1, 2), vec![0]);
+        assert_eq!(frames(PlaybackMode::Reverse, 2, 3), vec![1, 0]);
+    }
+
+    /// App where each update lasts as long as a frame of the clips spawned with [`spawn_animation`].
+    fn animation_app() -> App {
+        let mut app = test_app(Sprite3dPlugin::default());
+        app.add_plugins(Sprite3dAnimationPlugin);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+        app
+    }
+
+    /// Sprite playing a clip of two 100ms frames.
+    fn spawn_animation(app: &mut App, mode: PlaybackMode) -> Entity {
+        let rects = [Rect::new(0.0, 0.0, 8.0, 8.0), Rect::new(8.0, 0.0, 16.0, 8.0)];
+        let clip = app.world_mut().resource_mut::<Assets<Sprite3dClip>>().add(Sprite3dClip::from_fps(rects, 10.0));
+        app.world_mut().spawn((Sprite3d::default(), Sprite3dAnimation::new(clip).with_mode(mode))).id()
+    }
+
+    /// Finished events sent since the last call.
+    fn finished_events(app: &mut App) -> Vec<Entity> {
+        let mut events = app.world_mut().resource_mut::<Events<Sprite3dAnimationFinished>>();
+        events.drain().map(|event| event.entity).collect()
+    }
+
+    #[test]
+    fn only_modes_that_play_once_finish() {
+        let mut app = animation_app();
+        let looping = [PlaybackMode::Loop, PlaybackMode::LoopReverse, PlaybackMode::PingPong]
+            .map(|mode| spawn_animation(&mut app, mode));
+        let forward = spawn_animation(&mut app, PlaybackMode::Forward);
+        let reverse = spawn_animation(&mut app, PlaybackMode::Reverse);
+        let mut finished = Vec::new();
+        for _ in 0..6 {
+            app.update();
+            finished.extend(finished_events(&mut app));
+        }
+
+        finished.sort();
+        assert_eq!(finished, vec![forward, reverse]);
+        for entity in looping {
+            assert!(!app.world().get::<Sprite3dAnimation>(entity).unwrap().finished);
+        }
+        let rect = |entity| app.world().get::<Sprite3d>(entity).unwrap().rect;
+        assert_eq!(rect(forward), Some(Rect::new(8.0, 0.0, 16.0, 8.0)));
+        assert_eq!(rect(reverse), Some(Rect::new(0.0, 0.0, 8.0, 8.0)));
+    }
+
+    #[test]
+    fn sprites_are_despawned_after_finishing_when_asked_to() {
+        let mut app = animation_app();
+        let kept = spawn_animation(&mut app, PlaybackMode::Forward);
+        let despawned = spawn_animation(&mut app, PlaybackMode::Forward);
+        app.world_mut().entity_mut(despawned).insert(Sprite3dDespawnOnFinish);
+        while finished_events(&mut app).is_empty() {
+            app.update();
+        }
+        assert!(app.world().get_entity(despawned).is_ok());
+
+        app.update();
+        assert!(app.world().get_entity(despawned).is_err());
+        assert!(app.world().get_entity(kept).is_ok());
+    }
+}
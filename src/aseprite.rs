@@ -0,0 +1,635 @@
+use std::io::Read;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_asset::io::Reader;
+use bevy_asset::{prelude::*, AssetLoader, LoadContext, RenderAssetUsages};
+use bevy_image::prelude::*;
+use bevy_math::{Rect, UVec2, Vec2};
+use bevy_reflect::prelude::*;
+use bevy_render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::{ClipFrame, Sprite3dClip};
+
+const HEADER_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+const CHUNK_OLD_PALETTE: u16 = 0x0004;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+const CHUNK_SLICE: u16 = 0x2022;
+
+/// Loads Aseprite files (`.ase`, `.aseprite`) directly, without exporting them first.
+pub struct AsepritePlugin;
+
+impl Plugin for AsepritePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Aseprite>();
+        app.register_asset_loader(AsepriteLoader);
+    }
+}
+
+/// Sprite sheet loaded from an Aseprite file.
+/// Frames are flattened (visible layers blended together) and laid out left to right in a single image,
+/// which is also available as the `image` labeled asset.
+/// Use [`Aseprite::frame_rect`] as the [`Sprite3d::rect`](crate::Sprite3d::rect) of a sprite showing a frame.
+#[derive(Asset, TypePath, Debug)]
+pub struct Aseprite {
+    pub image: Handle<Image>,
+    /// Size of a frame, in pixels.
+    pub frame_size: UVec2,
+    pub frames: Vec<AsepriteFrame>,
+    pub tags: Vec<AsepriteTag>,
+    pub slices: Vec<AsepriteSlice>,
+}
+
+impl Aseprite {
+    /// Region of the image showing a frame, in pixels.
+    pub fn frame_rect(&self, index: usize) -> Option<Rect> {
+        self.frames.get(index).map(|frame| frame.rect)
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&AsepriteTag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    pub fn slice(&self, name: &str) -> Option<&AsepriteSlice> {
+        self.slices.iter().find(|slice| slice.name == name)
+    }
+
+    /// Clip playing the frames of a tag, with their durations, in the order of the tag's direction.
+    /// Ping-pong tags play the frames back and forth, without repeating the frames at either end.
+    pub fn clip(&self, tag_name: &str) -> Option<Sprite3dClip> {
+        let tag = self.tag(tag_name)?;
+        let forward: Vec<usize> = tag.frames.clone().collect();
+        let backward: Vec<usize> = tag.frames.clone().rev().collect();
+        let inner = |frames: &[usize]| frames.iter().copied().skip(1).take(frames.len().saturating_sub(2)).collect();
+        let indices = match tag.direction {
+            AsepriteDirection::Forward => forward,
+            AsepriteDirection::Reverse => backward,
+            AsepriteDirection::PingPong => [forward, inner(&backward)].concat(),
+            AsepriteDirection::PingPongReverse => [backward, inner(&forward)].concat(),
+        };
+        let frames = indices
+            .into_iter()
+            .filter_map(|index| self.frames.get(index))
+            .map(|frame| ClipFrame { rect: frame.rect, duration: frame.duration })
+            .collect();
+        Some(Sprite3dClip::new(frames))
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AsepriteFrame {
+    /// Region of the image showing the frame, in pixels.
+    pub rect: Rect,
+    pub duration: Duration,
+}
+
+/// Named range of frames, ie: an animation.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AsepriteTag {
+    pub name: String,
+    pub frames: RangeInclusive<usize>,
+    pub direction: AsepriteDirection,
+    /// Number of times the animation plays. 0 for infinitely.
+    pub repeat: u16,
+}
+
+/// Order the frames of an [`AsepriteTag`] play in.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum AsepriteDirection {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+/// Named region of the frames, ie: a hitbox or an attachment point.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AsepriteSlice {
+    pub name: String,
+    /// Region of the slice, in pixels, relative to the top left of a frame. Taken from the slice's first key.
+    pub rect: Rect,
+    /// Center region of a 9-patch slice, relative to `rect`.
+    pub center: Option<Rect>,
+    /// Pivot of the slice, relative to `rect`.
+    pub pivot: Option<Vec2>,
+}
+
+#[derive(Default)]
+struct AsepriteLoader;
+
+impl AssetLoader for AsepriteLoader {
+    type Asset = Aseprite;
+    type Settings = ();
+    type Error = AsepriteLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Aseprite, AsepriteLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file = parse_file(&bytes)?;
+        let frame_size = UVec2::new(file.width, file.height);
+        let sheet_size = UVec2::new(file.width * file.frames.len() as u32, file.height);
+
+        let mut pixels = vec![0; (sheet_size.x * sheet_size.y * 4) as usize];
+        let mut frames = Vec::with_capacity(file.frames.len());
+        for (frame_index, frame) in file.frames.iter().enumerate() {
+            let frame_offset = UVec2::new(frame_index as u32 * file.width, 0);
+            for cel in file.layer_ordered_cels(frame_index) {
+                file.blend_cel(cel, &mut pixels, sheet_size, frame_offset);
+            }
+            frames.push(AsepriteFrame {
+                rect: Rect::from_corners(frame_offset.as_vec2(), (frame_offset + frame_size).as_vec2()),
+                duration: Duration::from_millis(frame.duration_ms as u64),
+            });
+        }
+        let image = Image::new(
+            Extent3d { width: sheet_size.x, height: sheet_size.y, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            pixels,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+
+        Ok(Aseprite {
+            image: load_context.add_labeled_asset("image".to_string(), image),
+            frame_size,
+            frames,
+            tags: file.tags,
+            slices: file.slices,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ase", "aseprite"]
+    }
+}
+
+/// Contents of an Aseprite file, before flattening.
+struct AseFile {
+    width: u32,
+    height: u32,
+    color_depth: u16,
+    transparent_index: u8,
+    layer_opacity_valid: bool,
+    palette: Vec<[u8; 4]>,
+    layers: Vec<AseLayer>,
+    frames: Vec<AseFrame>,
+    tags: Vec<AsepriteTag>,
+    slices: Vec<AsepriteSlice>,
+}
+
+struct AseLayer {
+    /// Visible, along with all of its parent groups.
+    visible: bool,
+    opacity: u8,
+    is_image: bool,
+    child_level: u16,
+}
+
+struct AseFrame {
+    duration_ms: u16,
+    cels: Vec<AseCel>,
+}
+
+#[derive(Clone)]
+struct AseCel {
+    layer: usize,
+    position: (i32, i32),
+    opacity: u8,
+    content: AseCelContent,
+}
+
+#[derive(Clone)]
+enum AseCelContent {
+    Image { width: u32, height: u32, pixels: Vec<u8> },
+    /// Same image as the cel of the same layer in another frame.
+    Linked(usize),
+    /// Tilemap cels, which aren't supported.
+    Unsupported,
+}
+
+impl AseFile {
+    /// Cels of a frame drawn from bottom to top, with linked cels resolved.
+    fn layer_ordered_cels(&self, frame_index: usize) -> Vec<AseCel> {
+        let mut cels: Vec<AseCel> = self.frames[frame_index]
+            .cels
+            .iter()
+            .filter_map(|cel| match cel.content {
+                AseCelContent::Image { .. } => Some(cel.clone()),
+                AseCelContent::Linked(linked_frame) => {
+                    let linked = self.frames.get(linked_frame)?.cels.iter().find(|other| other.layer == cel.layer)?;
+                    matches!(linked.content, AseCelContent::Image { .. }).then(|| AseCel {
+                        content: linked.content.clone(),
+                        ..cel.clone()
+                    })
+                },
+                AseCelContent::Unsupported => None,
+            })
+            .collect();
+        cels.sort_by_key(|cel| cel.layer);
+        cels
+    }
+
+    /// Blends a cel over the RGBA8 pixels of a sheet, at the offset of its frame.
+    fn blend_cel(&self, cel: AseCel, sheet: &mut [u8], sheet_size: UVec2, frame_offset: UVec2) {
+        let Some(layer) = self.layers.get(cel.layer) else { return };
+        if !layer.visible || !layer.is_image { return };
+        let AseCelContent::Image { width, height, pixels } = &cel.content else { return };
+        let layer_opacity = if self.layer_opacity_valid { layer.opacity as u32 } else { 255 };
+        let opacity = cel.opacity as u32 * layer_opacity / 255;
+        let bytes_per_pixel = (self.color_depth / 8) as usize;
+        for y in 0..*height as i32 {
+            for x in 0..*width as i32 {
+                let (frame_x, frame_y) = (cel.position.0 + x, cel.position.1 + y);
+                if frame_x < 0 || frame_y < 0 || frame_x >= self.width as i32 || frame_y >= self.height as i32 { continue };
+                let src_index = (y as usize * *width as usize + x as usize) * bytes_per_pixel;
+                let Some(src) = pixels.get(src_index..src_index + bytes_per_pixel) else { continue };
+                let [r, g, b, a] = self.pixel_color(src);
+                let src_alpha = a as u32 * opacity / 255;
+                if src_alpha == 0 { continue };
+                let dst_x = frame_offset.x + frame_x as u32;
+                let dst_y = frame_offset.y + frame_y as u32;
+                let dst_index = ((dst_y * sheet_size.x + dst_x) * 4) as usize;
+                let dst = &mut sheet[dst_index..dst_index + 4];
+                // Source-over blending, in straight (non-premultiplied) alpha
+                let dst_alpha = dst[3] as u32 * (255 - src_alpha) / 255;
+                let out_alpha = src_alpha + dst_alpha;
+                for (channel, src_channel) in [r, g, b].into_iter().enumerate() {
+                    let blended = src_channel as u32 * src_alpha + dst[channel] as u32 * dst_alpha;
+                    dst[channel] = (blended / out_alpha) as u8;
+                }
+                dst[3] = out_alpha as u8;
+            }
+        }
+    }
+
+    fn pixel_color(&self, pixel: &[u8]) -> [u8; 4] {
+        match self.color_depth {
+            32 => [pixel[0], pixel[1], pixel[2], pixel[3]],
+            16 => [pixel[0], pixel[0], pixel[0], pixel[1]],
+            _ => match pixel[0] == self.transparent_index {
+                true => [0; 4],
+                false => self.palette.get(pixel[0] as usize).copied().unwrap_or([0; 4]),
+            },
+        }
+    }
+}
+
+fn parse_file(bytes: &[u8]) -> Result<AseFile, AsepriteLoaderError> {
+    let mut header = ByteReader::new(bytes);
+    header.skip(4)?;
+    if header.word()? != HEADER_MAGIC {
+        return Err(AsepriteLoaderError::invalid("not an Aseprite file"));
+    }
+    let frame_count = header.word()?;
+    let width = header.word()? as u32;
+    let height = header.word()? as u32;
+    let color_depth = header.word()?;
+    if ![8, 16, 32].contains(&color_depth) {
+        return Err(AsepriteLoaderError::invalid("unsupported color depth"));
+    }
+    let flags = header.dword()?;
+    header.skip(2 + 4 + 4)?;
+    let transparent_index = header.byte()?;
+
+    let mut file = AseFile {
+        width,
+        height,
+        color_depth,
+        transparent_index,
+        layer_opacity_valid: flags & 1 != 0,
+        palette: Vec::new(),
+        layers: Vec::new(),
+        frames: Vec::new(),
+        tags: Vec::new(),
+        slices: Vec::new(),
+    };
+
+    let mut frame_start = 128;
+    for _ in 0..frame_count {
+        let mut frame_header = ByteReader::new(bytes.get(frame_start..).unwrap_or_default());
+        let frame_len = frame_header.dword()? as usize;
+        if frame_header.word()? != FRAME_MAGIC {
+            return Err(AsepriteLoaderError::invalid("invalid frame"));
+        }
+        let old_chunk_count = frame_header.word()?;
+        let duration_ms = frame_header.word()?;
+        frame_header.skip(2)?;
+        let chunk_count = match frame_header.dword()? {
+            0 => old_chunk_count as u32,
+            count => count,
+        };
+        let mut frame = AseFrame { duration_ms, cels: Vec::new() };
+
+        let mut chunk_start = frame_start + 16;
+        for _ in 0..chunk_count {
+            let mut chunk_header = ByteReader::new(bytes.get(chunk_start..).unwrap_or_default());
+            let chunk_len = chunk_header.dword()? as usize;
+            let chunk_type = chunk_header.word()?;
+            let chunk_data = bytes
+                .get(chunk_start + 6..chunk_start + chunk_len)
+                .ok_or_else(|| AsepriteLoaderError::invalid("truncated chunk"))?;
+            let mut chunk = ByteReader::new(chunk_data);
+            match chunk_type {
+                CHUNK_LAYER => parse_layer(&mut chunk, &mut file.layers)?,
+                CHUNK_CEL => frame.cels.push(parse_cel(&mut chunk)?),
+                CHUNK_TAGS => file.tags = parse_tags(&mut chunk)?,
+                CHUNK_PALETTE => parse_palette(&mut chunk, &mut file.palette)?,
+                CHUNK_OLD_PALETTE if file.palette.is_empty() => parse_old_palette(&mut chunk, &mut file.palette)?,
+                CHUNK_SLICE => file.slices.push(parse_slice(&mut chunk)?),
+                _ => {},
+            }
+            chunk_start += chunk_len;
+        }
+        file.frames.push(frame);
+        frame_start += frame_len;
+    }
+    Ok(file)
+}
+
+fn parse_layer(chunk: &mut ByteReader, layers: &mut Vec<AseLayer>) -> Result<(), AsepriteLoaderError> {
+    let flags = chunk.word()?;
+    let layer_type = chunk.word()?;
+    let child_level = chunk.word()?;
+    chunk.skip(2 + 2 + 2)?;
+    let opacity = chunk.byte()?;
+    // A layer is hidden if any group it's in is hidden
+    let parent_visible = layers
+        .iter()
+        .rev()
+        .find(|layer| layer.child_level < child_level)
+        .is_none_or(|parent| parent.visible);
+    layers.push(AseLayer {
+        visible: flags & 1 != 0 && parent_visible,
+        opacity,
+        is_image: layer_type == 0,
+        child_level,
+    });
+    Ok(())
+}
+
+fn parse_cel(chunk: &mut ByteReader) -> Result<AseCel, AsepriteLoaderError> {
+    let layer = chunk.word()? as usize;
+    let position = (chunk.short()? as i32, chunk.short()? as i32);
+    let opacity = chunk.byte()?;
+    let cel_type = chunk.word()?;
+    chunk.skip(2 + 5)?;
+    let content = match cel_type {
+        0 => {
+            let (width, height) = (chunk.word()? as u32, chunk.word()? as u32);
+            AseCelContent::Image { width, height, pixels: chunk.rest().to_vec() }
+        },
+        1 => AseCelContent::Linked(chunk.word()? as usize),
+        2 => {
+            let (width, height) = (chunk.word()? as u32, chunk.word()? as u32);
+            let mut pixels = Vec::new();
+            flate2::read::ZlibDecoder::new(chunk.rest()).read_to_end(&mut pixels)?;
+            AseCelContent::Image { width, height, pixels }
+        },
+        _ => AseCelContent::Unsupported,
+    };
+    Ok(AseCel { layer, position, opacity, content })
+}
+
+fn parse_tags(chunk: &mut ByteReader) -> Result<Vec<AsepriteTag>, AsepriteLoaderError> {
+    let count = chunk.word()?;
+    chunk.skip(8)?;
+    (0..count)
+        .map(|_| {
+            let from = chunk.word()? as usize;
+            let to = chunk.word()? as usize;
+            let direction = match chunk.byte()? {
+                1 => AsepriteDirection::Reverse,
+                2 => AsepriteDirection::PingPong,
+                3 => AsepriteDirection::PingPongReverse,
+                _ => AsepriteDirection::Forward,
+            };
+            let repeat = chunk.word()?;
+            chunk.skip(6 + 3 + 1)?;
+            Ok(AsepriteTag { name: chunk.string()?, frames: from..=to, direction, repeat })
+        })
+        .collect()
+}
+
+fn parse_palette(chunk: &mut ByteReader, palette: &mut Vec<[u8; 4]>) -> Result<(), AsepriteLoaderError> {
+    let size = chunk.dword()? as usize;
+    let first = chunk.dword()? as usize;
+    let last = chunk.dword()? as usize;
+    chunk.skip(8)?;
+    palette.resize(size.max(palette.len()), [0; 4]);
+    for i in first..=last {
+        let flags = chunk.word()?;
+        let color = [chunk.byte()?, chunk.byte()?, chunk.byte()?, chunk.byte()?];
+        if flags & 1 != 0 {
+            chunk.string()?;
+        }
+        if let Some(entry) = palette.get_mut(i) {
+            *entry = color;
+        }
+    }
+    Ok(())
+}
+
+fn parse_old_palette(chunk: &mut ByteReader, palette: &mut Vec<[u8; 4]>) -> Result<(), AsepriteLoaderError> {
+    let packet_count = chunk.word()?;
+    let mut index = 0;
+    for _ in 0..packet_count {
+        index += chunk.byte()? as usize;
+        let count = match chunk.byte()? {
+            0 => 256,
+            count => count as usize,
+        };
+        for _ in 0..count {
+            let color = [chunk.byte()?, chunk.byte()?, chunk.byte()?, 255];
+            if palette.len() <= index {
+                palette.resize(index + 1, [0; 4]);
+            }
+            palette[index] = color;
+            index += 1;
+        }
+    }
+    Ok(())
+}
+
+fn parse_slice(chunk: &mut ByteReader) -> Result<AsepriteSlice, AsepriteLoaderError> {
+    let key_count = chunk.dword()?;
+    let flags = chunk.dword()?;
+    chunk.skip(4)?;
+    let name = chunk.string()?;
+    if key_count == 0 {
+        return Err(AsepriteLoaderError::invalid("slice has no keys"));
+    }
+    chunk.skip(4)?;
+    let min = Vec2::new(chunk.long()? as f32, chunk.long()? as f32);
+    let size = Vec2::new(chunk.dword()? as f32, chunk.dword()? as f32);
+    let center = match flags & 1 != 0 {
+        true => {
+            let center_min = Vec2::new(chunk.long()? as f32, chunk.long()? as f32);
+            let center_size = Vec2::new(chunk.dword()? as f32, chunk.dword()? as f32);
+            Some(Rect::from_corners(center_min, center_min + center_size))
+        },
+        false => None,
+    };
+    let pivot = match flags & 2 != 0 {
+        true => Some(Vec2::new(chunk.long()? as f32, chunk.long()? as f32)),
+        false => None,
+    };
+    Ok(AsepriteSlice { name, rect: Rect::from_corners(min, min + size), center, pivot })
+}
+
+/// Reads the little-endian values Aseprite files are made of.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], AsepriteLoaderError> {
+        let Some((taken, rest)) = self.bytes.split_first_chunk::<N>() else {
+            return Err(AsepriteLoaderError::invalid("unexpected end of file"));
+        };
+        self.bytes = rest;
+        Ok(*taken)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), AsepriteLoaderError> {
+        let rest = self.bytes.get(count..).ok_or_else(|| AsepriteLoaderError::invalid("unexpected end of file"))?;
+        self.bytes = rest;
+        Ok(())
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    fn byte(&mut self) -> Result<u8, AsepriteLoaderError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn word(&mut self) -> Result<u16, AsepriteLoaderError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn short(&mut self) -> Result<i16, AsepriteLoaderError> {
+        Ok(i16::from_le_bytes(self.take()?))
+    }
+
+    fn dword(&mut self) -> Result<u32, AsepriteLoaderError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn long(&mut self) -> Result<i32, AsepriteLoaderError> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    fn string(&mut self) -> Result<String, AsepriteLoaderError> {
+        let len = self.word()? as usize;
+        let bytes = self.bytes.get(..len).ok_or_else(|| AsepriteLoaderError::invalid("unexpected end of file"))?;
+        self.bytes = &self.bytes[len..];
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+/// Error while loading an [`Aseprite`].
+#[derive(Debug)]
+pub enum AsepriteLoaderError {
+    Io(std::io::Error),
+    /// The file is malformed, or uses a feature that isn't supported.
+    Invalid(String),
+}
+
+impl AsepriteLoaderError {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self::Invalid(message.into())
+    }
+}
+
+impl std::fmt::Display for AsepriteLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read Aseprite file: {err}"),
+            Self::Invalid(message) => write!(f, "invalid Aseprite file: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for AsepriteLoaderError {}
+
+impl From<std::io::Error> for AsepriteLoaderError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::test_utils::{fixture_app, load_fixture};
+
+    /// Loads a file of 4x4 frames: a red square, then a green frame, then the same green frame linked.
+    fn load_walk(app: &mut App) -> Handle<Aseprite> {
+        app.add_plugins(AsepritePlugin);
+        load_fixture(app, "walk.aseprite")
+    }
+
+    #[test]
+    fn frames_are_laid_out_with_their_durations() {
+        let mut app = fixture_app("aseprite");
+        let handle = load_walk(&mut app);
+        let aseprite = app.world().resource::<Assets<Aseprite>>().get(&handle).unwrap();
+        assert_eq!(aseprite.frame_size, UVec2::splat(4));
+        let frames: Vec<(Rect, u64)> = aseprite.frames
+            .iter()
+            .map(|frame| (frame.rect, frame.duration.as_millis() as u64))
+            .collect();
+        assert_eq!(frames, vec![
+            (Rect::new(0.0, 0.0, 4.0, 4.0), 100),
+            (Rect::new(4.0, 0.0, 8.0, 4.0), 150),
+            (Rect::new(8.0, 0.0, 12.0, 4.0), 200),
+        ]);
+
+        let image = app.world().resource::<Assets<Image>>().get(&aseprite.image).unwrap();
+        assert_eq!(image.size(), UVec2::new(12, 4));
+        let pixel = |x: usize, y: usize| &image.data[(y * 12 + x) * 4..][..4];
+        assert_eq!(pixel(0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(1, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(4, 0), [0, 255, 0, 255]);
+        assert_eq!(pixel(11, 3), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn tags_and_slices_are_loaded() {
+        let mut app = fixture_app("aseprite");
+        let handle = load_walk(&mut app);
+        let aseprite = app.world().resource::<Assets<Aseprite>>().get(&handle).unwrap();
+        assert_eq!(aseprite.tags, vec![
+            AsepriteTag { name: "idle".into(), frames: 0..=1, direction: AsepriteDirection::Reverse, repeat: 0 },
+            AsepriteTag { name: "attack".into(), frames: 0..=2, direction: AsepriteDirection::PingPong, repeat: 3 },
+        ]);
+        let slice = aseprite.slice("hitbox").unwrap();
+        assert_eq!(slice.rect, Rect::new(1.0, 0.0, 3.0, 3.0));
+        assert_eq!(slice.pivot, Some(Vec2::new(1.0, 2.0)));
+
+        let clip_rects = |tag| -> Vec<Rect> {
+            aseprite.clip(tag).unwrap().frames.iter().map(|frame| frame.rect).collect()
+        };
+        let rects: Vec<Rect> = aseprite.frames.iter().map(|frame| frame.rect).collect();
+        assert_eq!(clip_rects("idle"), vec![rects[1], rects[0]]);
+        assert_eq!(clip_rects("attack"), vec![rects[0], rects[1], rects[2], rects[1]]);
+        assert_eq!(aseprite.clip("attack").unwrap().duration().as_millis(), 100 + 150 + 200 + 150);
+    }
+}
@@ -0,0 +1,52 @@
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_sprite::{TextureAtlas, TextureAtlasLayout};
+
+use crate::Sprite3d;
+
+/// Shows a region of a [`TextureAtlasLayout`] on a sprite, by index, ie: for sprite sheets laid out in a grid.
+/// Sets the [`Sprite3d::rect`] of the sprite, once the layout is loaded.
+/// Requires the [`Sprite3dAtlasPlugin`].
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+pub struct Sprite3dAtlas {
+    pub layout: Handle<TextureAtlasLayout>,
+    pub index: usize,
+}
+
+impl Sprite3dAtlas {
+    pub fn new(layout: Handle<TextureAtlasLayout>, index: usize) -> Self {
+        Self { layout, index }
+    }
+}
+
+impl From<TextureAtlas> for Sprite3dAtlas {
+    fn from(atlas: TextureAtlas) -> Self {
+        Self::new(atlas.layout, atlas.index)
+    }
+}
+
+/// Sets the rect of sprites to the one of their [`Sprite3dAtlas`].
+pub struct Sprite3dAtlasPlugin;
+
+impl Plugin for Sprite3dAtlasPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, sync_atlas_rects);
+    }
+}
+
+pub(crate) fn sync_atlas_rects(
+    mut sprites: Query<(&mut Sprite3d, &Sprite3dAtlas)>,
+    layouts: Option<Res<Assets<TextureAtlasLayout>>>,
+) {
+    let Some(layouts) = layouts else { return };
+    for (mut sprite, atlas) in &mut sprites {
+        let Some(layout) = layouts.get(&atlas.layout) else { continue };
+        let Some(rect) = layout.textures.get(atlas.index) else { continue };
+        let rect = Some(rect.as_rect());
+        if sprite.rect != rect {
+            sprite.rect = rect;
+        }
+    }
+}
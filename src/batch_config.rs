@@ -0,0 +1,96 @@
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_render::alpha::AlphaMode;
+use bevy_render::view::RenderLayers;
+use bevy_utils::HashMap;
+
+use crate::{MeshBatch, SizedMaterial};
+
+/// Overrides of how the batches of a single material are rendered, ie: to render foliage, overlays and characters
+/// with different settings within one [`Sprite3dPlugin`](crate::Sprite3dPlugin).
+/// Added to [`Sprite3dBatchConfigs`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Sprite3dBatchConfig {
+    /// Draw order of the batches, replacing the [`Sprite3dDrawOrder`](crate::Sprite3dDrawOrder) of their sprites.
+    pub draw_order: Option<i32>,
+    /// If false, the batches don't cast shadows.
+    pub cast_shadows: bool,
+    /// Render layers of the batches, replacing those of their sprites.
+    /// [`Sprite3dShadowOnly`](crate::Sprite3dShadowOnly) sprites stay on the shadow-only layer.
+    pub render_layers: Option<RenderLayers>,
+    /// Alpha mode the batches render with, using a copy of the material made with [`SizedMaterial::with_alpha_mode`].
+    pub alpha_mode: Option<AlphaMode>,
+}
+
+impl Default for Sprite3dBatchConfig {
+    fn default() -> Self {
+        Self {
+            draw_order: None,
+            cast_shadows: true,
+            render_layers: None,
+            alpha_mode: None,
+        }
+    }
+}
+
+impl Sprite3dBatchConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_draw_order(mut self, draw_order: i32) -> Self {
+        self.draw_order = Some(draw_order);
+        self
+    }
+
+    pub fn without_shadows(mut self) -> Self {
+        self.cast_shadows = false;
+        self
+    }
+
+    pub fn with_render_layers(mut self, render_layers: RenderLayers) -> Self {
+        self.render_layers = Some(render_layers);
+        self
+    }
+
+    pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = Some(alpha_mode);
+        self
+    }
+}
+
+/// [`Sprite3dBatchConfig`]s of the materials of a [`Sprite3dPlugin`](crate::Sprite3dPlugin), keyed by material.
+/// With [`Sprite3dPlugin::dedup_materials`](crate::Sprite3dPlugin::dedup_materials), configure the material that
+/// sprites of equal materials are batched with, which is the first one batched.
+/// Changing configs rebuilds every batch of the plugin.
+#[derive(Resource, Debug)]
+pub struct Sprite3dBatchConfigs<M: SizedMaterial>(pub HashMap<AssetId<M>, Sprite3dBatchConfig>);
+
+impl<M: SizedMaterial> Default for Sprite3dBatchConfigs<M> {
+    fn default() -> Self {
+        Self(HashMap::default())
+    }
+}
+
+impl<M: SizedMaterial> Sprite3dBatchConfigs<M> {
+    pub fn insert(&mut self, material: impl Into<AssetId<M>>, config: Sprite3dBatchConfig) {
+        self.0.insert(material.into(), config);
+    }
+
+    pub fn remove(&mut self, material: impl Into<AssetId<M>>) -> Option<Sprite3dBatchConfig> {
+        self.0.remove(&material.into())
+    }
+
+    pub fn get(&self, material: impl Into<AssetId<M>>) -> Option<&Sprite3dBatchConfig> {
+        self.0.get(&material.into())
+    }
+}
+
+// Hands changed configs to the batcher, which rebuilds its batches with them.
+pub(crate) fn sync_batch_configs<M: SizedMaterial>(
+    configs: Res<Sprite3dBatchConfigs<M>>,
+    mut mesh_batch: ResMut<MeshBatch<M>>,
+) {
+    if !configs.is_changed() { return };
+    mesh_batch.set_configs(configs.0.clone());
+}
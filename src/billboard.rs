@@ -0,0 +1,83 @@
+use bevy_ecs::prelude::*;
+use bevy_math::{EulerRot, Quat, Vec3};
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::view::{nearest_view, SpriteView};
+use crate::UpAxis;
+
+/// Turns a sprite to face the nearest camera, ie: for characters and props in 2.5D games.
+/// Replaces the rotation of the sprite when it is rendered, keeping its translation and scale.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dBillboard {
+    /// If true, the sprite only turns around the up axis, staying upright like a cardboard cutout.
+    /// Same as a `max_pitch` of 0.
+    pub upright: bool,
+    /// Maximum angle, in radians, the sprite tilts up or down towards the camera.
+    /// Keeps sprites from lying flat, and vanishing, when seen from above, ie: a max tilt of 30° towards
+    /// the overhead camera of a top-down 2.5D game.
+    pub max_pitch: f32,
+    /// Maximum angle, in radians, the sprite rolls along with the camera, if `roll_with_camera` is set.
+    pub max_roll: f32,
+    /// If true, the sprite faces the same way as the camera, rolling along with it, rather than facing its position.
+    /// Keeps sprites parallel to the screen, even near its edges.
+    pub roll_with_camera: bool,
+}
+
+impl Default for Sprite3dBillboard {
+    fn default() -> Self {
+        Self {
+            upright: false,
+            max_pitch: std::f32::consts::FRAC_PI_2,
+            max_roll: 0.0,
+            roll_with_camera: false,
+        }
+    }
+}
+
+impl Sprite3dBillboard {
+    /// Billboard turning around the up axis only.
+    pub fn upright() -> Self {
+        Self { upright: true, ..Self::default() }
+    }
+
+    pub fn with_max_pitch(mut self, max_pitch: f32) -> Self {
+        self.max_pitch = max_pitch;
+        self
+    }
+
+    /// Makes the sprite face the same way as the camera, rolling along with it up to an angle.
+    pub fn with_camera_roll(mut self, max_roll: f32) -> Self {
+        self.roll_with_camera = true;
+        self.max_roll = max_roll;
+        self
+    }
+
+    /// Transform of the sprite, turned towards the camera nearest to it, standing along `up_axis`.
+    pub(crate) fn apply(&self, sprite_transf: &GlobalTransform, views: &[SpriteView], up_axis: UpAxis) -> GlobalTransform {
+        let (scale, _, translation) = sprite_transf.to_scale_rotation_translation();
+        let Some(view) = nearest_view(views, translation.into()) else { return *sprite_transf };
+
+        // Sprites are visible looking down their -Z axis, so -Z points away from the camera.
+        // Directions are taken to a Y-up basis, where yaw turns around Y, and turned back to the up axis after.
+        let to_y_up = up_axis.rotation().inverse();
+        let (away, roll) = match self.roll_with_camera {
+            true => {
+                let (_, _, roll) = (to_y_up * view.rotation).to_euler(EulerRot::YXZ);
+                (to_y_up * Vec3::from(view.forward), roll)
+            },
+            false => (to_y_up * (translation - Vec3::from(view.position)), 0.0),
+        };
+        if away.length_squared() <= 0.0 { return *sprite_transf };
+
+        // Yaw and pitch turning -Z towards `away`, with the pitch clamped
+        let max_pitch = if self.upright { 0.0 } else { self.max_pitch.max(0.0) };
+        let yaw = (-away.x).atan2(-away.z);
+        let pitch = away.y.atan2(away.x.hypot(away.z)).clamp(-max_pitch, max_pitch);
+        let roll = roll.clamp(-self.max_roll.max(0.0), self.max_roll.max(0.0));
+        Transform::from_translation(translation)
+            .with_rotation(up_axis.rotation() * Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll))
+            .with_scale(scale)
+            .into()
+    }
+}
@@ -0,0 +1,56 @@
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_image::prelude::*;
+use bevy_math::{Quat, Rect, Vec2};
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::{sprite_size, MeshBatch, SizedMaterial, Sprite3d, SpriteMaterial3d};
+
+/// Rectangle covered by a sprite, accounting for its size, rect and anchor.
+/// Kept up to date on sprites that have this component, ie: to size physics colliders so that clickable or blocking
+/// sprites don't need hand-measured shapes. A cuboid collider with half extents `rect.half_size()` (and a thin depth),
+/// offset by `rect.center()` and turned by `rotation`, matches the sprite. With the `rapier` or `avian` features,
+/// `Collider::from(&bounds)` builds it.
+/// Remains empty until the sprite's material, and the image it depends on, are loaded.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Default, Debug)]
+pub struct Sprite3dBounds {
+    /// Rectangle in the XY plane of the sprite, once turned by `rotation`.
+    pub rect: Rect,
+    /// Rotation from the sprite's transform to the plane of `rect`, ie: with [`UpAxis::Z`](crate::UpAxis::Z), the
+    /// [`UpAxis::rotation`](crate::UpAxis::rotation) that stands sprites along Z, putting `rect` in their XZ plane.
+    pub rotation: Quat,
+}
+
+impl Sprite3dBounds {
+    /// Bounds of a sprite, given the size of its material.
+    pub fn from_sprite(sprite: &Sprite3d, sprite_mat_size: Vec2) -> Self {
+        let size = sprite_size(sprite, sprite_mat_size);
+        let center = -sprite.anchor.as_vec() * size;
+        Self { rect: Rect::from_center_size(center, size), rotation: Quat::IDENTITY }
+    }
+
+    /// Transform of the plane `rect` lies in, for a sprite with the given transform.
+    pub fn plane_transform(&self, sprite_transf: &GlobalTransform) -> GlobalTransform {
+        sprite_transf.mul_transform(Transform::from_rotation(self.rotation))
+    }
+}
+
+pub(crate) fn update_sprite_bounds<M: SizedMaterial>(
+    mut sprites: Query<(&Sprite3d, &SpriteMaterial3d<M>, &mut Sprite3dBounds)>,
+    materials: Res<Assets<M>>,
+    images: Res<Assets<Image>>,
+    mesh_batch: Res<MeshBatch<M>>,
+) {
+    for (sprite, sprite_mat_handle, mut bounds) in &mut sprites {
+        let sprite_mat_size = materials
+            .get(&sprite_mat_handle.0)
+            .and_then(|sprite_mat| sprite_mat.size(&images))
+            .or_else(|| mesh_batch.last_material_size(sprite_mat_handle.0.id()));
+        let Some(sprite_mat_size) = sprite_mat_size else { continue };
+        bounds.set_if_neq(Sprite3dBounds {
+            rotation: mesh_batch.up_axis.rotation(),
+            ..Sprite3dBounds::from_sprite(sprite, sprite_mat_size)
+        });
+    }
+}
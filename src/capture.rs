@@ -0,0 +1,87 @@
+use bevy_asset::{prelude::*, RenderAssetUsages};
+use bevy_ecs::prelude::*;
+use bevy_image::prelude::*;
+use bevy_image::TextureFormatPixelInfo;
+use bevy_math::{URect, UVec2};
+use bevy_render::render_resource::{Extent3d, TextureDimension};
+use bevy_render::view::screenshot::ScreenshotCaptured;
+
+use crate::SizedMaterial;
+
+/// Turns a [`Screenshot`](bevy_render::view::screenshot::Screenshot) into a sprite material, ie: photo mode
+/// polaroids, or impostors made at runtime.
+/// Spawned along with the screenshot, which captures a window, or the image a camera renders to. To capture a
+/// single entity, render it on its own [`RenderLayers`](bevy_render::view::RenderLayers) with a camera targeting
+/// a [`render_target_image`](crate::render_target_image), and screenshot that image.
+/// Once captured, a [`Sprite3dCaptured`] event is sent with the image and the material.
+#[derive(Component, Clone, Debug)]
+pub struct Sprite3dCapture<M: SizedMaterial> {
+    /// Material the captured material is a copy of, made with [`SizedMaterial::with_texture`].
+    pub material: M,
+    /// Region of the screenshot to keep, in pixels. If None, the whole screenshot is kept.
+    pub rect: Option<URect>,
+}
+
+impl<M: SizedMaterial> Sprite3dCapture<M> {
+    pub fn new(material: M) -> Self {
+        Self { material, rect: None }
+    }
+
+    pub fn with_rect(mut self, rect: URect) -> Self {
+        self.rect = Some(rect);
+        self
+    }
+}
+
+/// Sent when the screenshot of a [`Sprite3dCapture`] is turned into a material.
+#[derive(Event, Clone, Debug)]
+pub struct Sprite3dCaptured<M: SizedMaterial> {
+    /// Screenshot entity the capture was spawned on. Despawned by then.
+    pub entity: Entity,
+    pub image: Handle<Image>,
+    pub material: Handle<M>,
+}
+
+// Crops captured screenshots, and textures a copy of their capture's material with them.
+pub(crate) fn capture_sprite_materials<M: SizedMaterial>(
+    trigger: Trigger<ScreenshotCaptured>,
+    captures: Query<&Sprite3dCapture<M>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<M>>,
+    mut captured_events: EventWriter<Sprite3dCaptured<M>>,
+) {
+    let entity = trigger.entity();
+    let Ok(capture) = captures.get(entity) else { return };
+    let screenshot = &trigger.event().0;
+    let image = match capture.rect {
+        Some(rect) => crop_image(screenshot, rect),
+        None => screenshot.clone(),
+    };
+    let image = images.add(image);
+    let Some(material) = capture.material.with_texture(image.clone()) else { return };
+    let material = materials.add(material);
+    captured_events.send(Sprite3dCaptured { entity, image, material });
+}
+
+/// Copy of a region of an image, clamped to its bounds.
+fn crop_image(image: &Image, rect: URect) -> Image {
+    let rect = rect.intersect(URect::from_corners(UVec2::ZERO, image.size()));
+    let pixel_size = image.texture_descriptor.format.pixel_size();
+    let row_size = image.width() as usize * pixel_size;
+    let data = (rect.min.y..rect.max.y)
+        .flat_map(|y| {
+            let start = y as usize * row_size + rect.min.x as usize * pixel_size;
+            &image.data[start..start + rect.width() as usize * pixel_size]
+        })
+        .copied()
+        .collect();
+    let mut cropped = Image::new(
+        Extent3d { width: rect.width(), height: rect.height(), depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        image.texture_descriptor.format,
+        RenderAssetUsages::default(),
+    );
+    cropped.sampler = image.sampler.clone();
+    cropped
+}
@@ -0,0 +1,120 @@
+use bevy_ecs::prelude::*;
+use bevy_math::{Rect, Vec2, Vec3, Vec3A};
+use bevy_reflect::prelude::*;
+
+use crate::polygon::sub_quad;
+use crate::SpriteQuad;
+
+/// Clips a sprite against world space planes, ie: sprites emerging from spawners, doors or water surfaces.
+/// Only the parts of the sprite on the kept side of every plane are rendered. Quads are cut during batching,
+/// with their UVs adjusted to match, so the sprite's material is unaffected.
+/// Also clips the sprite's [`Sprite3dParts`](crate::Sprite3dParts), [`Sprite3dPolygon`](crate::Sprite3dPolygon)
+/// and the copies along its [`SpritePath3d`](crate::SpritePath3d).
+#[derive(Component, Reflect, Clone, PartialEq, Default, Debug)]
+pub struct Sprite3dClipRect {
+    pub planes: Vec<ClipPlane>,
+}
+
+/// World space plane of a [`Sprite3dClipRect`].
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct ClipPlane {
+    /// Any point on the plane.
+    pub point: Vec3,
+    /// Points towards the side of the plane that is kept. Doesn't need to be normalized.
+    pub normal: Vec3,
+}
+
+impl ClipPlane {
+    pub fn new(point: Vec3, normal: Vec3) -> Self {
+        Self { point, normal }
+    }
+
+    /// Signed distance of a point to the plane, scaled by the length of its normal.
+    /// Positive on the side that is kept.
+    fn distance(&self, point: Vec3A) -> f32 {
+        (point - Vec3A::from(self.point)).dot(Vec3A::from(self.normal))
+    }
+}
+
+impl Sprite3dClipRect {
+    pub fn new(planes: Vec<ClipPlane>) -> Self {
+        Self { planes }
+    }
+
+    /// Keeps the parts of the sprite within a rect of the world's XY plane, extending infinitely along Z.
+    pub fn from_rect(rect: Rect) -> Self {
+        Self::new(vec![
+            ClipPlane::new(rect.min.extend(0.0), Vec3::X),
+            ClipPlane::new(rect.min.extend(0.0), Vec3::Y),
+            ClipPlane::new(rect.max.extend(0.0), Vec3::NEG_X),
+            ClipPlane::new(rect.max.extend(0.0), Vec3::NEG_Y),
+        ])
+    }
+
+    /// Keeps the parts of the sprite above a height, ie: rising out of the ground or a water surface.
+    pub fn above(height: f32) -> Self {
+        Self::new(vec![ClipPlane::new(Vec3::Y * height, Vec3::Y)])
+    }
+
+    pub fn with_plane(mut self, point: Vec3, normal: Vec3) -> Self {
+        self.planes.push(ClipPlane::new(point, normal));
+        self
+    }
+
+    /// Parts of a quad on the kept side of every plane.
+    /// Unclipped quads are returned as-is. Clipped ones are split into a fan of quads sharing their first point,
+    /// like [`Sprite3dPolygon`](crate::Sprite3dPolygon)s.
+    pub(crate) fn clip(&self, quad: SpriteQuad) -> Vec<SpriteQuad> {
+        let corners = quad.positions.map(Vec3A::from);
+        let mut is_clipped = false;
+        for plane in &self.planes {
+            let distances = corners.map(|corner| plane.distance(corner));
+            if distances.iter().all(|&distance| distance < 0.0) {
+                return Vec::new();
+            }
+            is_clipped |= distances.iter().any(|&distance| distance < 0.0);
+        }
+        if !is_clipped {
+            return vec![quad];
+        }
+
+        // Clips the quad in quad space, where (0, 0) is its top left, and (1, 1) its bottom right.
+        // Corners are listed bl, br, tr, tl, like the quad's own, which keeps its winding.
+        let mut points = vec![Vec2::new(0.0, 1.0), Vec2::ONE, Vec2::new(1.0, 0.0), Vec2::ZERO];
+        for plane in &self.planes {
+            let [bl, br, tr, tl] = corners.map(|corner| plane.distance(corner));
+            let distance = |point: Vec2| {
+                let top = tl + (tr - tl) * point.x;
+                let bottom = bl + (br - bl) * point.x;
+                top + (bottom - top) * point.y
+            };
+            points = clip_polygon(&points, distance);
+            if points.len() < 3 {
+                return Vec::new();
+            }
+        }
+        let n = points.len();
+        (0..(n - 1) / 2)
+            .map(|k| {
+                let i = 1 + 2 * k;
+                sub_quad(&quad, [points[0], points[i], points[i + 1], points[(i + 2).min(n - 1)]])
+            })
+            .collect()
+    }
+}
+
+/// Keeps the part of a convex polygon where the distance function is positive (Sutherland-Hodgman).
+fn clip_polygon(points: &[Vec2], distance: impl Fn(Vec2) -> f32) -> Vec<Vec2> {
+    let mut clipped = Vec::with_capacity(points.len() + 1);
+    for (i, &point) in points.iter().enumerate() {
+        let next = points[(i + 1) % points.len()];
+        let (point_dist, next_dist) = (distance(point), distance(next));
+        if point_dist >= 0.0 {
+            clipped.push(point);
+        }
+        if (point_dist >= 0.0) != (next_dist >= 0.0) {
+            clipped.push(point.lerp(next, point_dist / (point_dist - next_dist)));
+        }
+    }
+    clipped
+}
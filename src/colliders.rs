@@ -0,0 +1,77 @@
+// Colliders matching the bounds of sprites: cuboids as thick as `SPRITE_COLLIDER_DEPTH`, offset by the center of
+// their rect and turned by their rotation. Insert them whenever `Sprite3dBounds` changes, so that they follow the
+// sprite's size.
+
+use bevy_math::Vec3;
+
+use crate::Sprite3dBounds;
+
+/// Thickness of the colliders of sprites, along their normal.
+pub const SPRITE_COLLIDER_DEPTH: f32 = 0.01;
+
+impl Sprite3dBounds {
+    /// Center of the collider, in the sprite's space.
+    fn collider_center(&self) -> Vec3 {
+        self.rotation * self.rect.center().extend(0.0)
+    }
+}
+
+#[cfg(feature = "rapier")]
+impl From<&Sprite3dBounds> for bevy_rapier3d::prelude::Collider {
+    fn from(bounds: &Sprite3dBounds) -> Self {
+        use bevy_rapier3d::prelude::Collider;
+
+        let half_size = bounds.rect.half_size();
+        let cuboid = Collider::cuboid(half_size.x, half_size.y, SPRITE_COLLIDER_DEPTH / 2.0);
+        Collider::compound(vec![(bounds.collider_center(), bounds.rotation, cuboid)])
+    }
+}
+
+#[cfg(feature = "avian")]
+impl From<&Sprite3dBounds> for avian3d::prelude::Collider {
+    fn from(bounds: &Sprite3dBounds) -> Self {
+        use avian3d::prelude::Collider;
+
+        let size = bounds.rect.size();
+        let cuboid = Collider::cuboid(size.x, size.y, SPRITE_COLLIDER_DEPTH);
+        Collider::compound(vec![(bounds.collider_center(), bounds.rotation, cuboid)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::sprite::Anchor;
+
+    use crate::*;
+
+    /// Bounds of a 2x4 sprite standing on its bottom edge, along Z.
+    fn standing_bounds() -> Sprite3dBounds {
+        let sprite = Sprite3d { custom_size: Some(Vec2::new(2.0, 4.0)), anchor: Anchor::BottomCenter, ..default() };
+        Sprite3dBounds { rotation: UpAxis::Z.rotation(), ..Sprite3dBounds::from_sprite(&sprite, Vec2::ONE) }
+    }
+
+    const MIN: Vec3 = Vec3::new(-1.0, -SPRITE_COLLIDER_DEPTH / 2.0, 0.0);
+    const MAX: Vec3 = Vec3::new(1.0, SPRITE_COLLIDER_DEPTH / 2.0, 4.0);
+
+    #[cfg(feature = "rapier")]
+    #[test]
+    fn rapier_colliders_cover_the_bounds() {
+        use bevy_rapier3d::parry::math::Isometry;
+        use bevy_rapier3d::prelude::Collider;
+
+        let aabb = Collider::from(&standing_bounds()).raw.compute_aabb(&Isometry::identity());
+        assert!(Vec3::from(aabb.mins).abs_diff_eq(MIN, 1e-4));
+        assert!(Vec3::from(aabb.maxs).abs_diff_eq(MAX, 1e-4));
+    }
+
+    #[cfg(feature = "avian")]
+    #[test]
+    fn avian_colliders_cover_the_bounds() {
+        use avian3d::prelude::{AnyCollider, Collider};
+
+        let aabb = Collider::from(&standing_bounds()).aabb(Vec3::ZERO, Quat::IDENTITY);
+        assert!(aabb.min.abs_diff_eq(MIN, 1e-4));
+        assert!(aabb.max.abs_diff_eq(MAX, 1e-4));
+    }
+}
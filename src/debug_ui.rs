@@ -0,0 +1,208 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_core::Name;
+use bevy_ecs::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use bevy_gizmos::config::GizmoConfigStore;
+use bevy_pbr::StandardMaterial;
+use bevy_render::prelude::*;
+use bevy_render::view::VisibleEntities;
+use bevy_time::{Real, Time};
+use bevy_utils::{HashMap, HashSet};
+
+use crate::layout_editor::{draw_sprite_layout, edit_sprite_layout, LayoutDrag};
+use crate::sorted_view::filter_view_batches;
+use crate::{refresh_batch_visibility, SizedMaterial, Sprite3d, Sprite3dBatch, Sprite3dSystems};
+
+/// Shows an egui window listing the batches of a [`Sprite3dPlugin`](crate::Sprite3dPlugin), ie: to find out why a
+/// scene has more draw calls than expected. Batches can be hidden, or isolated so that only they are rendered.
+/// With [`Sprite3dDebugUi::edit_layout`], sprites can be selected by clicking them in the viewport, and their
+/// anchor and custom size tuned by dragging their handles, ie: as a minimal in-game sprite layout editor.
+/// Adds the [`EguiPlugin`] if needed.
+pub struct Sprite3dDebugUiPlugin<M: SizedMaterial = StandardMaterial>(PhantomData<M>);
+
+impl<M: SizedMaterial> Default for Sprite3dDebugUiPlugin<M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<M: SizedMaterial> Plugin for Sprite3dDebugUiPlugin<M> {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        app.init_resource::<Sprite3dDebugUi<M>>();
+        app.add_systems(Update, (show_batch_window::<M>, edit_sprite_layout::<M>.after(show_batch_window::<M>)));
+        app.add_systems(
+            PostUpdate,
+            (
+                track_batch_rebuilds::<M>.after(Sprite3dSystems),
+                hide_debug_batches::<M>.after(refresh_batch_visibility::<M>).after(filter_view_batches::<M>),
+            ),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        if app.world().contains_resource::<GizmoConfigStore>() {
+            app.add_systems(PostUpdate, draw_sprite_layout::<M>.after(Sprite3dSystems));
+        }
+    }
+}
+
+/// State of the window of a [`Sprite3dDebugUiPlugin`].
+#[derive(Resource)]
+pub struct Sprite3dDebugUi<M: SizedMaterial = StandardMaterial> {
+    /// If false, the window isn't shown.
+    pub open: bool,
+    /// Batches that aren't rendered.
+    pub hidden: HashSet<Entity>,
+    /// If set, only this batch is rendered.
+    pub isolated: Option<Entity>,
+    /// If true, clicking a sprite selects it, and dragging its handles moves its anchor or resizes it.
+    /// The anchor handle is at the sprite's origin, and the size handle at its top right corner.
+    pub edit_layout: bool,
+    /// Sprite whose layout is being edited.
+    pub selected: Option<Entity>,
+    pub(crate) drag: Option<LayoutDrag>,
+    /// Time each batch's mesh was last rebuilt, since startup.
+    rebuilds: HashMap<Entity, Duration>,
+    marker: PhantomData<M>,
+}
+
+impl<M: SizedMaterial> Default for Sprite3dDebugUi<M> {
+    fn default() -> Self {
+        Self {
+            open: true,
+            hidden: HashSet::default(),
+            isolated: None,
+            edit_layout: false,
+            selected: None,
+            drag: None,
+            rebuilds: HashMap::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<M: SizedMaterial> Sprite3dDebugUi<M> {
+    fn is_hidden(&self, batch: Entity) -> bool {
+        self.hidden.contains(&batch) || self.isolated.is_some_and(|isolated| isolated != batch)
+    }
+}
+
+// Records when batch meshes were last modified.
+fn track_batch_rebuilds<M: SizedMaterial>(
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    batches: Query<(Entity, &Mesh3d), With<Sprite3dBatch<M>>>,
+    mut debug_ui: ResMut<Sprite3dDebugUi<M>>,
+    time: Res<Time<Real>>,
+) {
+    let rebuilt: HashSet<AssetId<Mesh>> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let now = time.elapsed();
+    for (entity, mesh) in &batches {
+        if rebuilt.contains(&mesh.id()) {
+            debug_ui.rebuilds.insert(entity, now);
+        }
+    }
+    debug_ui.rebuilds.retain(|entity, _| batches.contains(*entity));
+    debug_ui.hidden.retain(|entity| batches.contains(*entity));
+    if debug_ui.isolated.is_some_and(|entity| !batches.contains(entity)) {
+        debug_ui.isolated = None;
+    }
+}
+
+// Removes hidden batches from the entities cameras render.
+fn hide_debug_batches<M: SizedMaterial>(
+    mut views: Query<&mut VisibleEntities>,
+    debug_ui: Res<Sprite3dDebugUi<M>>,
+) {
+    if debug_ui.hidden.is_empty() && debug_ui.isolated.is_none() { return };
+    for mut visible_entities in &mut views {
+        visible_entities
+            .get_mut::<With<Mesh3d>>()
+            .retain(|entity| !debug_ui.is_hidden(*entity));
+    }
+}
+
+// Lists batches with their sprite count, vertex data size and last rebuild, with toggles to hide or isolate them.
+#[allow(clippy::type_complexity)]
+fn show_batch_window<M: SizedMaterial>(
+    mut contexts: EguiContexts,
+    batches: Query<(Entity, Option<&Name>, &Mesh3d, &Sprite3dBatch<M>)>,
+    sprites: Query<&Sprite3d>,
+    meshes: Res<Assets<Mesh>>,
+    mut debug_ui: ResMut<Sprite3dDebugUi<M>>,
+    time: Res<Time<Real>>,
+) {
+    if !debug_ui.open { return };
+    let Some(ctx) = contexts.try_ctx_mut() else { return };
+    let mut batches: Vec<_> = batches.iter().collect();
+    batches.sort_by_key(|(entity, ..)| *entity);
+    let debug_ui = &mut *debug_ui;
+    let mut open = debug_ui.open;
+    egui::Window::new(format!("Sprite3d Batches ({})", core::any::type_name::<M>()))
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label(format!("{} batches", batches.len()));
+            ui.checkbox(&mut debug_ui.edit_layout, "Edit layout");
+            if let Some(sprite) = debug_ui.selected.filter(|_| debug_ui.edit_layout).and_then(|entity| sprites.get(entity).ok()) {
+                let anchor = sprite.anchor.as_vec();
+                ui.label(format!("Anchor: ({:.3}, {:.3})", anchor.x, anchor.y));
+                match sprite.custom_size {
+                    Some(size) => ui.label(format!("Custom size: ({:.3}, {:.3})", size.x, size.y)),
+                    None => ui.label("Custom size: -"),
+                };
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("sprite3d_batches").striped(true).show(ui, |ui| {
+                    ui.strong("Batch");
+                    ui.strong("Material");
+                    ui.strong("Sprites");
+                    ui.strong("Vertex bytes");
+                    ui.strong("Last rebuild");
+                    ui.strong("Hidden");
+                    ui.strong("Isolated");
+                    ui.end_row();
+                    for (entity, name, mesh, batch) in batches {
+                        let mesh = meshes.get(mesh);
+                        // Sprites are quads of 4 vertices, and two-sided sprites count twice.
+                        let sprites = mesh.map_or(0, |mesh| mesh.count_vertices() / 4);
+                        let vertex_bytes = mesh.map_or(0, Mesh::get_vertex_buffer_size);
+                        let last_rebuild = match debug_ui.rebuilds.get(&entity) {
+                            Some(rebuild) => format!("{:.1}s ago", (time.elapsed() - *rebuild).as_secs_f32()),
+                            None => "-".to_string(),
+                        };
+                        ui.label(name.map_or_else(|| entity.to_string(), |name| name.to_string()));
+                        ui.label(batch.material.to_string());
+                        ui.label(sprites.to_string());
+                        ui.label(vertex_bytes.to_string());
+                        ui.label(last_rebuild);
+                        let mut hidden = debug_ui.hidden.contains(&entity);
+                        if ui.checkbox(&mut hidden, "").changed() {
+                            if hidden {
+                                debug_ui.hidden.insert(entity);
+                            } else {
+                                debug_ui.hidden.remove(&entity);
+                            }
+                        }
+                        let mut isolated = debug_ui.isolated == Some(entity);
+                        if ui.checkbox(&mut isolated, "").changed() {
+                            debug_ui.isolated = isolated.then_some(entity);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+    debug_ui.open = open;
+}
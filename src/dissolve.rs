@@ -0,0 +1,112 @@
+use bevy_app::prelude::*;
+use bevy_asset::{load_internal_asset, prelude::*};
+use bevy_color::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_pbr::prelude::*;
+use bevy_pbr::{ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline};
+use bevy_reflect::prelude::*;
+use bevy_render::mesh::{MeshVertexAttribute, MeshVertexBufferLayoutRef};
+use bevy_render::prelude::*;
+use bevy_render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, Shader, ShaderRef, SpecializedMeshPipelineError, VertexFormat,
+};
+
+use crate::{SpriteMaterialExtension, SpriteVertexAttributes};
+
+const DISSOLVE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x6a17_c2f9_84d3_4e0b_9f52_1b7e_a06d_38c4);
+
+/// Per-vertex dissolve amount of sprite batches.
+/// Written from [`Sprite3dDissolve`] when the material requires [`SpriteVertexAttributes::dissolve`].
+pub const ATTRIBUTE_DISSOLVE: MeshVertexAttribute =
+    MeshVertexAttribute::new("Sprite3d_Dissolve", 2_140_529_363, VertexFormat::Float32);
+
+/// How far a sprite is dissolved, from 0 (intact) to 1 (gone), ie: death and teleport effects.
+/// Written per vertex, so that sprites dissolving at different rates still share a material and a batch.
+/// Only has an effect on sprites rendered with a [`DissolveMaterial`].
+#[derive(Component, Reflect, Copy, Clone, PartialEq, PartialOrd, Default, Debug)]
+pub struct Sprite3dDissolve(pub f32);
+
+/// Material of sprites that dissolve in a noise pattern.
+pub type DissolveMaterial = ExtendedMaterial<StandardMaterial, DissolveExtension>;
+
+/// Extends a material with a shader that discards the pixels of [`Sprite3dDissolve`] sprites whose noise is below
+/// their dissolve amount, and colors the pixels about to be discarded.
+/// The noise follows the texture's UVs, so that it moves along with the sprite.
+/// The dissolve is applied in the main pass only, so shadows and prepass outputs remain whole, and isn't
+/// supported with deferred rendering.
+#[derive(Asset, AsBindGroup, Reflect, Clone, Debug)]
+pub struct DissolveExtension {
+    /// Color of the edge of the dissolved region, added to the sprite's. Usually bright, ie: embers.
+    #[uniform(100)]
+    pub edge_color: LinearRgba,
+    /// Width of the edge of the dissolved region, in dissolve amount.
+    #[uniform(101)]
+    pub edge_width: f32,
+    /// How many noise cells span the texture, on each axis. Higher values dissolve in finer grains.
+    #[uniform(102)]
+    pub noise_scale: f32,
+}
+
+impl Default for DissolveExtension {
+    fn default() -> Self {
+        Self {
+            edge_color: LinearRgba::rgb(4.0, 1.5, 0.2),
+            edge_width: 0.05,
+            noise_scale: 16.0,
+        }
+    }
+}
+
+impl MaterialExtension for DissolveExtension {
+    fn vertex_shader() -> ShaderRef {
+        DISSOLVE_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        DISSOLVE_SHADER_HANDLE.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // Prepass and shadow pipelines use their own vertex shader, which doesn't read the dissolve attribute
+        if descriptor.vertex.shader != DISSOLVE_SHADER_HANDLE {
+            return Ok(());
+        }
+        let optional_attributes = [
+            Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+            Mesh::ATTRIBUTE_UV_0.at_shader_location(2),
+            Mesh::ATTRIBUTE_UV_1.at_shader_location(3),
+            Mesh::ATTRIBUTE_COLOR.at_shader_location(5),
+        ];
+        let mut attributes = vec![Mesh::ATTRIBUTE_POSITION.at_shader_location(0)];
+        attributes.extend(optional_attributes.into_iter().filter(|attribute| layout.0.contains(attribute.id)));
+        attributes.push(ATTRIBUTE_DISSOLVE.at_shader_location(10));
+        descriptor.vertex.buffers = vec![layout.0.get_layout(&attributes)?];
+        Ok(())
+    }
+}
+
+impl SpriteMaterialExtension for DissolveExtension {
+    fn required_vertex_attributes() -> SpriteVertexAttributes {
+        SpriteVertexAttributes { dissolve: true, ..SpriteVertexAttributes::NONE }
+    }
+
+    fn vertex_colors_optional() -> bool {
+        true
+    }
+}
+
+/// Registers [`DissolveMaterial`] and its shader.
+/// Sprites using it are batched by adding a [`Sprite3dPlugin::<DissolveMaterial>`](crate::Sprite3dPlugin) as well.
+pub struct Sprite3dDissolvePlugin;
+
+impl Plugin for Sprite3dDissolvePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, DISSOLVE_SHADER_HANDLE, "dissolve.wgsl", Shader::from_wgsl);
+        app.add_plugins(MaterialPlugin::<DissolveMaterial>::default());
+    }
+}
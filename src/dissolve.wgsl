@@ -0,0 +1,154 @@
+#import bevy_pbr::{
+    mesh_functions,
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+    view_transformations::position_world_to_clip,
+}
+
+@group(2) @binding(100) var<uniform> edge_color: vec4<f32>;
+@group(2) @binding(101) var<uniform> edge_width: f32;
+@group(2) @binding(102) var<uniform> noise_scale: f32;
+
+// Same as bevy_pbr::forward_io::Vertex, plus the dissolve attribute.
+// Sprite batches have no tangents, and are never skinned nor morphed.
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+#ifdef VERTEX_NORMALS
+    @location(1) normal: vec3<f32>,
+#endif
+#ifdef VERTEX_UVS_A
+    @location(2) uv: vec2<f32>,
+#endif
+#ifdef VERTEX_UVS_B
+    @location(3) uv_b: vec2<f32>,
+#endif
+#ifdef VERTEX_COLORS
+    @location(5) color: vec4<f32>,
+#endif
+    @location(10) dissolve: f32,
+};
+
+// Same as bevy_pbr::forward_io::VertexOutput, plus the dissolve amount.
+struct DissolveVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+#ifdef VERTEX_UVS_A
+    @location(2) uv: vec2<f32>,
+#endif
+#ifdef VERTEX_UVS_B
+    @location(3) uv_b: vec2<f32>,
+#endif
+#ifdef VERTEX_COLORS
+    @location(5) color: vec4<f32>,
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    @location(6) @interpolate(flat) instance_index: u32,
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    @location(7) @interpolate(flat) visibility_range_dither: i32,
+#endif
+    @location(8) @interpolate(flat) dissolve: f32,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> DissolveVertexOutput {
+    var out: DissolveVertexOutput;
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+
+#ifdef VERTEX_NORMALS
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(vertex.normal, vertex.instance_index);
+#endif
+
+    out.world_position = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0));
+    out.position = position_world_to_clip(out.world_position.xyz);
+
+#ifdef VERTEX_UVS_A
+    out.uv = vertex.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    out.uv_b = vertex.uv_b;
+#endif
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = mesh_functions::get_visibility_range_dither_level(
+        vertex.instance_index, world_from_local[3]);
+#endif
+    out.dissolve = vertex.dissolve;
+
+    return out;
+}
+
+fn hash(cell: vec2<f32>) -> f32 {
+    return fract(sin(dot(cell, vec2(127.1, 311.7))) * 43758.5453);
+}
+
+// Value noise, from 0 to 1
+fn value_noise(point: vec2<f32>) -> f32 {
+    let cell = floor(point);
+    let t = smoothstep(vec2(0.0), vec2(1.0), fract(point));
+    let bottom = mix(hash(cell), hash(cell + vec2(1.0, 0.0)), t.x);
+    let top = mix(hash(cell + vec2(0.0, 1.0)), hash(cell + vec2(1.0, 1.0)), t.x);
+    return mix(bottom, top, t.y);
+}
+
+@fragment
+fn fragment(
+    dissolve_in: DissolveVertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var in: VertexOutput;
+    in.position = dissolve_in.position;
+    in.world_position = dissolve_in.world_position;
+    in.world_normal = dissolve_in.world_normal;
+#ifdef VERTEX_UVS_A
+    in.uv = dissolve_in.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    in.uv_b = dissolve_in.uv_b;
+#endif
+#ifdef VERTEX_COLORS
+    in.color = dissolve_in.color;
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    in.instance_index = dissolve_in.instance_index;
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    in.visibility_range_dither = dissolve_in.visibility_range_dither;
+#endif
+
+    // Pixels whose noise the dissolve amount has passed are discarded, and those it is about to pass glow
+    let dissolve = dissolve_in.dissolve;
+    var noise = 1.0;
+#ifdef VERTEX_UVS_A
+    noise = value_noise(in.uv * noise_scale);
+#endif
+    if noise < dissolve {
+        discard;
+    }
+    let is_edge = dissolve > 0.0 && noise < dissolve + edge_width;
+
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+    var out: FragmentOutput;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+        out.color = apply_pbr_lighting(pbr_input);
+    } else {
+        out.color = pbr_input.material.base_color;
+    }
+    if is_edge {
+        out.color = vec4(out.color.rgb + edge_color.rgb * edge_color.a, out.color.a);
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+
+    return out;
+}
@@ -0,0 +1,248 @@
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_color::prelude::*;
+use bevy_core::Name;
+use bevy_ecs::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_hierarchy::prelude::*;
+use bevy_image::prelude::*;
+use bevy_math::{UVec2, Vec2};
+use bevy_pbr::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_render::alpha::AlphaMode;
+use bevy_transform::prelude::*;
+use bevy_utils::{HashMap, HashSet};
+
+use crate::{spawn_tile_sprites, texture_size, Sprite3dTileLayer, SpriteMaterial3d, Tile3d, TileAtlas};
+
+/// Renders `bevy_ecs_tilemap` tilemaps marked with [`Sprite3dEcsTilemap`] as batched sprites, so that existing 2D
+/// tilemaps can be laid on floors and walls of 3D scenes, and lit.
+/// Requires a [`Sprite3dPlugin<StandardMaterial>`](crate::Sprite3dPlugin) to render them. bevy_ecs_tilemap's own
+/// `TilemapPlugin` isn't needed, and if added, still draws the tilemaps for 2D cameras.
+pub struct EcsTilemapPlugin;
+
+impl Plugin for EcsTilemapPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Sprite3dEcsTilemap>();
+        app.add_systems(PostUpdate, sync_ecs_tilemaps.before(spawn_tile_sprites::<StandardMaterial>));
+    }
+}
+
+/// Renders the `bevy_ecs_tilemap` tilemap of this entity through a child [`Sprite3dTileLayer`], marked with
+/// [`Sprite3dEcsTilemapLayer`], which is kept up to date as its tiles change.
+/// Tiles are placed where the tilemap would draw them in 2D, scaled to `tile_size`.
+/// Square tilemaps textured with a single atlas image, ie: `TilemapTexture::Single`, are supported. Tiles are laid
+/// out on a square grid whatever the tilemap's type, and hidden tiles, ie: with `TileVisible(false)`, are left out.
+#[derive(Component, Reflect, Clone, Default, Debug)]
+pub struct Sprite3dEcsTilemap {
+    /// Size of a tile, in world units. Defaults to the tilemap's grid size.
+    pub tile_size: Option<Vec2>,
+    /// Material of the tiles, whose texture should be the tilemap's. Defaults to a [`StandardMaterial`] using the
+    /// tilemap's texture, alpha masked.
+    pub material: Option<Handle<StandardMaterial>>,
+}
+
+impl Sprite3dEcsTilemap {
+    pub fn with_tile_size(mut self, tile_size: Vec2) -> Self {
+        self.tile_size = Some(tile_size);
+        self
+    }
+
+    pub fn with_material(mut self, material: Handle<StandardMaterial>) -> Self {
+        self.material = Some(material);
+        self
+    }
+}
+
+/// Marks the tile layer spawned for a [`Sprite3dEcsTilemap`].
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Sprite3dEcsTilemapLayer;
+
+/// Components of a tilemap the layer is built from.
+type TilemapQuery<'a> = (
+    Entity,
+    Ref<'a, Sprite3dEcsTilemap>,
+    Ref<'a, TileStorage>,
+    Ref<'a, TilemapTexture>,
+    Ref<'a, TilemapTileSize>,
+    Ref<'a, TilemapGridSize>,
+    Option<Ref<'a, TilemapSpacing>>,
+    Option<&'a Children>,
+);
+
+/// Components of a tile the layer is built from.
+type TileQuery<'a> = (&'a TileTextureIndex, Option<&'a TileFlip>, Option<&'a TileColor>, Option<&'a TileVisible>);
+
+// Rebuilds the tile layers of tilemaps whose tiles changed, and of tilemaps whose texture is still loading.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn sync_ecs_tilemaps(
+    mut commands: Commands,
+    tilemaps: Query<TilemapQuery>,
+    tiles: Query<TileQuery>,
+    changed_tiles: Query<
+        &TilemapId,
+        Or<(Changed<TileTextureIndex>, Changed<TileFlip>, Changed<TileColor>, Changed<TileVisible>)>,
+    >,
+    mut layers: Query<
+        (&mut Sprite3dTileLayer, &mut SpriteMaterial3d<StandardMaterial>, &mut Transform),
+        With<Sprite3dEcsTilemapLayer>,
+    >,
+    images: Res<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut default_materials: Local<HashMap<AssetId<Image>, Handle<StandardMaterial>>>,
+    mut loading: Local<HashSet<Entity>>,
+) {
+    let changed_tilemaps: HashSet<Entity> = changed_tiles.iter().map(|tilemap_id| tilemap_id.0).collect();
+    for (entity, tilemap, storage, texture, tile_size, grid_size, spacing, children) in &tilemaps {
+        let changed = tilemap.is_changed()
+            || storage.is_changed()
+            || texture.is_changed()
+            || tile_size.is_changed()
+            || grid_size.is_changed()
+            || spacing.as_ref().is_some_and(Ref::is_changed)
+            || changed_tilemaps.contains(&entity);
+        if !changed && !loading.contains(&entity) { continue };
+
+        let TilemapTexture::Single(image_handle) = &*texture else { continue };
+        let Some(image) = images.get(image_handle) else {
+            loading.insert(entity);
+            continue;
+        };
+        loading.remove(&entity);
+
+        let spacing = spacing.map_or(0, |spacing| spacing.x as u32);
+        let atlas_tile_size = UVec2::new(tile_size.x as u32, tile_size.y as u32);
+        let columns = (texture_size(image).x as u32 + spacing) / (atlas_tile_size.x + spacing).max(1);
+        let atlas = TileAtlas { spacing, ..TileAtlas::new(atlas_tile_size, columns) };
+        let cell_size = tilemap.tile_size.unwrap_or(Vec2::new(grid_size.x, grid_size.y));
+        let mut layer = Sprite3dTileLayer::new(UVec2::new(storage.size.x, storage.size.y), cell_size, atlas);
+        for (i, tile_entity) in storage.iter().enumerate() {
+            let Some(tile_entity) = tile_entity else { continue };
+            let Ok((index, flip, color, visible)) = tiles.get(*tile_entity) else { continue };
+            if visible.is_some_and(|visible| !visible.0) { continue };
+            let flip = flip.copied().unwrap_or_default();
+            // bevy_ecs_tilemap's rows go up from the bottom, while tile layers' go down from the top
+            let position = UVec2::new(i as u32 % storage.size.x, storage.size.y - 1 - i as u32 / storage.size.x);
+            layer.set(position, Some(Tile3d {
+                flip_x: flip.x,
+                flip_y: flip.y,
+                flip_d: flip.d,
+                color: color.map_or(Color::WHITE, |color| color.0),
+                ..Tile3d::new(index.0)
+            }));
+        }
+
+        let material = match &tilemap.material {
+            Some(material) => material.clone(),
+            None => default_materials
+                .entry(image_handle.id())
+                .or_insert_with(|| materials.add(StandardMaterial {
+                    base_color_texture: Some(image_handle.clone()),
+                    alpha_mode: AlphaMode::Mask(0.5),
+                    perceptual_roughness: 1.0,
+                    ..Default::default()
+                }))
+                .clone(),
+        };
+        // Tile (0, 0) is centered on the tilemap's origin, as in 2D
+        let offset = Vec2::new(-0.5, storage.size.y as f32 - 0.5) * cell_size;
+        let transform = Transform::from_translation(offset.extend(0.0));
+
+        let existing = children.into_iter().flatten().find(|&&child| layers.contains(child));
+        match existing.and_then(|&child| layers.get_mut(child).ok()) {
+            Some((mut tile_layer, mut layer_material, mut layer_transform)) => {
+                tile_layer.set_if_neq(layer);
+                if layer_material.0 != material {
+                    layer_material.0 = material;
+                }
+                layer_transform.set_if_neq(transform);
+            },
+            None => {
+                let layer_entity = commands.spawn((
+                    layer,
+                    SpriteMaterial3d(material),
+                    transform,
+                    Name::new("bevy_ecs_tilemap Layer"),
+                    Sprite3dEcsTilemapLayer,
+                )).id();
+                commands.entity(entity).add_child(layer_entity);
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::render::render_asset::RenderAssetUsages;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+    use bevy_ecs_tilemap::prelude::*;
+
+    use crate::*;
+
+    #[test]
+    fn tilemaps_are_rendered_as_tile_layers() {
+        let mut app = App::new();
+        let plugin = Sprite3dPlugin::<StandardMaterial>::default();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin, plugin, EcsTilemapPlugin));
+        app.init_asset::<Image>();
+        app.init_asset::<Mesh>();
+        app.init_asset::<StandardMaterial>();
+        let image = app.world_mut().resource_mut::<Assets<Image>>().add(Image::new_fill(
+            Extent3d { width: 32, height: 16, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            &[255, 255, 255, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::all(),
+        ));
+        let size = TilemapSize { x: 2, y: 2 };
+        let tilemap = app.world_mut().spawn_empty().id();
+        let mut storage = TileStorage::empty(size);
+        let tiles = [(TilePos::new(0, 0), 1, true), (TilePos::new(1, 1), 0, true), (TilePos::new(1, 0), 0, false)];
+        for (position, index, visible) in tiles {
+            let tile = app.world_mut().spawn(TileBundle {
+                position,
+                texture_index: TileTextureIndex(index),
+                tilemap_id: TilemapId(tilemap),
+                visible: TileVisible(visible),
+                ..default()
+            }).id();
+            storage.set(&position, tile);
+        }
+        app.world_mut().entity_mut(tilemap).insert((
+            Sprite3dEcsTilemap::default().with_tile_size(Vec2::ONE),
+            storage,
+            size,
+            TilemapTexture::Single(image),
+            TilemapTileSize { x: 16.0, y: 16.0 },
+            TilemapGridSize { x: 16.0, y: 16.0 },
+            Transform::default(),
+            Visibility::default(),
+        ));
+        app.update();
+
+        let mut layers = app.world_mut().query_filtered::<&Sprite3dTileLayer, With<Sprite3dEcsTilemapLayer>>();
+        let layer = layers.single(app.world());
+        assert_eq!(layer.atlas.columns, 2);
+        assert_eq!(layer.get(UVec2::new(0, 1)).map(|tile| tile.index), Some(1));
+        assert_eq!(layer.get(UVec2::new(1, 0)).map(|tile| tile.index), Some(0));
+        assert_eq!(layer.get(UVec2::new(1, 1)), None);
+
+        // Tiles are placed where bevy_ecs_tilemap draws them
+        app.update();
+        let mut tiles = app.world_mut().query::<(&Sprite3dTile, &GlobalTransform)>();
+        let bottom_left = tiles.iter(app.world()).find(|(tile, _)| tile.position == UVec2::new(0, 1)).unwrap().1;
+        assert!(bottom_left.translation().abs_diff_eq(Vec3::ZERO, 1e-4));
+
+        // Changed tiles are picked up
+        let mut indices = app.world_mut().query::<(&TilePos, &mut TileTextureIndex)>();
+        for (position, mut index) in indices.iter_mut(app.world_mut()) {
+            if *position == TilePos::new(1, 1) {
+                index.0 = 1;
+            }
+        }
+        app.update();
+        let layer = layers.single(app.world());
+        assert_eq!(layer.get(UVec2::new(1, 0)).map(|tile| tile.index), Some(1));
+    }
+}
@@ -0,0 +1,129 @@
+use bevy_ecs::entity::EntityHashMap;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_render::prelude::*;
+
+use crate::{MeshBatch, SizedMaterial, Sprite3dGpuPointBatch, Sprite3dPointBatch, Sprite3dQueue};
+
+/// Turns sprite rendering on and off at runtime, ie: behind menus and pause screens, or in server-authoritative
+/// modes that don't render.
+/// While disabled, [`Sprite3dSystems`](crate::Sprite3dSystems) don't run, batch entities (copies for
+/// [`Sprite3dSortedView`](crate::Sprite3dSortedView) cameras and point batches included) are hidden and queued
+/// sprites are discarded. Batches and their cached sprite data are kept, and brought up to date when re-enabled.
+/// Hidden entities get back the [`Visibility`] they had, unless it was changed while disabled.
+#[derive(Resource, Reflect, Copy, Clone, Eq, PartialEq, Debug)]
+#[reflect(Resource)]
+pub struct Sprite3dEnabled(pub bool);
+
+impl Default for Sprite3dEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Run condition that is true unless sprite rendering is disabled with [`Sprite3dEnabled`].
+pub fn sprite3d_enabled(enabled: Option<Res<Sprite3dEnabled>>) -> bool {
+    enabled.is_none_or(|enabled| enabled.0)
+}
+
+/// Visibility of the entities hidden while sprite rendering is disabled, to restore when it gets re-enabled.
+#[derive(Default)]
+pub(crate) struct HiddenEntities(EntityHashMap<Visibility>);
+
+impl HiddenEntities {
+    fn toggle(
+        &mut self,
+        enabled: bool,
+        entities: impl IntoIterator<Item = Entity>,
+        visibilities: &mut Query<&mut Visibility>,
+    ) {
+        if enabled {
+            for (entity, previous) in self.0.drain() {
+                let Ok(mut visibility) = visibilities.get_mut(entity) else { continue };
+                // Left alone if it was changed while disabled
+                if *visibility == Visibility::Hidden {
+                    visibility.set_if_neq(previous);
+                }
+            }
+        } else {
+            for entity in entities {
+                let Ok(mut visibility) = visibilities.get_mut(entity) else { continue };
+                self.0.entry(entity).or_insert(*visibility);
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
+
+/// Hides batch entities when sprite rendering gets disabled, and shows them again when it gets re-enabled.
+pub(crate) fn toggle_batch_visibility<M: SizedMaterial>(
+    enabled: Res<Sprite3dEnabled>,
+    mesh_batch: Res<MeshBatch<M>>,
+    mut hidden: Local<HiddenEntities>,
+    mut visibilities: Query<&mut Visibility>,
+) {
+    if !enabled.is_changed() { return };
+    hidden.toggle(enabled.0, mesh_batch.batch_entities(), &mut visibilities);
+}
+
+/// Same as [`toggle_batch_visibility`], for [`Sprite3dPointBatch`]es and [`Sprite3dGpuPointBatch`]es.
+#[allow(clippy::type_complexity)]
+pub(crate) fn toggle_point_batch_visibility(
+    enabled: Res<Sprite3dEnabled>,
+    point_batches: Query<Entity, Or<(With<Sprite3dPointBatch>, With<Sprite3dGpuPointBatch>)>>,
+    mut hidden: Local<HiddenEntities>,
+    mut visibilities: Query<&mut Visibility>,
+) {
+    if !enabled.is_changed() { return };
+    hidden.toggle(enabled.0, &point_batches, &mut visibilities);
+}
+
+/// Empties the queue while sprite rendering is disabled, as it isn't emptied by batching.
+pub(crate) fn discard_queued_sprites<M: SizedMaterial>(mut queue: ResMut<Sprite3dQueue<M>>) {
+    queue.sprites.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::test_utils::{test_app, textured_material};
+    use crate::*;
+
+    fn set_enabled(app: &mut App, enabled: bool) {
+        app.world_mut().resource_mut::<Sprite3dEnabled>().0 = enabled;
+        app.update();
+    }
+
+    #[test]
+    fn disabling_hides_every_batch_and_restores_their_visibility() {
+        let mut app = test_app(Sprite3dPlugin::default());
+        let material = textured_material(&mut app, 16, 16);
+        let mut materials = app.world_mut().resource_mut::<Assets<StandardMaterial>>();
+        materials.get_mut(&material).unwrap().alpha_mode = AlphaMode::Blend;
+        app.world_mut().spawn((Camera::default(), Sprite3dSortedView, Transform::from_xyz(0.0, 0.0, 10.0)));
+        app.world_mut().spawn((Sprite3d::default(), SpriteMaterial3d(material)));
+        let points = app.world_mut().spawn(Sprite3dPointBatch::new(Handle::default())).id();
+        let hidden_points = app.world_mut()
+            .spawn((Sprite3dPointBatch::new(Handle::default()), Visibility::Hidden))
+            .id();
+        app.update();
+        let mut view_batches = app.world_mut().query_filtered::<Entity, With<Sprite3dViewBatch>>();
+        let view_batch = view_batches.single(app.world());
+        let mut batches = app.world_mut().query_filtered::<Entity, (With<Mesh3d>, Without<Sprite3dViewBatch>)>();
+        let batch = batches.single(app.world());
+        // Shown on its own, ie: by a debug tool isolating a batch
+        app.world_mut().entity_mut(batch).insert(Visibility::Visible);
+
+        set_enabled(&mut app, false);
+        for entity in [batch, view_batch, points, hidden_points] {
+            assert_eq!(app.world().get::<Visibility>(entity), Some(&Visibility::Hidden));
+        }
+
+        set_enabled(&mut app, true);
+        assert_eq!(app.world().get::<Visibility>(batch), Some(&Visibility::Visible));
+        assert_eq!(app.world().get::<Visibility>(view_batch), Some(&Visibility::Inherited));
+        assert_eq!(app.world().get::<Visibility>(points), Some(&Visibility::Inherited));
+        assert_eq!(app.world().get::<Visibility>(hidden_points), Some(&Visibility::Hidden));
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_color::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_time::prelude::*;
+
+use crate::{Sprite3d, Sprite3dAnimationGroup, Sprite3dAnimationTime};
+
+/// Animates [`Sprite3d::color`] (alpha included) from one color to another over time,
+/// ie: for damage numbers and pickup sparkles.
+/// Requires the [`Sprite3dFadePlugin`].
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dFade {
+    pub duration: Duration,
+    pub from: Color,
+    pub to: Color,
+    /// What happens once the fade reaches `to`.
+    pub on_complete: FadeComplete,
+    /// Time since the fade started.
+    pub elapsed: Duration,
+}
+
+impl Sprite3dFade {
+    pub fn new(duration: Duration, from: Color, to: Color) -> Self {
+        Self { duration, from, to, on_complete: FadeComplete::default(), elapsed: Duration::ZERO }
+    }
+
+    /// Fades a color out to full transparency.
+    pub fn out(duration: Duration, color: Color) -> Self {
+        Self::new(duration, color, color.with_alpha(0.0))
+    }
+
+    pub fn with_on_complete(mut self, on_complete: FadeComplete) -> Self {
+        self.on_complete = on_complete;
+        self
+    }
+
+    /// Progress of the fade, from 0 to 1.
+    pub fn ratio(&self) -> f32 {
+        match self.duration.is_zero() {
+            true => 1.0,
+            false => (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// What happens to an entity once its [`Sprite3dFade`] completes.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum FadeComplete {
+    /// The sprite keeps the final color, and the fade component is removed.
+    #[default]
+    Remove,
+    /// The sprite and its descendants are despawned.
+    Despawn,
+}
+
+/// Plays [`Sprite3dFade`]s.
+pub struct Sprite3dFadePlugin;
+
+impl Plugin for Sprite3dFadePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sprite3dAnimationTime>();
+        app.add_systems(Update, fade_sprites);
+    }
+}
+
+pub(crate) fn fade_sprites(
+    mut commands: Commands,
+    mut sprites: Query<(Entity, &mut Sprite3d, &mut Sprite3dFade, Option<&Sprite3dAnimationGroup>)>,
+    time: Res<Time>,
+    animation_time: Res<Sprite3dAnimationTime>,
+) {
+    for (entity, mut sprite, mut fade, group) in &mut sprites {
+        fade.elapsed += animation_time.delta(time.delta(), group);
+        sprite.color = fade.from.mix(&fade.to, fade.ratio());
+        if !fade.is_complete() { continue };
+        match fade.on_complete {
+            FadeComplete::Remove => { commands.entity(entity).remove::<Sprite3dFade>(); },
+            FadeComplete::Despawn => commands.entity(entity).despawn_recursive(),
+        }
+    }
+}
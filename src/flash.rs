@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_color::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_math::curve::{Curve, EaseFunction, EasingCurve};
+use bevy_reflect::prelude::*;
+use bevy_time::prelude::*;
+
+use crate::fade::fade_sprites;
+use crate::{Sprite3d, Sprite3dAnimationGroup, Sprite3dAnimationTime};
+
+/// Briefly tints a sprite with a color, then restores its own, ie: when it takes damage or picks up an item.
+/// The flash starts at full strength and fades out along `curve`. Changes to [`Sprite3d::color`] made while
+/// flashing, ie: by a [`Sprite3dFade`](crate::Sprite3dFade), are kept and flashed over.
+/// The sprite's color is restored when the flash completes, gets removed, or is replaced by another flash.
+/// Requires the [`Sprite3dFlashPlugin`].
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dFlash {
+    pub color: Color,
+    pub duration: Duration,
+    /// How the strength of the flash goes from full to none over its duration.
+    pub curve: EaseFunction,
+    pub mode: FlashMode,
+    /// Time since the flash started.
+    pub elapsed: Duration,
+    /// Color of the sprite without the flash.
+    base: Option<Color>,
+    /// Color last written to the sprite by the flash.
+    written: Option<Color>,
+}
+
+impl Sprite3dFlash {
+    pub fn new(color: Color, duration: Duration) -> Self {
+        Self {
+            color,
+            duration,
+            curve: EaseFunction::Linear,
+            mode: FlashMode::default(),
+            elapsed: Duration::ZERO,
+            base: None,
+            written: None,
+        }
+    }
+
+    pub fn with_curve(mut self, curve: EaseFunction) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: FlashMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Strength of the flash, from 1 when it starts to 0 when it completes.
+    pub fn strength(&self) -> f32 {
+        if self.is_complete() { return 0.0 };
+        let ratio = self.elapsed.as_secs_f32() / self.duration.as_secs_f32();
+        EasingCurve::new(1.0, 0.0, self.curve).sample_clamped(ratio)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Color of a sprite tinted with the flash.
+    pub fn apply(&self, color: Color) -> Color {
+        let strength = self.strength();
+        match self.mode {
+            FlashMode::Mix => color.mix(&self.color, strength),
+            FlashMode::Multiply => {
+                let (base, tint) = (LinearRgba::from(color), LinearRgba::from(self.color));
+                let tinted = LinearRgba::new(base.red * tint.red, base.green * tint.green, base.blue * tint.blue, base.alpha);
+                color.mix(&tinted.into(), strength)
+            },
+            FlashMode::Override => match strength > 0.0 {
+                true => self.color,
+                false => color,
+            },
+        }
+    }
+}
+
+/// How a [`Sprite3dFlash`] tints a sprite.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum FlashMode {
+    /// Blends the sprite's color towards the flash color, by the strength of the flash.
+    #[default]
+    Mix,
+    /// Multiplies the sprite's color by the flash color, by the strength of the flash. Keeps the sprite's alpha.
+    Multiply,
+    /// Replaces the sprite's color with the flash color until the flash completes, ignoring its curve.
+    Override,
+}
+
+/// Plays [`Sprite3dFlash`]es.
+pub struct Sprite3dFlashPlugin;
+
+impl Plugin for Sprite3dFlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sprite3dAnimationTime>();
+        app.add_systems(Update, flash_sprites.after(fade_sprites));
+        app.add_observer(restore_flashed_colors);
+    }
+}
+
+pub(crate) fn flash_sprites(
+    mut commands: Commands,
+    mut sprites: Query<(Entity, &mut Sprite3d, &mut Sprite3dFlash, Option<&Sprite3dAnimationGroup>)>,
+    time: Res<Time>,
+    animation_time: Res<Sprite3dAnimationTime>,
+) {
+    for (entity, mut sprite, mut flash, group) in &mut sprites {
+        // Something else changed the sprite's color since the last frame, which becomes the color to restore
+        if flash.written != Some(sprite.color) {
+            flash.base = Some(sprite.color);
+        }
+        let base = flash.base.unwrap_or(sprite.color);
+        flash.elapsed += animation_time.delta(time.delta(), group);
+        sprite.color = flash.apply(base);
+        flash.written = Some(sprite.color);
+        if flash.is_complete() {
+            commands.entity(entity).remove::<Sprite3dFlash>();
+        }
+    }
+}
+
+// Restores the color of sprites whose flash gets removed or replaced before it completes.
+pub(crate) fn restore_flashed_colors(
+    trigger: Trigger<OnReplace, Sprite3dFlash>,
+    mut sprites: Query<(&mut Sprite3d, &Sprite3dFlash)>,
+) {
+    let Ok((mut sprite, flash)) = sprites.get_mut(trigger.entity()) else { return };
+    let Some(base) = flash.base else { return };
+    if flash.written == Some(sprite.color) {
+        sprite.color = base;
+    }
+}
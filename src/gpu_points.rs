@@ -0,0 +1,363 @@
+use bevy_app::prelude::*;
+use bevy_asset::{load_internal_asset, prelude::*};
+use bevy_ecs::prelude::*;
+use bevy_image::prelude::*;
+use bevy_math::{Rect, Vec2, Vec3};
+use bevy_render::graph::CameraDriverLabel;
+use bevy_render::render_asset::RenderAssets;
+use bevy_render::render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel};
+use bevy_render::render_resource::binding_types::{storage_buffer_read_only_sized, storage_buffer_sized};
+use bevy_render::render_resource::*;
+use bevy_render::renderer::{RenderContext, RenderDevice};
+use bevy_render::sync_component::SyncComponentPlugin;
+use bevy_render::sync_world::RenderEntity;
+use bevy_render::texture::GpuImage;
+use bevy_render::{Extract, ExtractSchedule, Render, RenderApp, RenderSet};
+use bevy_render::view::{ViewVisibility, Visibility};
+use bevy_transform::prelude::*;
+use bevy_utils::HashMap;
+use bytemuck::{Pod, Zeroable};
+
+use crate::point::{PointBuffers, PointPipeline};
+use crate::point_culling::PointCullLabel;
+use crate::{PointSprite, Sprite3dPointPlugin};
+
+const POINT_DELTAS_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x51d0_a7c3_e89b_4f12_8a64_0c2f_b7e1_93d5);
+
+/// Deltas applied per compute workgroup.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Change to a point of a [`Sprite3dGpuPointBatch`], applied on the GPU.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PointDelta {
+    /// Index of the point in the batch.
+    pub index: u32,
+    /// Added to the point's position.
+    pub translation: Vec3,
+    /// Added to the point's rotation, in radians.
+    pub rotation: f32,
+    /// Multiplies the point's size.
+    pub scale: f32,
+    /// If set, replaces the area of the texture the point displays, ie: to advance an animation.
+    pub uv_rect: Option<Rect>,
+}
+
+impl PointDelta {
+    pub fn new(index: u32) -> Self {
+        Self { index, translation: Vec3::ZERO, rotation: 0.0, scale: 1.0, uv_rect: None }
+    }
+
+    /// Combines two deltas of the same point, this one applied first.
+    fn then(self, other: PointDelta) -> Self {
+        Self {
+            index: self.index,
+            translation: self.translation + other.translation,
+            rotation: self.rotation + other.rotation,
+            scale: self.scale * other.scale,
+            uv_rect: other.uv_rect.or(self.uv_rect),
+        }
+    }
+}
+
+/// [`Sprite3dPointBatch`](crate::Sprite3dPointBatch) whose points live on the GPU, ie: for crowds of tens of
+/// thousands of moving sprites.
+/// Points are uploaded once, then only changed through [`PointDelta`]s, which a compute shader applies to the
+/// points in place. Deltas are uploaded and cleared every frame, so moving a few sprites costs a few deltas,
+/// rather than rewriting every point.
+/// The points kept on the CPU are the ones last given to [`Self::set_points`], without the deltas applied since.
+/// Deltas are applied even while the batch is hidden.
+/// Requires the [`Sprite3dGpuPointPlugin`].
+#[derive(Component, Clone, Debug)]
+#[require(Transform, Visibility)]
+pub struct Sprite3dGpuPointBatch {
+    pub texture: Handle<Image>,
+    points: Vec<PointSprite>,
+    /// Incremented whenever the points are replaced, so that they get uploaded again.
+    generation: u32,
+    deltas: Vec<PointDelta>,
+}
+
+impl Sprite3dGpuPointBatch {
+    pub fn new(texture: Handle<Image>, points: Vec<PointSprite>) -> Self {
+        Self { texture, points, generation: 0, deltas: Vec::new() }
+    }
+
+    pub fn points(&self) -> &[PointSprite] {
+        &self.points
+    }
+
+    /// Replaces the points of the batch, uploading them again, and discards pending deltas.
+    pub fn set_points(&mut self, points: Vec<PointSprite>) {
+        self.points = points;
+        self.generation = self.generation.wrapping_add(1);
+        self.deltas.clear();
+    }
+
+    /// Queues a change to a point, applied on the GPU this frame.
+    pub fn push_delta(&mut self, delta: PointDelta) {
+        self.deltas.push(delta);
+    }
+
+    pub fn translate(&mut self, index: u32, translation: Vec3) {
+        self.push_delta(PointDelta { translation, ..PointDelta::new(index) });
+    }
+
+    pub fn rotate(&mut self, index: u32, rotation: f32) {
+        self.push_delta(PointDelta { rotation, ..PointDelta::new(index) });
+    }
+
+    pub fn scale(&mut self, index: u32, scale: f32) {
+        self.push_delta(PointDelta { scale, ..PointDelta::new(index) });
+    }
+
+    pub fn set_uv_rect(&mut self, index: u32, uv_rect: Rect) {
+        self.push_delta(PointDelta { uv_rect: Some(uv_rect), ..PointDelta::new(index) });
+    }
+
+    pub fn deltas(&self) -> &[PointDelta] {
+        &self.deltas
+    }
+}
+
+/// Renders [`Sprite3dGpuPointBatch`]es, on top of the [`Sprite3dPointPlugin`], which it adds if needed.
+pub struct Sprite3dGpuPointPlugin;
+
+impl Plugin for Sprite3dGpuPointPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<Sprite3dPointPlugin>() {
+            app.add_plugins(Sprite3dPointPlugin);
+        }
+        load_internal_asset!(app, POINT_DELTAS_SHADER_HANDLE, "point_deltas.wgsl", Shader::from_wgsl);
+        app.add_plugins(SyncComponentPlugin::<Sprite3dGpuPointBatch>::default());
+        app.add_systems(First, clear_point_deltas);
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else { return };
+        render_app
+            .init_resource::<PointDeltaDispatches>()
+            .add_systems(ExtractSchedule, extract_gpu_points)
+            .add_systems(Render, prepare_gpu_points.in_set(RenderSet::PrepareResources));
+        let mut render_graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        render_graph.add_node(PointDeltaLabel, PointDeltaNode);
+        render_graph.add_node_edge(PointDeltaLabel, CameraDriverLabel);
+        render_graph.add_node_edge(PointDeltaLabel, PointCullLabel);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else { return };
+        render_app.init_resource::<PointDeltaPipeline>();
+    }
+}
+
+// Deltas were extracted at the end of the previous frame.
+fn clear_point_deltas(mut batches: Query<&mut Sprite3dGpuPointBatch>) {
+    for mut batch in &mut batches {
+        if !batch.deltas.is_empty() {
+            batch.deltas.clear();
+        }
+    }
+}
+
+/// [`Sprite3dGpuPointBatch`] in the render world.
+#[derive(Component)]
+pub(crate) struct ExtractedGpuPoints {
+    texture: AssetId<Image>,
+    pub visible: bool,
+    generation: u32,
+    /// Points, if they changed since they were last uploaded.
+    points: Option<Vec<PointSprite>>,
+    deltas: Vec<PointDelta>,
+}
+
+/// Points of a [`Sprite3dGpuPointBatch`] on the GPU, and deltas waiting for the compute pipeline to be ready.
+#[derive(Component)]
+struct GpuPointState {
+    points: Buffer,
+    length: usize,
+    generation: u32,
+    pending: Vec<PointDelta>,
+}
+
+fn extract_gpu_points(
+    mut commands: Commands,
+    batches: Extract<Query<(RenderEntity, &ViewVisibility, &Sprite3dGpuPointBatch)>>,
+    states: Query<&GpuPointState>,
+) {
+    for (render_entity, view_visibility, batch) in &batches {
+        let uploaded = states.get(render_entity).is_ok_and(|state| state.generation == batch.generation);
+        commands.entity(render_entity).insert(ExtractedGpuPoints {
+            texture: batch.texture.id(),
+            visible: view_visibility.get(),
+            generation: batch.generation,
+            points: (!uploaded).then(|| batch.points.clone()),
+            deltas: batch.deltas.clone(),
+        });
+    }
+}
+
+/// GPU layout of [`PointDelta`], matching `point_deltas.wgsl`.
+#[derive(Copy, Clone, Pod, Zeroable)]
+#[repr(C)]
+struct GpuPointDelta {
+    translation: Vec3,
+    rotation: f32,
+    scale: f32,
+    index: u32,
+    flags: u32,
+    _padding: u32,
+    uv_min: Vec2,
+    uv_max: Vec2,
+}
+
+impl From<PointDelta> for GpuPointDelta {
+    fn from(delta: PointDelta) -> Self {
+        let uv_rect = delta.uv_rect.unwrap_or_default();
+        Self {
+            translation: delta.translation,
+            rotation: delta.rotation,
+            scale: delta.scale,
+            index: delta.index,
+            flags: delta.uv_rect.is_some() as u32,
+            _padding: 0,
+            uv_min: uv_rect.min,
+            uv_max: uv_rect.max,
+        }
+    }
+}
+
+/// Bind groups of the deltas to apply this frame, and how many deltas each has.
+#[derive(Resource, Default)]
+struct PointDeltaDispatches(Vec<(BindGroup, u32)>);
+
+#[allow(clippy::too_many_arguments)]
+fn prepare_gpu_points(
+    mut commands: Commands,
+    mut batches: Query<(Entity, &mut ExtractedGpuPoints, Option<&mut GpuPointState>)>,
+    mut dispatches: ResMut<PointDeltaDispatches>,
+    delta_pipeline: Res<PointDeltaPipeline>,
+    point_pipeline: Res<PointPipeline>,
+    pipeline_cache: Res<PipelineCache>,
+    images: Res<RenderAssets<GpuImage>>,
+    render_device: Res<RenderDevice>,
+) {
+    dispatches.0.clear();
+    let pipeline_ready = pipeline_cache.get_compute_pipeline(delta_pipeline.pipeline).is_some();
+    for (entity, mut extracted, state) in &mut batches {
+        // Replaced points discard the deltas still pending for the previous ones
+        let mut uploaded = extracted.points.take().map(|points| GpuPointState {
+            points: render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("sprite3d_gpu_points"),
+                contents: bytemuck::cast_slice(&points),
+                usage: BufferUsages::STORAGE | BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            }),
+            length: points.len(),
+            generation: extracted.generation,
+            pending: Vec::new(),
+        });
+        let state = match (&mut uploaded, state) {
+            (Some(uploaded), _) => uploaded,
+            (None, Some(state)) => state.into_inner(),
+            (None, None) => continue,
+        };
+        state.pending.append(&mut extracted.deltas);
+
+        // Merges deltas per point, so that no two compute invocations write the same point
+        if pipeline_ready && !state.pending.is_empty() && state.length > 0 {
+            let mut merged: HashMap<u32, PointDelta> = HashMap::new();
+            for delta in state.pending.drain(..) {
+                merged.entry(delta.index).and_modify(|merged| *merged = merged.then(delta)).or_insert(delta);
+            }
+            let deltas: Vec<GpuPointDelta> = merged.into_values().map(GpuPointDelta::from).collect();
+            let delta_buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("sprite3d_gpu_point_deltas"),
+                contents: bytemuck::cast_slice(&deltas),
+                usage: BufferUsages::STORAGE,
+            });
+            let bind_group = render_device.create_bind_group(
+                "sprite3d_gpu_point_deltas",
+                &delta_pipeline.layout,
+                &BindGroupEntries::sequential((state.points.as_entire_binding(), delta_buffer.as_entire_binding())),
+            );
+            dispatches.0.push((bind_group, deltas.len() as u32));
+        }
+
+        match images.get(extracted.texture) {
+            Some(image) => {
+                let texture_bind_group = render_device.create_bind_group(
+                    "sprite3d_point_texture",
+                    &point_pipeline.texture_layout,
+                    &BindGroupEntries::sequential((&image.texture_view, &image.sampler)),
+                );
+                commands.entity(entity).insert(PointBuffers {
+                    instances: state.points.clone(),
+                    length: state.length,
+                    texture_bind_group,
+                    texture_view: image.texture_view.id(),
+                });
+            },
+            None => { commands.entity(entity).remove::<PointBuffers>(); },
+        }
+        if let Some(uploaded) = uploaded {
+            commands.entity(entity).insert(uploaded);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct PointDeltaPipeline {
+    layout: BindGroupLayout,
+    pipeline: CachedComputePipelineId,
+}
+
+impl FromWorld for PointDeltaPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let layout = world.resource::<RenderDevice>().create_bind_group_layout(
+            "sprite3d_gpu_point_deltas_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    storage_buffer_sized(false, None),
+                    storage_buffer_read_only_sized(false, None),
+                ),
+            ),
+        );
+        let pipeline = world.resource::<PipelineCache>().queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some("sprite3d_gpu_point_deltas_pipeline".into()),
+            layout: vec![layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader: POINT_DELTAS_SHADER_HANDLE,
+            shader_defs: Vec::new(),
+            entry_point: "apply_deltas".into(),
+            zero_initialize_workgroup_memory: false,
+        });
+        Self { layout, pipeline }
+    }
+}
+
+#[derive(RenderLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct PointDeltaLabel;
+
+/// Applies the deltas of every batch, once per frame, before cameras render.
+struct PointDeltaNode;
+
+impl Node for PointDeltaNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let dispatches = world.resource::<PointDeltaDispatches>();
+        if dispatches.0.is_empty() { return Ok(()) };
+        let pipeline_id = world.resource::<PointDeltaPipeline>().pipeline;
+        let Some(pipeline) = world.resource::<PipelineCache>().get_compute_pipeline(pipeline_id) else { return Ok(()) };
+        let mut pass = render_context.command_encoder().begin_compute_pass(&ComputePassDescriptor {
+            label: Some("sprite3d_gpu_point_deltas"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(pipeline);
+        for (bind_group, delta_count) in &dispatches.0 {
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.dispatch_workgroups(delta_count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+        Ok(())
+    }
+}
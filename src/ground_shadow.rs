@@ -0,0 +1,143 @@
+use bevy_asset::prelude::*;
+use bevy_color::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_image::Image;
+use bevy_math::{Affine3A, Mat3A, Vec3, Vec3A};
+use bevy_pbr::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_render::camera::{Camera, OrthographicProjection, Projection};
+use bevy_render::prelude::*;
+use bevy_transform::prelude::*;
+use bevy_utils::HashMap;
+
+use crate::view::SpriteView;
+use crate::{MeshBatch, SizedMaterial, Sprite3d, Sprite3dBillboard, Sprite3dQueue, SpriteMaterial3d};
+
+/// Distance shadows are lifted off the ground plane, so that they don't z-fight with the ground.
+pub const GROUND_SHADOW_LIFT: f32 = 0.005;
+
+/// Draws a flattened, darkened copy of a sprite on the ground, skewed along the direction of the light, ie: the
+/// classic shadow of characters and props in 2.5D games, which don't cast proper shadows as billboards.
+/// The copy is the sprite standing upright and facing the nearest camera, projected onto the ground plane.
+/// Shadows are drawn through the [`Sprite3dQueue`] of the default [`Sprite3dPlugin`](crate::Sprite3dPlugin),
+/// which needs to be added, with an unlit [`ground_shadow_material`] sharing the sprite's texture, so they are
+/// batched together across sprites of the same texture, whatever their own material.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dGroundShadow {
+    /// Height of the ground plane, along the up axis of the sprites.
+    pub ground: f32,
+    /// Direction the light travels in. If None, that of the first [`DirectionalLight`] in the scene.
+    pub direction: Option<Vec3>,
+    /// Opacity of the shadow, multiplied by the sprite's alpha.
+    pub alpha: f32,
+}
+
+impl Default for Sprite3dGroundShadow {
+    fn default() -> Self {
+        Self { ground: 0.0, direction: None, alpha: 0.5 }
+    }
+}
+
+impl Sprite3dGroundShadow {
+    pub fn new(ground: f32) -> Self {
+        Self { ground, ..Self::default() }
+    }
+
+    pub fn with_direction(mut self, direction: Vec3) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Affine transform flattening points onto the ground plane along `direction`, lifted by [`GROUND_SHADOW_LIFT`].
+    /// None if the light doesn't travel down towards the ground.
+    fn projection(&self, direction: Vec3, up: Vec3) -> Option<Affine3A> {
+        let direction = Vec3A::from(direction.try_normalize()?);
+        let up = Vec3A::from(up);
+        let descent = direction.dot(up);
+        if descent > -f32::EPSILON { return None };
+
+        // Moves each point along the light by its height above the ground, ie: p - d * (p·up - ground) / (d·up)
+        let matrix = Mat3A::IDENTITY - Mat3A::from_cols(direction * up.x, direction * up.y, direction * up.z) / descent;
+        let translation = direction * self.ground / descent + up * GROUND_SHADOW_LIFT;
+        Some(Affine3A { matrix3: matrix, translation })
+    }
+}
+
+/// Unlit material drawing sprites with a texture as flat silhouettes, tinted by their vertex colors.
+/// Used by [`Sprite3dGroundShadow`] with sprites colored black.
+pub fn ground_shadow_material(texture: Handle<Image>) -> StandardMaterial {
+    StandardMaterial {
+        base_color_texture: Some(texture),
+        unlit: true,
+        alpha_mode: AlphaMode::Blend,
+        double_sided: true,
+        cull_mode: None,
+        ..Default::default()
+    }
+}
+
+/// Shadow materials of [`Sprite3dGroundShadow`], keyed by texture.
+#[derive(Resource, Default, Debug)]
+pub(crate) struct GroundShadowMaterials(HashMap<AssetId<Image>, Handle<StandardMaterial>>);
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn queue_ground_shadows<M: SizedMaterial>(
+    sprites: Query<(&Sprite3d, &Sprite3dGroundShadow, &SpriteMaterial3d<M>, &GlobalTransform, &InheritedVisibility)>,
+    lights: Query<&GlobalTransform, With<DirectionalLight>>,
+    cameras: Query<(&GlobalTransform, &Camera, Option<&Projection>, Option<&OrthographicProjection>)>,
+    mut material_assets: ParamSet<(Res<Assets<M>>, ResMut<Assets<StandardMaterial>>)>,
+    mut shadow_materials: ResMut<GroundShadowMaterials>,
+    queue: Option<ResMut<Sprite3dQueue<StandardMaterial>>>,
+    mesh_batch: Option<Res<MeshBatch<StandardMaterial>>>,
+    mut textures: Local<Vec<Option<Handle<Image>>>>,
+) {
+    let (Some(mut queue), Some(mesh_batch)) = (queue, mesh_batch) else { return };
+    let up_axis = mesh_batch.up_axis;
+    let light_direction = lights.iter().next().map(|transform| transform.forward().as_vec3());
+    let views: Vec<SpriteView> = cameras
+        .iter()
+        .filter(|(_, camera, _, _)| camera.is_active)
+        .map(|(transform, camera, projection, orthographic)| SpriteView::new(transform, camera, projection, orthographic))
+        .collect();
+
+    // Looks up the textures of the sprites first, as their material may be a StandardMaterial too
+    let materials = material_assets.p0();
+    textures.clear();
+    textures.extend(sprites.iter().map(|(_, _, material, _, _)| materials.get(&material.0)?.texture().cloned()));
+    let standard_materials = &mut *material_assets.p1();
+    for ((sprite, shadow, _, transform, visibility), texture) in sprites.iter().zip(textures.drain(..)) {
+        if !visibility.get() || shadow.alpha <= 0.0 { continue };
+        let Some(texture) = texture else { continue };
+        let Some(direction) = shadow.direction.or(light_direction) else { continue };
+        let Some(projection) = shadow.projection(direction, up_axis.up()) else { continue };
+
+        // Stands the sprite upright facing the camera, like a cutout, and flattens it onto the ground
+        let (scale, _, translation) = transform.to_scale_rotation_translation();
+        let upright: GlobalTransform = Transform::from_translation(translation)
+            .with_rotation(up_axis.rotation())
+            .with_scale(scale)
+            .into();
+        let caster = Sprite3dBillboard::upright().apply(&upright, &views, up_axis);
+        let shadow_affine = projection * caster.affine();
+        let flattened = shadow_affine.matrix3.x_axis.cross(shadow_affine.matrix3.y_axis);
+        if flattened.length_squared() <= f32::EPSILON { continue };
+
+        let material = shadow_materials
+            .0
+            .entry(texture.id())
+            .or_insert_with(|| standard_materials.add(ground_shadow_material(texture)))
+            .clone();
+        let shadow_sprite = Sprite3d {
+            color: Color::BLACK.with_alpha(shadow.alpha * sprite.color.alpha()),
+            ..sprite.clone()
+        };
+        // Queued sprites are turned to stand along the up axis, which the projection already accounts for
+        let unturn = Transform::from_rotation(up_axis.rotation().inverse());
+        queue.draw(shadow_sprite, GlobalTransform::from(shadow_affine).mul_transform(unturn), material);
+    }
+}
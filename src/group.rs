@@ -0,0 +1,136 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_color::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_time::prelude::*;
+use bevy_utils::HashMap;
+
+use crate::Sprite3d;
+
+/// Group a sprite belongs to, so that a whole category of sprites can be hidden, faded or tinted at once,
+/// ie: all map markers, using [`Sprite3dGroups`].
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+#[reflect(Component)]
+pub struct Sprite3dGroup(pub u32);
+
+/// Visibility, alpha and tint of the sprites of each [`Sprite3dGroup`].
+/// Applied when batching, on top of the sprites' own visibility and color, so that changing a group doesn't touch
+/// its sprites. Groups without settings render as usual.
+/// Requires the [`Sprite3dGroupPlugin`].
+#[derive(Resource, Reflect, Clone, PartialEq, Default, Debug)]
+#[reflect(Resource)]
+pub struct Sprite3dGroups {
+    pub groups: HashMap<u32, Sprite3dGroupSettings>,
+}
+
+/// How the sprites of a [`Sprite3dGroup`] are rendered.
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dGroupSettings {
+    pub visible: bool,
+    /// Multiplies the alpha of the group's sprites.
+    pub alpha: f32,
+    /// Multiplies the color of the group's sprites, in linear space.
+    pub tint: Color,
+    /// Alpha the group is fading to, and how much it changes per second.
+    pub fade: Option<(f32, f32)>,
+}
+
+impl Default for Sprite3dGroupSettings {
+    fn default() -> Self {
+        Self { visible: true, alpha: 1.0, tint: Color::WHITE, fade: None }
+    }
+}
+
+impl Sprite3dGroups {
+    /// Settings of a group, which are the defaults if it has none.
+    pub fn get(&self, group: u32) -> Sprite3dGroupSettings {
+        self.groups.get(&group).copied().unwrap_or_default()
+    }
+
+    pub fn get_mut(&mut self, group: u32) -> &mut Sprite3dGroupSettings {
+        self.groups.entry(group).or_default()
+    }
+
+    pub fn hide(&mut self, group: u32) {
+        self.get_mut(group).visible = false;
+    }
+
+    pub fn show(&mut self, group: u32) {
+        self.get_mut(group).visible = true;
+    }
+
+    /// Sets the alpha of a group right away, stopping its fade, if any.
+    pub fn set_alpha(&mut self, group: u32, alpha: f32) {
+        let settings = self.get_mut(group);
+        settings.alpha = alpha;
+        settings.fade = None;
+    }
+
+    /// Fades the alpha of a group from its current value to another, over a duration.
+    pub fn fade(&mut self, group: u32, alpha: f32, duration: Duration) {
+        let settings = self.get_mut(group);
+        match duration.is_zero() {
+            true => settings.alpha = alpha,
+            false => settings.fade = Some((alpha, (alpha - settings.alpha).abs() / duration.as_secs_f32())),
+        }
+    }
+
+    pub fn tint(&mut self, group: u32, tint: Color) {
+        self.get_mut(group).tint = tint;
+    }
+
+    /// Brings a group back to its default settings.
+    pub fn reset(&mut self, group: u32) {
+        self.groups.remove(&group);
+    }
+
+    pub(crate) fn is_visible(&self, group: Option<&Sprite3dGroup>) -> bool {
+        group.and_then(|group| self.groups.get(&group.0)).is_none_or(|settings| settings.visible)
+    }
+
+    /// Sprite with the alpha and tint of its group applied.
+    pub(crate) fn apply<'a>(&self, group: Option<&Sprite3dGroup>, mut sprite: Cow<'a, Sprite3d>) -> Cow<'a, Sprite3d> {
+        let Some(settings) = group.and_then(|group| self.groups.get(&group.0)) else { return sprite };
+        if settings.alpha == 1.0 && settings.tint == Color::WHITE { return sprite };
+        let (color, tint) = (sprite.color.to_linear(), settings.tint.to_linear());
+        let color = LinearRgba::new(
+            color.red * tint.red,
+            color.green * tint.green,
+            color.blue * tint.blue,
+            color.alpha * tint.alpha * settings.alpha,
+        );
+        sprite.to_mut().color = color.into();
+        sprite
+    }
+}
+
+/// Applies [`Sprite3dGroups`] to the sprites of each [`Sprite3dGroup`], and fades groups over time.
+/// Without it, groups render as usual.
+pub struct Sprite3dGroupPlugin;
+
+impl Plugin for Sprite3dGroupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sprite3dGroups>();
+        app.register_type::<Sprite3dGroup>();
+        app.register_type::<Sprite3dGroups>();
+        app.add_systems(Update, fade_sprite_groups);
+    }
+}
+
+pub(crate) fn fade_sprite_groups(mut groups: ResMut<Sprite3dGroups>, time: Res<Time>) {
+    if groups.groups.values().all(|settings| settings.fade.is_none()) { return };
+    for settings in groups.groups.values_mut() {
+        let Some((target, speed)) = settings.fade else { continue };
+        let step = speed * time.delta_secs();
+        settings.alpha = match settings.alpha < target {
+            true => (settings.alpha + step).min(target),
+            false => (settings.alpha - step).max(target),
+        };
+        if settings.alpha == target {
+            settings.fade = None;
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use bevy_app::prelude::*;
+use bevy_color::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_math::Vec3;
+use bevy_reflect::prelude::*;
+use bevy_time::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::{Sprite3dBounds, VertexColorSpace};
+
+/// Feedback shown on a sprite while it is hovered or [`Sprite3dSelected`], ie: interactive objects and units.
+/// Applied when batching, so the sprite's own color and transform are left untouched, and nothing needs to be
+/// restored once the highlight ends.
+/// `hovered` is kept up to date by [`Sprite3dPickingPlugin`](crate::Sprite3dPickingPlugin) with the `picking`
+/// feature, or can be set by hand. Highlights are shown and pulse with the [`Sprite3dHighlightPlugin`].
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+#[require(Sprite3dBounds)]
+pub struct Sprite3dHighlight {
+    /// Multiplies the sprite's color. Channels above 1 brighten it.
+    pub tint: Color,
+    /// Color of a rectangle drawn around the sprite's bounds. Only drawn by
+    /// [`Sprite3dPickingPlugin`](crate::Sprite3dPickingPlugin), with gizmos.
+    pub outline: Option<Color>,
+    /// How much the sprite grows and shrinks, relative to its size. 0 disables the pulse.
+    pub pulse: f32,
+    /// Pulses per second.
+    pub pulse_frequency: f32,
+    pub hovered: bool,
+    /// If the highlight is currently shown.
+    shown: bool,
+    /// Time since the highlight was shown.
+    elapsed: Duration,
+}
+
+/// Highlights a sprite with a [`Sprite3dHighlight`] regardless of whether it is hovered, ie: selected units.
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct Sprite3dSelected;
+
+impl Sprite3dHighlight {
+    pub fn new(tint: Color) -> Self {
+        Self {
+            tint,
+            outline: None,
+            pulse: 0.0,
+            pulse_frequency: 2.0,
+            hovered: false,
+            shown: false,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn with_outline(mut self, outline: Color) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+
+    pub fn with_pulse(mut self, pulse: f32, pulse_frequency: f32) -> Self {
+        self.pulse = pulse;
+        self.pulse_frequency = pulse_frequency;
+        self
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.shown
+    }
+
+    /// Scale of the sprite at this point of the pulse.
+    pub fn scale(&self) -> f32 {
+        if !self.shown || self.pulse == 0.0 { return 1.0 };
+        let phase = self.elapsed.as_secs_f32() * self.pulse_frequency * TAU;
+        1.0 + self.pulse * 0.5 * (1.0 - phase.cos())
+    }
+
+    pub(crate) fn apply_transform(&self, sprite_transf: &GlobalTransform) -> GlobalTransform {
+        let scale = self.scale();
+        if scale == 1.0 { return *sprite_transf };
+        sprite_transf.mul_transform(Transform::from_scale(Vec3::splat(scale)))
+    }
+
+    pub(crate) fn apply_color(&self, color: [f32; 4], color_space: VertexColorSpace) -> [f32; 4] {
+        if !self.shown { return color };
+        let tint = color_space.convert(self.tint);
+        std::array::from_fn(|i| color[i] * tint[i])
+    }
+}
+
+/// Brightens sprites by half.
+impl Default for Sprite3dHighlight {
+    fn default() -> Self {
+        Self::new(LinearRgba::rgb(1.5, 1.5, 1.5).into())
+    }
+}
+
+/// Shows the [`Sprite3dHighlight`]s of hovered and [`Sprite3dSelected`] sprites, and animates their pulse.
+pub struct Sprite3dHighlightPlugin;
+
+impl Plugin for Sprite3dHighlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_highlights);
+    }
+}
+
+// Shows the highlights of hovered and selected sprites, and advances their pulse.
+pub(crate) fn update_highlights(
+    mut highlights: Query<(&mut Sprite3dHighlight, Has<Sprite3dSelected>)>,
+    time: Res<Time>,
+) {
+    for (mut highlight, selected) in &mut highlights {
+        let shown = highlight.hovered || selected;
+        if shown != highlight.shown {
+            highlight.shown = shown;
+            highlight.elapsed = Duration::ZERO;
+        } else if shown && highlight.pulse != 0.0 {
+            highlight.elapsed += time.delta();
+        }
+    }
+}
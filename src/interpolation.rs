@@ -0,0 +1,39 @@
+use bevy_ecs::prelude::*;
+use bevy_math::{Affine3A, Vec3};
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+/// Local transform of a sprite as of the previous [`FixedUpdate`](bevy_app::FixedUpdate) tick.
+/// Adding this to a sprite opts it into interpolation: it is rendered at a transform between its previous and
+/// current [`Transform`], based on how far along the next fixed timestep the frame is.
+/// This keeps sprites moved in fixed-timestep systems (ie: physics) from stuttering at low tick rates.
+/// Updated automatically at the start of every fixed timestep.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Default, Debug)]
+pub struct PreviousTransform(pub Transform);
+
+pub(crate) fn store_previous_transforms(mut sprites: Query<(&Transform, &mut PreviousTransform)>) {
+    for (transf, mut prev_transf) in &mut sprites {
+        prev_transf.0 = *transf;
+    }
+}
+
+/// Interpolates a sprite's global transform between its previous and current local transform.
+/// The parent's contribution is recovered from the global transform, so sprites in hierarchies work as well.
+pub(crate) fn interpolate_transform(
+    global_transf: &GlobalTransform,
+    transf: &Transform,
+    prev_transf: &PreviousTransform,
+    overstep: f32,
+) -> GlobalTransform {
+    let prev = prev_transf.0;
+    let interpolated = Transform {
+        translation: prev.translation.lerp(transf.translation, overstep),
+        rotation: prev.rotation.slerp(transf.rotation, overstep),
+        scale: prev.scale.lerp(transf.scale, overstep),
+    };
+    if transf.scale.cmpeq(Vec3::ZERO).any() {
+        return *global_transf;
+    }
+    let parent: Affine3A = global_transf.affine() * transf.compute_affine().inverse();
+    GlobalTransform::from(parent * interpolated.compute_affine())
+}
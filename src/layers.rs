@@ -0,0 +1,134 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_utils::HashMap;
+
+use crate::{Sprite3dBillboard, Sprite3dSystems};
+
+/// Named layers of sprites, each with a draw order and default rendering settings, ie: "background", "characters"
+/// and "effects", so that sprite rendering policy is managed in one place.
+/// Sprites join a layer with a [`Sprite3dLayer`]. Their own [`Sprite3dDrawOrder`](crate::Sprite3dDrawOrder),
+/// [`Sprite3dBillboard`] and [`Sprite3dNoPrepass`](crate::Sprite3dNoPrepass) take precedence over their layer's.
+/// Changing layers regenerates the sprites on them.
+/// Requires the [`Sprite3dLayerPlugin`].
+#[derive(Resource, Reflect, Clone, PartialEq, Default, Debug)]
+#[reflect(Resource)]
+pub struct Sprite3dLayers {
+    pub layers: HashMap<String, Sprite3dLayerSettings>,
+}
+
+impl Sprite3dLayers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_layer(mut self, name: impl Into<String>, settings: Sprite3dLayerSettings) -> Self {
+        self.insert(name, settings);
+        self
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, settings: Sprite3dLayerSettings) {
+        self.layers.insert(name.into(), settings);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Sprite3dLayerSettings> {
+        self.layers.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Sprite3dLayerSettings> {
+        self.layers.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Sprite3dLayerSettings> {
+        self.layers.get_mut(name)
+    }
+}
+
+/// Settings of a layer of [`Sprite3dLayers`].
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dLayerSettings {
+    /// Draw order of the layer's sprites, relative to other batches. Batches sort by draw order before depth,
+    /// so a higher draw order renders over lower ones.
+    pub draw_order: i32,
+    /// If false, the layer's sprites don't cast shadows. They are rendered in separate batches.
+    pub cast_shadows: bool,
+    /// Billboard turning the layer's sprites towards the camera, if any.
+    pub billboard: Option<Sprite3dBillboard>,
+    /// If false, the layer's sprites are excluded from prepasses, like with a [`Sprite3dNoPrepass`](crate::Sprite3dNoPrepass).
+    pub prepass: bool,
+}
+
+impl Default for Sprite3dLayerSettings {
+    fn default() -> Self {
+        Self {
+            draw_order: 0,
+            cast_shadows: true,
+            billboard: None,
+            prepass: true,
+        }
+    }
+}
+
+impl Sprite3dLayerSettings {
+    pub fn new(draw_order: i32) -> Self {
+        Self { draw_order, ..Self::default() }
+    }
+
+    pub fn without_shadows(mut self) -> Self {
+        self.cast_shadows = false;
+        self
+    }
+
+    pub fn with_billboard(mut self, billboard: Sprite3dBillboard) -> Self {
+        self.billboard = Some(billboard);
+        self
+    }
+
+    pub fn without_prepass(mut self) -> Self {
+        self.prepass = false;
+        self
+    }
+}
+
+/// Puts a sprite on a layer of [`Sprite3dLayers`], ie: `Sprite3dLayer("effects")`.
+/// Sprites on a layer missing from [`Sprite3dLayers`] use the default [`Sprite3dLayerSettings`].
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[reflect(Component)]
+pub struct Sprite3dLayer(pub &'static str);
+
+/// Settings of the layer a sprite is on, kept in sync with [`Sprite3dLayers`].
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub(crate) struct ResolvedSprite3dLayer(pub Sprite3dLayerSettings);
+
+/// Gives sprites on a [`Sprite3dLayer`] the settings of their layer in [`Sprite3dLayers`].
+pub struct Sprite3dLayerPlugin;
+
+impl Plugin for Sprite3dLayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sprite3dLayers>();
+        app.register_type::<Sprite3dLayer>();
+        app.register_type::<Sprite3dLayers>();
+        app.add_systems(PostUpdate, resolve_sprite_layers.before(Sprite3dSystems));
+    }
+}
+
+// Gives sprites the settings of their layer, when either changes.
+pub(crate) fn resolve_sprite_layers(
+    mut commands: Commands,
+    sprites: Query<(Entity, Ref<Sprite3dLayer>, Option<&ResolvedSprite3dLayer>)>,
+    mut removed: RemovedComponents<Sprite3dLayer>,
+    layers: Res<Sprite3dLayers>,
+) {
+    for entity in removed.read() {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.remove::<ResolvedSprite3dLayer>();
+        }
+    }
+    for (entity, layer, resolved) in &sprites {
+        if !layer.is_changed() && !layers.is_changed() && resolved.is_some() { continue };
+        let settings = layers.get(layer.0).copied().unwrap_or_default();
+        if resolved.map(|resolved| resolved.0) != Some(settings) {
+            commands.entity(entity).insert(ResolvedSprite3dLayer(settings));
+        }
+    }
+}
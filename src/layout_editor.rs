@@ -0,0 +1,173 @@
+use bevy_asset::prelude::*;
+use bevy_color::palettes::css;
+use bevy_ecs::prelude::*;
+use bevy_egui::EguiContexts;
+use bevy_gizmos::prelude::*;
+use bevy_image::prelude::*;
+use bevy_input::prelude::*;
+use bevy_math::{Isometry3d, Ray3d, Vec2, Vec3};
+use bevy_render::prelude::*;
+use bevy_sprite::Anchor;
+use bevy_transform::prelude::*;
+use bevy_window::Window;
+
+use crate::{SizedMaterial, Sprite3d, Sprite3dBounds, Sprite3dDebugUi, SpriteMaterial3d};
+
+/// Distance from a handle, in logical pixels, within which clicks grab it.
+const HANDLE_RADIUS: f32 = 8.0;
+
+/// Handle of the selected sprite being dragged, with the state of the sprite when the drag started.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct LayoutDrag {
+    handle: LayoutHandle,
+    /// Transform of the sprite's plane, so that the sprite moving while dragged doesn't feed back into the drag.
+    plane: GlobalTransform,
+    translation: Vec3,
+    anchor: Vec2,
+    size: Vec2,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum LayoutHandle {
+    /// Moves the anchor of the sprite, keeping its quad in place.
+    Anchor,
+    /// Resizes the sprite from its top right corner, keeping its anchor in place.
+    Size,
+}
+
+// Selects sprites clicked in the viewport, and drags the handles of the selected sprite, writing back to its
+// anchor, custom size and translation.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn edit_sprite_layout<M: SizedMaterial>(
+    mut contexts: EguiContexts,
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut sprites: Query<(Entity, &mut Sprite3d, &mut Transform, &GlobalTransform, &SpriteMaterial3d<M>, &ViewVisibility)>,
+    materials: Res<Assets<M>>,
+    images: Res<Assets<Image>>,
+    mut debug_ui: ResMut<Sprite3dDebugUi<M>>,
+) {
+    if !debug_ui.edit_layout || mouse.just_released(MouseButton::Left) {
+        debug_ui.drag = None;
+    }
+    if !debug_ui.edit_layout { return };
+    let Some(cursor) = windows.iter().find_map(Window::cursor_position) else { return };
+    let Some((camera, camera_transf, ray)) = cursor_ray(&cameras, cursor) else { return };
+    let bounds = |sprite: &Sprite3d, material: &SpriteMaterial3d<M>| {
+        let sprite_mat_size = materials.get(&material.0).and_then(|sprite_mat| sprite_mat.size(&images))?;
+        Some(Sprite3dBounds::from_sprite(sprite, sprite_mat_size))
+    };
+
+    // Starts dragging a handle of the selected sprite, or selects the nearest sprite under the cursor
+    if mouse.just_pressed(MouseButton::Left) {
+        let over_ui = contexts.try_ctx_mut().is_some_and(|ctx| ctx.is_pointer_over_area() || ctx.wants_pointer_input());
+        if over_ui { return };
+        let selected = debug_ui.selected.and_then(|entity| sprites.get(entity).ok());
+        let grabbed = selected.and_then(|(_, sprite, transf, global_transf, material, _)| {
+            let bounds = bounds(sprite, material)?;
+            let handle = [(LayoutHandle::Anchor, Vec2::ZERO), (LayoutHandle::Size, bounds.rect.max)]
+                .into_iter()
+                .find(|(_, position)| {
+                    let position = global_transf.transform_point(position.extend(0.0));
+                    let viewport_rect = camera.logical_viewport_rect().unwrap_or_default();
+                    camera
+                        .world_to_viewport(camera_transf, position)
+                        .is_ok_and(|position| position.distance(cursor - viewport_rect.min) <= HANDLE_RADIUS)
+                })?
+                .0;
+            Some(LayoutDrag {
+                handle,
+                plane: *global_transf,
+                translation: transf.translation,
+                anchor: sprite.anchor.as_vec(),
+                size: bounds.rect.size(),
+            })
+        });
+        debug_ui.drag = grabbed;
+        if grabbed.is_none() {
+            debug_ui.selected = sprites
+                .iter()
+                .filter(|(.., visibility)| visibility.get())
+                .filter_map(|(entity, sprite, _, global_transf, material, _)| {
+                    let (distance, local) = intersect_plane(ray, global_transf)?;
+                    bounds(sprite, material)?.rect.contains(local).then_some((entity, distance))
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(entity, _)| entity);
+        }
+        return;
+    }
+
+    // Drags the grabbed handle along the plane the sprite was on when grabbed
+    let Some(drag) = debug_ui.drag else { return };
+    let Some(entity) = debug_ui.selected else { return };
+    let Ok((_, mut sprite, mut transf, ..)) = sprites.get_mut(entity) else { return };
+    let Some((_, local)) = intersect_plane(ray, &drag.plane) else { return };
+    match drag.handle {
+        LayoutHandle::Anchor => {
+            let anchor = drag.anchor + local / drag.size.max(Vec2::splat(f32::EPSILON));
+            sprite.anchor = Anchor::Custom(anchor);
+            transf.translation = drag.translation + transf.rotation * (transf.scale * local.extend(0.0));
+        },
+        LayoutHandle::Size => {
+            // The top right corner is at (0.5 - anchor) * size from the anchor
+            let extent = Vec2::splat(0.5) - drag.anchor;
+            let size = Vec2::new(
+                if extent.x > f32::EPSILON { local.x / extent.x } else { drag.size.x },
+                if extent.y > f32::EPSILON { local.y / extent.y } else { drag.size.y },
+            );
+            sprite.custom_size = Some(size.max(Vec2::splat(0.001)));
+        },
+    }
+}
+
+// Draws the bounds and handles of the selected sprite.
+pub(crate) fn draw_sprite_layout<M: SizedMaterial>(
+    sprites: Query<(&Sprite3d, &GlobalTransform, &SpriteMaterial3d<M>)>,
+    materials: Res<Assets<M>>,
+    images: Res<Assets<Image>>,
+    debug_ui: Res<Sprite3dDebugUi<M>>,
+    mut gizmos: Gizmos,
+) {
+    if !debug_ui.edit_layout { return };
+    let Some(Ok((sprite, transf, material))) = debug_ui.selected.map(|entity| sprites.get(entity)) else { return };
+    let Some(sprite_mat_size) = materials.get(&material.0).and_then(|sprite_mat| sprite_mat.size(&images)) else { return };
+    let rect = Sprite3dBounds::from_sprite(sprite, sprite_mat_size).rect;
+    let corners = [rect.min, rect.min.with_y(rect.max.y), rect.max, rect.max.with_y(rect.min.y), rect.min];
+    gizmos.linestrip(corners.map(|corner| transf.transform_point(corner.extend(0.0))), css::YELLOW);
+    let (_, rotation, translation) = transf.to_scale_rotation_translation();
+    let handle_radius = rect.size().min_element() * 0.05;
+    gizmos.circle(Isometry3d::new(translation, rotation), handle_radius, css::ORANGE);
+    let size_handle = transf.transform_point(rect.max.extend(0.0));
+    gizmos.rect(Isometry3d::new(size_handle, rotation), Vec2::splat(handle_radius * 2.0), css::AQUA);
+}
+
+/// Ray under the cursor, from the camera rendered last whose viewport contains it.
+fn cursor_ray<'a>(
+    cameras: &'a Query<(&Camera, &GlobalTransform)>,
+    cursor: Vec2,
+) -> Option<(&'a Camera, &'a GlobalTransform, Ray3d)> {
+    cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .filter(|(camera, _)| camera.logical_viewport_rect().is_some_and(|rect| rect.contains(cursor)))
+        .max_by_key(|(camera, _)| camera.order)
+        .and_then(|(camera, camera_transf)| {
+            let viewport_rect = camera.logical_viewport_rect()?;
+            let ray = camera.viewport_to_world(camera_transf, cursor - viewport_rect.min).ok()?;
+            Some((camera, camera_transf, ray))
+        })
+}
+
+/// Distance along a ray to the plane of a sprite, and where the ray crosses it in the sprite's local space.
+fn intersect_plane(ray: Ray3d, plane: &GlobalTransform) -> Option<(f32, Vec2)> {
+    let affine = plane.affine();
+    let normal = Vec3::from(affine.matrix3.z_axis);
+    let facing = normal.dot(*ray.direction);
+    if facing.abs() < f32::EPSILON { return None };
+    let distance = normal.dot(Vec3::from(affine.translation) - ray.origin) / facing;
+    if distance < 0.0 { return None };
+    let local = affine.inverse().transform_point3(ray.get_point(distance));
+    Some((distance, local.truncate()))
+}
@@ -0,0 +1,154 @@
+use bevy_color::{Color, Mix};
+use bevy_math::{FloatExt, Rect, Vec2};
+use bevy_reflect::prelude::*;
+
+use crate::Sprite3d;
+
+#[cfg(feature = "tweening")]
+use bevy_app::prelude::*;
+#[cfg(feature = "tweening")]
+use bevy_ecs::prelude::*;
+#[cfg(feature = "tweening")]
+use bevy_tweening::{AnimationSystem, Lens, Targetable, TweenCompleted};
+
+// Lenses animating a field of a Sprite3d, from a start value to an end value, where `ratio` goes from 0 to 1.
+// With the `tweening` feature, they implement bevy_tweening's `Lens<Sprite3d>`, so that they can be animated by an
+// `Animator<Sprite3d>`.
+
+/// Animates [`Sprite3d::color`], ie: for fades and flashes.
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dColorLens {
+    pub start: Color,
+    pub end: Color,
+}
+
+impl Sprite3dColorLens {
+    pub fn lerp(&mut self, target: &mut Sprite3d, ratio: f32) {
+        target.color = self.start.mix(&self.end, ratio);
+    }
+}
+
+/// Animates [`Sprite3d::custom_size`].
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dSizeLens {
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+impl Sprite3dSizeLens {
+    pub fn lerp(&mut self, target: &mut Sprite3d, ratio: f32) {
+        target.custom_size = Some(self.start.lerp(self.end, ratio));
+    }
+}
+
+/// Animates [`Sprite3d::rect`], ie: for revealing a sprite gradually.
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dRectLens {
+    pub start: Rect,
+    pub end: Rect,
+}
+
+impl Sprite3dRectLens {
+    pub fn lerp(&mut self, target: &mut Sprite3d, ratio: f32) {
+        target.rect = Some(Rect {
+            min: self.start.min.lerp(self.end.min, ratio),
+            max: self.start.max.lerp(self.end.max, ratio),
+        });
+    }
+}
+
+/// Animates how much of a sprite is shown, from 0 (empty) to 1 (full), ie: for health and progress bars.
+/// Crops the sprite's `rect` and `custom_size` from the right, so that it fills from its left edge if its anchor is
+/// on the left, ie: [`Anchor::CenterLeft`](bevy_sprite::Anchor::CenterLeft).
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dFillLens {
+    /// Region of the texture shown when full, in pixels.
+    pub rect: Rect,
+    /// Size of the sprite when full.
+    pub size: Vec2,
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Sprite3dFillLens {
+    pub fn lerp(&mut self, target: &mut Sprite3d, ratio: f32) {
+        let fill = self.start.lerp(self.end, ratio).clamp(0.0, 1.0);
+        target.rect = Some(Rect {
+            min: self.rect.min,
+            max: self.rect.max.with_x(self.rect.min.x + self.rect.width() * fill),
+        });
+        target.custom_size = Some(self.size * Vec2::new(fill, 1.0));
+    }
+}
+
+#[cfg(feature = "tweening")]
+macro_rules! impl_tweening_lens {
+    ($($lens:ty),*) => {$(
+        impl Lens<Sprite3d> for $lens {
+            fn lerp(&mut self, target: &mut dyn Targetable<Sprite3d>, ratio: f32) {
+                <$lens>::lerp(self, &mut **target, ratio);
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "tweening")]
+impl_tweening_lens!(Sprite3dColorLens, Sprite3dSizeLens, Sprite3dRectLens, Sprite3dFillLens);
+
+/// Plays the [`bevy_tweening::Animator`]s of sprites, ie: with the lenses of this module.
+#[cfg(feature = "tweening")]
+pub struct Sprite3dTweeningPlugin;
+
+#[cfg(feature = "tweening")]
+impl Plugin for Sprite3dTweeningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TweenCompleted>();
+        app.add_systems(Update, bevy_tweening::component_animator_system::<Sprite3d>
+            .in_set(AnimationSystem::AnimationUpdate)
+        );
+    }
+}
+
+#[cfg(all(test, feature = "tweening"))]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+    use bevy_tweening::{Animator, Tween};
+
+    use crate::*;
+
+    #[test]
+    fn tweens_interpolate_sprites() {
+        let mut app = App::new();
+        let plugin = Sprite3dPlugin::<StandardMaterial>::default();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin, plugin, Sprite3dTweeningPlugin));
+        app.init_asset::<Image>();
+        app.init_asset::<Mesh>();
+        app.init_asset::<StandardMaterial>();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(250)));
+        let fade = Sprite3dColorLens { start: Color::BLACK, end: Color::WHITE };
+        let rect = Rect::new(0.0, 0.0, 32.0, 8.0);
+        let fill = Sprite3dFillLens { rect, size: Vec2::new(2.0, 0.5), start: 0.0, end: 1.0 };
+        let fading = app.world_mut().spawn((
+            Sprite3d::default(),
+            Animator::new(Tween::new(EaseFunction::Linear, Duration::from_secs(1), fade)),
+        )).id();
+        let filling = app.world_mut().spawn((
+            Sprite3d::default(),
+            Animator::new(Tween::new(EaseFunction::Linear, Duration::from_secs(1), fill)),
+        )).id();
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let ratio = app.world().resource::<Time>().elapsed_secs();
+        assert!(ratio > 0.0 && ratio < 1.0);
+        let color = app.world().get::<Sprite3d>(fading).unwrap().color.to_linear();
+        assert!((color.red - Color::BLACK.mix(&Color::WHITE, ratio).to_linear().red).abs() < 1e-4);
+        let sprite = app.world().get::<Sprite3d>(filling).unwrap();
+        assert!(sprite.rect.unwrap().max.abs_diff_eq(Vec2::new(32.0 * ratio, 8.0), 1e-4));
+        assert!(sprite.custom_size.unwrap().abs_diff_eq(Vec2::new(2.0 * ratio, 0.5), 1e-4));
+    }
+}
@@ -1,95 +1,1268 @@
 use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
 
-use bevy_math::{Rect, Vec2, Vec3A};
+use bevy_math::{Affine3A, IVec3, Rect, Vec2, Vec3, Vec3A};
 use bevy_render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
-use bevy_render::primitives::Aabb;
+use bevy_render::primitives::{Aabb, Frustum};
 use bevy_render::render_asset::RenderAssetUsages;
-use bevy_render::view::VisibilitySystems;
-use bevy_utils::HashMap;
+use bevy_render::view::{check_visibility, RenderLayers, VisibilitySystems, VisibleEntities};
+use bevy_utils::tracing::info_span;
+use bevy_utils::{HashMap, HashSet, Instant};
+use std::cmp::Ordering;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use bevy_ecs::query::QueryData;
+use bevy_ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
 
 use bevy_color::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_app::prelude::*;
 use bevy_image::prelude::*;
+use bevy_image::ImageSampler;
 use bevy_render::prelude::*;
 use bevy_pbr::prelude::*;
+use bevy_pbr::{ExtendedMaterial, MaterialExtension, NotShadowCaster, ParallaxMappingMethod, UvChannel};
 use bevy_sprite::Anchor;
 use bevy_transform::prelude::*;
-use bevy_asset::prelude::*;
-use bevy_reflect::prelude::*;
+use bevy_asset::{load_internal_asset, prelude::*, UntypedAssetId};
+use bevy_reflect::{prelude::*, Struct};
+use bevy_core::Name;
+
+use crate::sky::SKY_DEPTH_BIAS;
+use crate::batch_config::sync_batch_configs;
+use crate::capture::capture_sprite_materials;
+use crate::layers::ResolvedSprite3dLayer;
+use crate::loading::{placeholder_material, Placeholders};
+use crate::memory::{fit_memory_budget, quad_bytes, MemoryBudgetState, QUAD_INDEX_BYTES};
+use crate::packing::{pack_octahedral, pack_snorm8, pack_unorm16, pack_unorm8, PACKING_SHADER_HANDLE};
+use crate::render_target::sync_render_targets;
+use crate::render_transform::{update_render_transforms, SpriteRenderTransform};
+use crate::sorted_view::filter_view_batches;
+use crate::view::{nearest_distance_squared, SpriteView};
+use crate::warning::{check_sprite_warnings, SpriteWarningState};
+
+mod anchor;
+#[cfg(feature = "animated-image")]
+mod animated_image;
+mod animation;
+#[cfg(feature = "aseprite")]
+mod aseprite;
+mod atlas;
+mod batch_config;
+mod billboard;
+mod bounds;
+mod capture;
+mod clip_rect;
+#[cfg(any(feature = "rapier", feature = "avian"))]
+mod colliders;
+#[cfg(feature = "debug-ui")]
+mod debug_ui;
+mod dissolve;
+#[cfg(feature = "ecs-tilemap")]
+mod ecs_tilemap;
+mod enabled;
+mod fade;
+mod flash;
+mod gpu_points;
+mod ground_shadow;
+mod group;
+mod highlight;
+mod interpolation;
+mod layers;
+#[cfg(feature = "debug-ui")]
+mod layout_editor;
+mod lens;
+mod light;
+mod loading;
+mod mask;
+mod memory;
+mod movement;
+mod nameplate;
+mod near_fade;
+mod packing;
+mod palette;
+mod parallax;
+#[cfg(feature = "particles")]
+mod particles;
+mod path;
+#[cfg(feature = "picking")]
+mod picking;
+mod pixel_camera;
+mod point;
+mod point_culling;
+mod polygon;
+mod presets;
+mod queue;
+mod render_target;
+mod render_transform;
+mod screen_scale;
+mod sky;
+mod sorted_view;
+mod surface;
+mod sway;
+#[doc(hidden)]
+pub mod test_support;
+#[cfg(test)]
+mod test_utils;
+mod texture_array;
+#[cfg(feature = "tiled")]
+mod tiled;
+mod tilemap;
+mod up_axis;
+mod validation;
+mod view;
+mod warning;
+mod waterline;
+
+pub use anchor::*;
+#[cfg(feature = "animated-image")]
+pub use animated_image::*;
+pub use animation::*;
+#[cfg(feature = "aseprite")]
+pub use aseprite::*;
+pub use atlas::*;
+pub use batch_config::*;
+pub use billboard::*;
+pub use bounds::*;
+pub use capture::*;
+pub use clip_rect::*;
+#[cfg(any(feature = "rapier", feature = "avian"))]
+pub use colliders::*;
+#[cfg(feature = "debug-ui")]
+pub use debug_ui::*;
+pub use dissolve::*;
+#[cfg(feature = "ecs-tilemap")]
+pub use ecs_tilemap::*;
+pub use enabled::*;
+pub use fade::*;
+pub use flash::*;
+pub use gpu_points::*;
+pub use ground_shadow::*;
+pub use group::*;
+pub use highlight::*;
+pub use interpolation::*;
+pub use layers::*;
+pub use lens::*;
+pub use light::*;
+pub use loading::*;
+pub use mask::*;
+pub use memory::*;
+pub use movement::*;
+pub use nameplate::*;
+pub use near_fade::*;
+pub use packing::*;
+pub use palette::*;
+pub use parallax::*;
+#[cfg(feature = "particles")]
+pub use particles::*;
+pub use path::*;
+#[cfg(feature = "picking")]
+pub use picking::*;
+pub use pixel_camera::*;
+pub use point::*;
+pub use point_culling::*;
+pub use polygon::*;
+pub use presets::*;
+pub use queue::*;
+pub use render_target::*;
+pub use screen_scale::*;
+pub use sky::*;
+pub use sorted_view::*;
+pub use surface::*;
+pub use sway::*;
+pub use texture_array::*;
+#[cfg(feature = "tiled")]
+pub use tiled::*;
+pub use tilemap::*;
+pub use up_axis::*;
+pub use validation::*;
+pub use warning::*;
+pub use waterline::*;
 
 /// Adds the ability to render sprites in a 3D space.
+/// Optional features have plugins of their own, ie: [`Sprite3dAnimationPlugin`] or [`Sprite3dFadePlugin`], so that
+/// apps only run the systems of the features they use.
 pub struct Sprite3dPlugin<M: SizedMaterial = StandardMaterial> {
+    /// Optional limit on how much sprite vertex data gets regenerated per frame.
+    /// When set, sprites that haven't changed reuse their vertex data from previous frames,
+    /// and large bursts of changes (level loads, etc) are spread out over multiple frames.
+    /// Only the batches whose sprites changed, got removed or moved to another material are then rewritten,
+    /// unless [`Self::memory_budget`] or [`Self::double_buffered`] is set.
+    pub budget: Option<BatchBudget>,
+    /// Schedule that [`Sprite3dSystems`] runs in. Defaults to [`PostUpdate`].
+    pub schedule: InternedScheduleLabel,
+    /// If true, [`Sprite3dSystems`] runs after transform propagation and visibility checks.
+    /// Disable this when those run at custom times, and order [`Sprite3dSystems`] manually instead.
+    pub default_ordering: bool,
+    /// Color space that sprite colors are written to vertices in.
+    pub vertex_color_space: VertexColorSpace,
+    /// If true, sprites whose materials have equal [`SizedMaterial::content_hash`]es share batches,
+    /// even though they use different handles. Useful when materials get created per sprite by code or scene loaders.
+    pub dedup_materials: bool,
+    /// If true, the plugin holds strong handles to the materials of existing sprites, so that materials don't
+    /// unload while sprites still use them, ie: when sprites were given weak handles, and the last strong handle
+    /// gets dropped. Handles are released once no sprite uses their material.
+    pub retain_materials: bool,
+    /// If set, sprites are split into separate batches per cube of this size, based on their position.
+    /// Batches get bounds that fit their sprites, so that chunks outside of the view (or hidden behind walls,
+    /// with occlusion culling) are skipped as a whole. Smaller chunks cull more precisely, at the cost of more draw calls.
+    pub chunk_size: Option<f32>,
+    /// Optional limits on the memory taken by batches.
+    pub memory_budget: Option<MemoryBudget>,
+    /// If true, each batch alternates between two meshes, writing one while the other was last given to the
+    /// renderer, so that a batch mesh is never modified while it is being extracted. Doubles batch mesh memory.
+    pub double_buffered: bool,
+    /// Frames a sprite's material or texture can take to load before a [`Sprite3dWarning`] is sent about it.
+    pub load_warning_frames: u32,
+    /// What is rendered for sprites whose material isn't loaded yet.
+    pub loading_policy: LoadingPolicy,
+    /// How sprites with rects that don't fit their texture are rendered.
+    pub rect_validation: RectValidation,
+    /// Components added to every batch entity the plugin spawns, on top of its own.
+    pub batch_bundle: Option<BatchBundleFactory>,
+    /// If true, batch vertices use the compact formats of [`SizedMaterial::supported_vertex_packing`], which roughly
+    /// halves their size, and the bandwidth needed to upload them, at the cost of precision.
+    pub packed_vertices: bool,
+    /// If true, batches get the same vertex data, in the same order, across runs of the same build, given sprites
+    /// with the same [`Entity`] ids and components, ie: for lockstep and replay games that compare frames.
+    /// Sprites are written in entity order within batches, rather than in the order of archetypes and internal hash
+    /// maps, and [`BatchBudget::Time`] regenerates every changed sprite, as elapsed time isn't reproducible.
+    /// Entity ids depend on the order entities are spawned and despawned in, so replays need to reproduce it.
+    /// Vertex data is computed with floats, so it may differ across platforms and compilers that round them
+    /// differently, ie: with fused multiply-adds or another implementation of trigonometric functions.
+    /// See [`MeshBatch::vertex_hash`] to compare the output of runs.
+    pub deterministic: bool,
+    /// World axis sprites stand along. Turns the quads of sprites and queued sprites so that the top of their
+    /// image points along it, and billboards turn around it.
+    pub up_axis: UpAxis,
     phantom: PhantomData<M>,
 }
 
 impl<M: SizedMaterial> Default for Sprite3dPlugin<M> {
     fn default() -> Self {
         Self {
+            budget: None,
+            schedule: PostUpdate.intern(),
+            default_ordering: true,
+            vertex_color_space: VertexColorSpace::default(),
+            dedup_materials: false,
+            retain_materials: false,
+            chunk_size: None,
+            memory_budget: None,
+            double_buffered: false,
+            load_warning_frames: 300,
+            loading_policy: LoadingPolicy::default(),
+            rect_validation: RectValidation::default(),
+            batch_bundle: None,
+            packed_vertices: false,
+            deterministic: false,
+            up_axis: UpAxis::default(),
             phantom: PhantomData,
         }
     }
 }
 
+impl<M: SizedMaterial> Sprite3dPlugin<M> {
+    pub fn with_budget(mut self, budget: BatchBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.schedule = schedule.intern();
+        self
+    }
+
+    pub fn without_default_ordering(mut self) -> Self {
+        self.default_ordering = false;
+        self
+    }
+
+    pub fn with_vertex_color_space(mut self, vertex_color_space: VertexColorSpace) -> Self {
+        self.vertex_color_space = vertex_color_space;
+        self
+    }
+
+    pub fn with_material_dedup(mut self) -> Self {
+        self.dedup_materials = true;
+        self
+    }
+
+    pub fn with_material_retention(mut self) -> Self {
+        self.retain_materials = true;
+        self
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: f32) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    pub fn with_memory_budget(mut self, memory_budget: MemoryBudget) -> Self {
+        self.memory_budget = Some(memory_budget);
+        self
+    }
+
+    pub fn with_double_buffering(mut self) -> Self {
+        self.double_buffered = true;
+        self
+    }
+
+    pub fn with_load_warning_frames(mut self, frames: u32) -> Self {
+        self.load_warning_frames = frames;
+        self
+    }
+
+    pub fn with_loading_policy(mut self, loading_policy: LoadingPolicy) -> Self {
+        self.loading_policy = loading_policy;
+        self
+    }
+
+    pub fn with_rect_validation(mut self, rect_validation: RectValidation) -> Self {
+        self.rect_validation = rect_validation;
+        self
+    }
+
+    pub fn with_packed_vertices(mut self) -> Self {
+        self.packed_vertices = true;
+        self
+    }
+
+    pub fn with_deterministic_batching(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
+
+    pub fn with_up_axis(mut self, up_axis: UpAxis) -> Self {
+        self.up_axis = up_axis;
+        self
+    }
+
+    /// Adds the bundle returned by `factory` to every batch entity, ie: [`NotShadowReceiver`](bevy_pbr::NotShadowReceiver)
+    /// or project-specific markers.
+    pub fn with_batch_bundle<B: Bundle>(mut self, factory: impl Fn() -> B + Send + Sync + 'static) -> Self {
+        self.batch_bundle = Some(BatchBundleFactory(Arc::new(move |entity_commands| {
+            entity_commands.insert(factory());
+        })));
+        self
+    }
+}
+
 impl<M: SizedMaterial> Plugin for Sprite3dPlugin<M> {
     fn build(&self, app: &mut App) {
-        app.init_resource::<MeshBatch<M>>();
-        app.configure_sets(PostUpdate, Sprite3dSystems
+        if !app.is_plugin_added::<Sprite3dCorePlugin>() {
+            app.add_plugins(Sprite3dCorePlugin);
+        }
+        if self.packed_vertices {
+            load_internal_asset!(app, PACKING_SHADER_HANDLE, "packing.wgsl", Shader::from_wgsl);
+        }
+        app.insert_resource(MeshBatch::<M>::new(self));
+        app.init_resource::<Sprite3dQueue<M>>();
+        app.init_resource::<Sprite3dBatchConfigs<M>>();
+        app.add_event::<Sprite3dMemoryExceeded>();
+        app.add_event::<Sprite3dWarning>();
+        app.add_event::<Sprite3dReady>();
+        app.add_event::<Sprite3dCaptured<M>>();
+        app.add_observer(capture_sprite_materials::<M>);
+        app.insert_resource(SpriteWarningState::<M>::new(self.load_warning_frames));
+        if self.default_ordering {
+            app.configure_sets(self.schedule, Sprite3dSystems
+                .after(TransformSystem::TransformPropagate)
+                .after(VisibilitySystems::CheckVisibility),
+            );
+        }
+        app.configure_sets(self.schedule, Sprite3dSystems.run_if(sprite3d_enabled));
+        app.add_systems(
+            self.schedule,
+            (update_render_transforms::<M>.before(batch_sprites::<M>), batch_sprites::<M>, update_sprite_bounds::<M>)
+                .in_set(Sprite3dSystems)
+        );
+        app.add_systems(self.schedule, trim_sprites::<M>.before(Sprite3dSystems));
+        #[cfg(feature = "particles")]
+        app.add_systems(self.schedule, queue_sprite_particles::<M>
             .after(TransformSystem::TransformPropagate)
-            .after(VisibilitySystems::VisibilityPropagate),
+            .after(VisibilitySystems::VisibilityPropagate)
+            .before(Sprite3dSystems)
+            .run_if(sprite3d_enabled)
         );
-        app.add_systems(
-            PostUpdate,
-            batch_sprites::<M>.in_set(Sprite3dSystems)
+        app.add_systems(self.schedule, queue_ground_shadows::<M>
+            .after(TransformSystem::TransformPropagate)
+            .after(VisibilitySystems::VisibilityPropagate)
+            .before(Sprite3dSystems)
+            .run_if(sprite3d_enabled)
         );
+        app.add_systems(self.schedule, discard_queued_sprites::<M>.before(Sprite3dSystems).run_if(not(sprite3d_enabled)));
+        app.add_systems(self.schedule, toggle_batch_visibility::<M>.after(Sprite3dSystems));
+        app.add_systems(self.schedule, check_sprite_warnings::<M>.before(Sprite3dSystems));
+        app.add_systems(self.schedule, sync_batch_configs::<M>.before(Sprite3dSystems));
+        app.add_systems(self.schedule, sync_render_targets::<M>.before(Sprite3dSystems));
+        app.add_systems(self.schedule, refresh_batch_visibility::<M>.after(Sprite3dSystems));
+        app.add_systems(self.schedule, filter_view_batches::<M>.after(refresh_batch_visibility::<M>));
+        app.add_systems(PostUpdate, spawn_tile_sprites::<M>.before(TransformSystem::TransformPropagate));
+    }
+
+    fn finish(&self, app: &mut App) {
+        if self.loading_policy != LoadingPolicy::Placeholder { return };
+        let world = app.world_mut();
+        let material = world.resource_scope(|world, mut images: Mut<Assets<Image>>| {
+            let mut materials = world.get_resource_mut::<Assets<StandardMaterial>>()?;
+            Some(placeholder_material(&mut images, &mut materials))
+        });
+        world.resource_mut::<MeshBatch<M>>().placeholders.material = material;
+    }
+}
+
+/// Systems shared by all [`Sprite3dPlugin`]s, regardless of material.
+struct Sprite3dCorePlugin;
+
+impl Plugin for Sprite3dCorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, check_visibility::<With<Sprite3d>>.in_set(VisibilitySystems::CheckVisibility));
+        app.add_systems(FixedFirst, store_previous_transforms);
+        app.init_resource::<Sprite3dEnabled>();
+        app.register_type::<Sprite3dEnabled>();
+        app.add_systems(PostUpdate, toggle_point_batch_visibility.before(VisibilitySystems::VisibilityPropagate));
+        app.init_resource::<GroundShadowMaterials>();
+        app.register_type::<Sprite3dGroundShadow>();
+        #[cfg(feature = "particles")]
+        app.register_type::<Sprite3dParticles>();
     }
 }
 
 #[derive(SystemSet, Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub struct Sprite3dSystems;
 
+/// Inserts components into a batch entity, see [`Sprite3dPlugin::with_batch_bundle`].
+#[derive(Clone)]
+pub struct BatchBundleFactory(Arc<dyn Fn(&mut EntityCommands) + Send + Sync>);
+
+impl std::fmt::Debug for BatchBundleFactory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BatchBundleFactory").finish_non_exhaustive()
+    }
+}
+
+/// Limits how much sprite vertex data is regenerated in a single frame.
+/// Sprites that exceed the budget keep their vertex data from a previous frame (or aren't rendered
+/// yet if they are new), and are regenerated in later frames.
+/// Changed sprites closest to a camera are regenerated first.
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub enum BatchBudget {
+    /// Maximum number of sprites regenerated per frame. At least one sprite is regenerated, so that a budget of 0
+    /// still lets sprites render eventually.
+    Sprites(usize),
+    /// Maximum time spent regenerating sprites per frame.
+    Time(Duration),
+}
+
+/// Color space of the colors written to sprite vertices.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum VertexColorSpace {
+    /// Linear RGB, which is what [`StandardMaterial`] expects.
+    #[default]
+    Linear,
+    /// Non-linear sRGB, for custom materials that expect it.
+    Srgb,
+}
+
+impl VertexColorSpace {
+    fn convert(self, color: Color) -> [f32; 4] {
+        match self {
+            Self::Linear => color.to_linear().to_f32_array(),
+            Self::Srgb => color.to_srgba().to_f32_array(),
+        }
+    }
+}
+
+#[derive(QueryData)]
+struct SpriteQuery<M: SizedMaterial> {
+    entity: Entity,
+    sprite: Ref<'static, Sprite3d>,
+    material: Ref<'static, SpriteMaterial3d<M>>,
+    global_transform: &'static GlobalTransform,
+    render: Ref<'static, SpriteRenderTransform>,
+    sway: Option<Ref<'static, Sprite3dSway>>,
+    dissolve: Option<Ref<'static, Sprite3dDissolve>>,
+    palette: Option<Ref<'static, Sprite3dPalette>>,
+    group: Option<Ref<'static, Sprite3dGroup>>,
+    draw_order: Option<Ref<'static, Sprite3dDrawOrder>>,
+    user_key: Option<Ref<'static, Sprite3dBatchKey>>,
+    parts: Option<Ref<'static, Sprite3dParts>>,
+    polygon: Option<Ref<'static, Sprite3dPolygon>>,
+    corners: Option<Ref<'static, Sprite3dQuad>>,
+    crossfade: Option<Ref<'static, Sprite3dCrossfade>>,
+    path: Option<Ref<'static, SpritePath3d>>,
+    sky: Option<&'static Sprite3dSky>,
+    clip_rect: Option<Ref<'static, Sprite3dClipRect>>,
+    highlight: Option<Ref<'static, Sprite3dHighlight>>,
+    layer: Option<Ref<'static, ResolvedSprite3dLayer>>,
+    shadow_only: Option<Ref<'static, Sprite3dShadowOnly>>,
+    no_prepass: Option<Ref<'static, Sprite3dNoPrepass>>,
+    render_layers: Option<Ref<'static, RenderLayers>>,
+    visibility: &'static ViewVisibility,
+}
+
+impl<M: SizedMaterial> SpriteQueryItem<'_, M> {
+    /// If true, the sprite is visible, its [`Sprite3dGroup`] isn't hidden, and it is within its `VisibilityRange`
+    /// from the camera nearest to it.
+    fn is_rendered(&self, groups: &Sprite3dGroups) -> bool {
+        self.visibility.get() && groups.is_visible(self.group.as_deref()) && self.render.range_alpha > 0.0
+    }
+
+    fn batch_key(&self, chunk_size: Option<f32>) -> BatchKey<M> {
+        BatchKey {
+            material: self.material.0.clone_weak(),
+            render_layers: match self.shadow_only {
+                Some(_) => RenderLayers::layer(SHADOW_ONLY_LAYER),
+                None => self.render_layers.as_deref().cloned().unwrap_or_default(),
+            },
+            filter: self.sprite.filter,
+            draw_order: match (self.draw_order.as_deref(), self.layer.as_deref()) {
+                (Some(order), _) => order.0,
+                (None, Some(layer)) => layer.0.draw_order,
+                (None, None) => 0,
+            },
+            user_key: self.user_key.as_deref().map(|key| key.0).unwrap_or_default(),
+            chunk: batch_chunk(self.global_transform.translation(), chunk_size),
+            no_prepass: self.no_prepass.is_some() || self.layer.as_deref().is_some_and(|layer| !layer.0.prepass),
+            no_shadows: self.layer.as_deref().is_some_and(|layer| !layer.0.cast_shadows),
+            sky: self.sky.is_some(),
+        }
+    }
+
+    fn is_changed(&self) -> bool {
+        self.sprite.is_changed()
+            || self.material.is_changed()
+            || self.render.is_changed()
+            || self.sway.as_ref().is_some_and(|sway| sway.is_changed())
+            || self.dissolve.as_ref().is_some_and(|dissolve| dissolve.is_changed())
+            || self.palette.as_ref().is_some_and(|palette| palette.is_changed())
+            || self.highlight.as_ref().is_some_and(|highlight| highlight.is_changed())
+            || self.group.as_ref().is_some_and(|group| group.is_changed())
+            || self.layer.as_ref().is_some_and(|layer| layer.is_changed())
+            || self.render_layers.as_ref().is_some_and(|layers| layers.is_changed())
+            || self.shadow_only.as_ref().is_some_and(|shadow_only| shadow_only.is_changed())
+            || self.no_prepass.as_ref().is_some_and(|no_prepass| no_prepass.is_changed())
+            || self.draw_order.as_ref().is_some_and(|order| order.is_changed())
+            || self.user_key.as_ref().is_some_and(|key| key.is_changed())
+            || self.parts.as_ref().is_some_and(|parts| parts.is_changed())
+            || self.corners.as_ref().is_some_and(|corners| corners.is_changed())
+            || self.crossfade.as_ref().is_some_and(|crossfade| crossfade.is_changed())
+            || self.polygon.as_ref().is_some_and(|polygon| polygon.is_changed())
+            || self.clip_rect.as_ref().is_some_and(|clip_rect| clip_rect.is_changed())
+            || self.path.as_ref().is_some_and(|path| path.is_changed() || path.alignment == PathAlignment::Billboard)
+    }
+
+    /// Number of quads the sprite is made of, including its [`Sprite3dParts`], [`Sprite3dPolygon`]
+    /// and [`Sprite3dCrossfade`], doubled for sprites visible from both sides.
+    /// Sprites repeated along a [`SpritePath3d`] count once, so this only serves as a hint.
+    fn quad_count(&self) -> usize {
+        let own_quad_count = self.polygon.as_ref().map_or(1, |polygon| polygon.quad_count());
+        let crossfade_quad_count = self.crossfade.as_ref().map_or(0, |_| 1);
+        let part_quad_count = self.parts.as_ref().map_or(0, |parts| parts.0.len());
+        let face_count = match self.sprite.facing {
+            Facing::Both => 2,
+            Facing::Front | Facing::Back => 1,
+        };
+        (own_quad_count + crossfade_quad_count + part_quad_count) * face_count
+    }
+
+    /// Vertex data of the sprite, followed by that of its [`Sprite3dParts`].
+    /// The sprite is given separately from the item, so that it can be a validated copy of it.
+    /// Crossfading sprites are preceded by the frame they blend from, so that it renders behind them.
+    /// Quads are faded out as a whole when the sprite has a fading [`Sprite3dNearFade`] or is within the margins
+    /// of its `VisibilityRange`, and cut by its [`Sprite3dClipRect`], if any.
+    /// Repeated for every point of its [`SpritePath3d`], if any.
+    fn quads<'a>(
+        &'a self,
+        sprite: &'a Sprite3d,
+        sprite_transf: &GlobalTransform,
+        sprite_mat_size: Vec2,
+        color_space: VertexColorSpace,
+        views: &[SpriteView],
+        up_axis: UpAxis,
+    ) -> impl Iterator<Item = SpriteQuad> + 'a {
+        let sway = self.sway.as_deref();
+        let corners = self.corners.as_deref().map(|quad| quad.corners);
+        let polygon = self.polygon.as_deref();
+        let crossfade = self.crossfade.as_deref();
+        let alpha = crossfade.map_or(1.0, Sprite3dCrossfade::ratio);
+        let near_alpha = self.render.near_alpha * self.render.range_alpha;
+        let clip_rect = self.clip_rect.as_deref();
+        let dissolve = self.dissolve.as_deref().map_or(0.0, |dissolve| dissolve.0);
+        let palette = self.palette.as_deref().map_or(0, |palette| palette.0);
+        let highlight = self.highlight.as_deref();
+        let parts = self.parts.as_deref().map(|parts| parts.0.as_slice()).unwrap_or_default();
+        let path_transforms = self.path.as_ref().map(|path| path.transforms(sprite_transf, views, up_axis));
+        let own_transform = path_transforms.is_none().then_some(*sprite_transf);
+        let transforms = own_transform.into_iter().chain(path_transforms.into_iter().flatten());
+        transforms.flat_map(move |transf| {
+            let blended_quad = crossfade.map(|crossfade| {
+                let blended_sprite = crossfade.blended_sprite(sprite);
+                let blended_size = sprite_size(&blended_sprite, sprite_mat_size);
+                let mut quad = sprite_quad(&blended_sprite, &transf, sprite_mat_size, blended_size, color_space, corners);
+                quad.color[3] *= 1.0 - alpha;
+                quad.with_sway(sway)
+            });
+            let sprite_size = sprite_size(sprite, sprite_mat_size);
+            let own_quad = sprite_quad(sprite, &transf, sprite_mat_size, sprite_size, color_space, corners).with_sway(sway);
+            let own_quads = polygon_quads(own_quad, sprite, polygon).map(move |mut quad| {
+                quad.color[3] *= alpha;
+                quad
+            });
+            let part_quads = parts.iter().map(move |part| {
+                let (part_sprite, part_transf) = part_sprite(sprite, part, &transf);
+                SpriteQuad::new(&part_sprite, &part_transf, sprite_mat_size, color_space).with_sway(sway)
+            });
+            blended_quad.into_iter().chain(own_quads).chain(part_quads)
+        })
+        .map(move |mut quad| {
+            quad.color[3] *= near_alpha;
+            if let Some(highlight) = highlight {
+                quad.color = highlight.apply_color(quad.color, color_space);
+            }
+            quad.dissolve = dissolve;
+            quad.palette = palette;
+            quad
+        })
+        .flat_map(move |quad| {
+            let clipped = clip_rect.map(|clip_rect| clip_rect.clip(quad));
+            let unclipped = clip_rect.is_none().then_some(quad);
+            unclipped.into_iter().chain(clipped.into_iter().flatten())
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn batch_sprites<M: SizedMaterial>(
     mut commands: Commands,
-    mut sprites: Query<(&Sprite3d, &SpriteMaterial3d<M>, &GlobalTransform, &InheritedVisibility)>,
+    sprites: Query<SpriteQuery<M>>,
+    cameras: Query<(Entity, &GlobalTransform, &Camera, Option<&Projection>, Option<&OrthographicProjection>, Has<Sprite3dSortedView>)>,
     mut mesh_batch: ResMut<MeshBatch<M>>,
-    materials: Res<Assets<M>>,
+    mut queue: ResMut<Sprite3dQueue<M>>,
+    mut materials: ResMut<Assets<M>>,
     mut meshes: ResMut<Assets<Mesh>>,
-    images: Res<Assets<Image>>,
+    mut images: ResMut<Assets<Image>>,
+    asset_server: Option<Res<AssetServer>>,
+    (mut image_events, mut material_events): (EventReader<AssetEvent<Image>>, EventReader<AssetEvent<M>>),
+    mut memory_events: EventWriter<Sprite3dMemoryExceeded>,
+    mut ready_events: EventWriter<Sprite3dReady>,
+    groups: Option<Res<Sprite3dGroups>>,
+    (mut removed_sprites, mut removed_materials): (RemovedComponents<Sprite3d>, RemovedComponents<SpriteMaterial3d<M>>),
 ) {
     let mesh_batch = &mut *mesh_batch;
+    // Without the Sprite3dGroupPlugin, every group renders as usual
+    let no_groups = Sprite3dGroups::default();
+    let groups_changed = groups.as_ref().is_some_and(|groups| groups.is_changed());
+    let groups = groups.as_deref().unwrap_or(&no_groups);
+    let changed_images: HashSet<AssetId<Image>> = image_events
+        .read()
+        .filter(|event| is_asset_changed(event))
+        .map(asset_event_id)
+        .collect();
+    let changed_materials: HashSet<AssetId<M>> = material_events
+        .read()
+        .filter(|event| is_asset_changed(event))
+        .map(asset_event_id)
+        .collect();
+
+    if mesh_batch.retain_materials {
+        mesh_batch.retain_sprite_materials(sprites.iter().map(|item| item.material.0.id()), &mut materials);
+    }
 
     // Clears mesh batch
+    let clear_span = info_span!("sprite3d_clear_batches").entered();
+    mesh_batch.remove_stale_canonical_materials(&changed_materials, &materials);
+    mesh_batch.remove_stale_material_variants(&changed_images, &changed_materials);
+    mesh_batch.remove_invalidated_meshes(&mut commands);
     mesh_batch.remove_unloaded_meshes(&materials, &mut commands);
     mesh_batch.clear_meshes(&mut meshes);
+    clear_span.exit();
+    let views: Vec<SpriteView> = cameras
+        .iter()
+        .filter(|(_, _, camera, _, _, _)| camera.is_active)
+        .map(|(_, transform, camera, projection, orthographic, _)| SpriteView::new(transform, camera, projection, orthographic))
+        .collect();
+    let sorted_views: Vec<(Entity, Vec3A)> = cameras
+        .iter()
+        .filter(|(_, _, camera, _, _, sorted)| camera.is_active && *sorted)
+        .map(|(entity, transform, ..)| (entity, transform.translation_vec3a()))
+        .collect();
 
-    // Submits sprite data to mesh batch
-    for (sprite, sprite_mat, sprite_transf, sprite_vis) in &mut sprites {
-        let visible = sprite_vis.get();
-        if !visible { continue };
-        mesh_batch.submit(
-            sprite,
-            &sprite_mat.0,
-            sprite_transf,
-            &mut meshes,
-            &materials, 
-            &images,
-            &mut commands
+    // Budgeted batching regenerates only a portion of the sprites, and reuses cached vertex data for the rest
+    if let Some(budget) = mesh_batch.budget {
+        mesh_batch.waiting.retain(|&entity| sprites.contains(entity));
+        mesh_batch.handle_asset_events(&changed_images, changed_materials, &materials, &images);
+
+        // Forgets sprites that are no longer rendered, only rewriting the batches they were in
+        for entity in removed_sprites.read().chain(removed_materials.read()) {
+            mesh_batch.forget_cached_sprite(entity);
+        }
+        let filter_span = info_span!("sprite3d_filter_visible").entered();
+        let mut changed = Vec::new();
+        for item in &sprites {
+            if item.sprite.is_added() {
+                mesh_batch.unready.insert(item.entity);
+            }
+            let visible = item.is_rendered(groups);
+            let is_unloaded = || mesh_batch.cache
+                .get(&item.entity)
+                .is_some_and(|(batch_key, _)| !materials.contains(&batch_key.material));
+            if !visible || is_unloaded() {
+                mesh_batch.forget_cached_sprite(item.entity);
+                continue;
+            }
+            let entity = item.entity;
+            let is_uncached = !mesh_batch.cache.contains_key(&entity) && !mesh_batch.waiting.contains(&entity);
+            let is_group_changed = groups_changed && item.group.is_some();
+            if item.is_changed() || is_group_changed || is_uncached || mesh_batch.pending.contains(&entity) {
+                let distance = nearest_distance_squared(&views, item.global_transform.translation_vec3a());
+                changed.push((entity, distance));
+            }
+        }
+        match mesh_batch.deterministic {
+            true => changed.sort_unstable_by(|(a, a_distance), (b, b_distance)| a_distance.total_cmp(b_distance).then(a.cmp(b))),
+            false => changed.sort_unstable_by(|(_, a), (_, b)| a.total_cmp(b)),
+        }
+        filter_span.exit();
+        let regenerate_span = info_span!("sprite3d_compute_vertices").entered();
+        mesh_batch.pending.clear();
+        let start = Instant::now();
+        for (i, &(entity, _)) in changed.iter().enumerate() {
+            let within_budget = match budget {
+                BatchBudget::Sprites(max_sprites) => i < max_sprites.max(1),
+                BatchBudget::Time(max_time) => mesh_batch.deterministic || start.elapsed() < max_time,
+            };
+            if !within_budget {
+                mesh_batch.pending.extend(changed[i..].iter().map(|(entity, _)| *entity));
+                break;
+            }
+            let item = sprites.get(entity).unwrap();
+            let sprite_transf = item.render.transform;
+            let color_space = mesh_batch.vertex_color_space;
+            let sprite_mat_size = mesh_batch.material_lookup(&item.material.0, &materials, &images).size;
+            match compute_quads(&item, &sprite_transf, sprite_mat_size, color_space, mesh_batch.rect_validation, groups, &views, mesh_batch.up_axis) {
+                Some(quads) => {
+                    let batch_key = mesh_batch.resolve_batch_key(item.batch_key(mesh_batch.chunk_size), &materials, &images);
+                    mesh_batch.dirty_batches.insert(batch_key.clone());
+                    if let Some((previous_key, _)) = mesh_batch.cache.insert(entity, (batch_key, quads)) {
+                        mesh_batch.dirty_batches.insert(previous_key);
+                    }
+                    mesh_batch.waiting.remove(&entity);
+                },
+                None => {
+                    // Sprites moved to a material that isn't loaded leave their previous batch, unless it is kept
+                    if mesh_batch.loading_policy != LoadingPolicy::LastFrame {
+                        mesh_batch.forget_cached_sprite(entity);
+                    }
+                    mesh_batch.waiting.insert(entity);
+                },
+            }
+        }
+        regenerate_span.exit();
+
+        // Submits cached sprite data to mesh batch, one batch at a time.
+        // Incrementally, only the batches whose sprites changed are cleared and rewritten.
+        let group_span = info_span!("sprite3d_group_batches").entered();
+        let incremental = mesh_batch.is_incremental();
+        if incremental {
+            mesh_batch.mark_queued_batches(&queue, &materials, &images);
+            mesh_batch.clear_dirty_meshes(&mut meshes);
+        }
+        let cache = std::mem::take(&mut mesh_batch.cache);
+        let mut cached: Vec<_> = cache
+            .iter()
+            .filter(|(_, (batch_key, _))| !incremental || mesh_batch.dirty_batches.contains(batch_key))
+            .map(|(entity, (batch_key, quads))| (batch_key, (*entity, quads)))
+            .collect();
+        if mesh_batch.deterministic {
+            cached.sort_unstable_by_key(|(_, (entity, _))| *entity);
+        }
+        if let Some(memory_budget) = &mesh_batch.memory_budget {
+            fit_memory_budget(
+                memory_budget,
+                &mut mesh_batch.memory_state,
+                quad_bytes(mesh_batch.attributes, mesh_batch.packing),
+                &mut cached,
+                |(batch_key, _)| batch_key.material.id().untyped(),
+                |(_, (_, quads))| quads.len(),
+                |(_, (entity, _))| sprites
+                    .get(*entity)
+                    .map_or(f32::INFINITY, |item| nearest_distance_squared(&views, item.global_transform.translation_vec3a())),
+                &mut memory_events,
+            );
+        }
+        match mesh_batch.deterministic {
+            true => cached.sort_unstable_by_key(|(batch_key, (entity, _))| (*batch_key, *entity)),
+            false => cached.sort_unstable_by_key(|(batch_key, _)| *batch_key),
+        }
+        group_span.exit();
+        let write_span = info_span!("sprite3d_write_vertices").entered();
+        for group in cached.chunk_by(|(a, _), (b, _)| a == b) {
+            let batch_key = group[0].0;
+            mesh_batch.count_sprites(batch_key, group.len());
+            let mesh = mesh_batch.get_or_spawn_mesh(batch_key, &mut meshes, &mut materials, &mut images, asset_server.as_deref(), &mut commands);
+            reserve_sprite_quads(mesh, group.iter().map(|(_, (_, quads))| quads.len()).sum());
+            let mut writer = SpriteVertexWriter::new(mesh);
+            for (_, (entity, quads)) in group {
+                for quad in quads.iter() {
+                    writer.write(quad);
+                }
+                if !mesh_batch.unready.is_empty() && mesh_batch.unready.remove(entity) {
+                    ready_events.send(Sprite3dReady { entity: *entity });
+                }
+            }
+        }
+        if mesh_batch.placeholders.material.is_some() {
+            let mut waiting: Vec<Entity> = mesh_batch.waiting.iter().copied().collect();
+            if mesh_batch.deterministic {
+                waiting.sort_unstable();
+            }
+            for entity in waiting {
+                let Ok(item) = sprites.get(entity) else { continue };
+                if cache.contains_key(&entity) || !item.is_rendered(groups) { continue };
+                let sprite_transf = item.render.transform;
+                let render_layers = item.batch_key(mesh_batch.chunk_size).render_layers;
+                mesh_batch.placeholders.push(&item.sprite, &sprite_transf, &render_layers, mesh_batch.vertex_color_space);
+            }
+        }
+        mesh_batch.cache = cache;
+        mesh_batch.submit_queued_sprites(&mut queue, &mut meshes, &mut materials, &mut images, asset_server.as_deref(), &mut commands);
+        mesh_batch.write_warm_ups(&mut meshes, &mut materials, &mut images, asset_server.as_deref(), &mut commands);
+        mesh_batch.placeholders.write(&mut meshes, &mut mesh_batch.quad_indices, &mut commands);
+        mesh_batch.forget_removed_sprites(&sprites);
+        write_span.exit();
+        let _finish_span = info_span!("sprite3d_finish_meshes").entered();
+        mesh_batch.finish_meshes(&mut meshes, &materials, &sorted_views, &mut commands);
+        return;
+    }
+
+    // Groups visible sprites by batch so that each batch is looked up once, and written to contiguously
+    let filter_span = info_span!("sprite3d_filter_visible").entered();
+    mesh_batch.unready.extend(sprites.iter().filter(|item| item.sprite.is_added()).map(|item| item.entity));
+    let mut visible_sprites: Vec<_> = sprites
+        .iter()
+        .filter(|item| item.is_rendered(groups))
+        .map(|item| {
+            (mesh_batch.resolve_batch_key(item.batch_key(mesh_batch.chunk_size), &materials, &images), item)
+        })
+        .collect();
+    filter_span.exit();
+    let group_span = info_span!("sprite3d_group_batches").entered();
+    if let Some(memory_budget) = &mesh_batch.memory_budget {
+        fit_memory_budget(
+            memory_budget,
+            &mut mesh_batch.memory_state,
+            quad_bytes(mesh_batch.attributes, mesh_batch.packing),
+            &mut visible_sprites,
+            |(batch_key, _)| batch_key.material.id().untyped(),
+            |(_, item)| item.quad_count(),
+            |(_, item)| nearest_distance_squared(&views, item.global_transform.translation_vec3a()),
+            &mut memory_events,
         );
     }
+    match mesh_batch.deterministic {
+        true => visible_sprites.sort_unstable_by(|(a, a_item), (b, b_item)| a.cmp(b).then(a_item.entity.cmp(&b_item.entity))),
+        false => visible_sprites.sort_unstable_by(|(a, _), (b, _)| a.cmp(b)),
+    }
+    group_span.exit();
+
+    // Submits sprite data to mesh batch
+    let write_span = info_span!("sprite3d_write_vertices").entered();
+    let mut loading_sprites = Vec::new();
+    for group in visible_sprites.chunk_by(|(a, _), (b, _)| a == b) {
+        let batch_key = &group[0].0;
+        let Some(sprite_mat_size) = mesh_batch.material_size(batch_key.material.id(), &materials, &images) else {
+            loading_sprites.extend(group.iter().map(|(batch_key, item)| (batch_key, item)));
+            continue;
+        };
+        mesh_batch.count_sprites(batch_key, group.len());
+        let mesh = mesh_batch.get_or_spawn_mesh(batch_key, &mut meshes, &mut materials, &mut images, asset_server.as_deref(), &mut commands);
+        reserve_sprite_quads(mesh, group.iter().map(|(_, item)| item.quad_count()).sum());
+        let mut writer = SpriteVertexWriter::new(mesh);
+        for (_, item) in group {
+            let Some(sprite) = mesh_batch.rect_validation.validate(&item.sprite, sprite_mat_size) else { continue };
+            let sprite = groups.apply(item.group.as_deref(), sprite);
+            let sprite_transf = item.render.transform;
+            let quads = item.quads(&sprite, &sprite_transf, sprite_mat_size, mesh_batch.vertex_color_space, &views, mesh_batch.up_axis);
+            if mesh_batch.loading_policy == LoadingPolicy::LastFrame {
+                let quads: Vec<SpriteQuad> = quads.collect();
+                for quad in &quads {
+                    writer.write(quad);
+                }
+                mesh_batch.last_quads.insert(item.entity, (batch_key.clone(), quads));
+            } else {
+                for quad in quads {
+                    writer.write(&quad);
+                }
+            }
+            if !mesh_batch.unready.is_empty() && mesh_batch.unready.remove(&item.entity) {
+                ready_events.send(Sprite3dReady { entity: item.entity });
+            }
+        }
+    }
+    mesh_batch.write_loading_sprites(
+        &loading_sprites,
+        (&mut meshes, &mut materials, &mut images),
+        asset_server.as_deref(),
+        &mut commands,
+    );
+    mesh_batch.submit_queued_sprites(&mut queue, &mut meshes, &mut materials, &mut images, asset_server.as_deref(), &mut commands);
+    mesh_batch.write_warm_ups(&mut meshes, &mut materials, &mut images, asset_server.as_deref(), &mut commands);
+    mesh_batch.placeholders.write(&mut meshes, &mut mesh_batch.quad_indices, &mut commands);
+    mesh_batch.forget_removed_sprites(&sprites);
+    write_span.exit();
+    let _finish_span = info_span!("sprite3d_finish_meshes").entered();
+    mesh_batch.finish_meshes(&mut meshes, &materials, &sorted_views, &mut commands);
+}
+
+/// Computes the vertex data of a sprite, and its parts.
+/// Returns None if the size of the sprite's material isn't known, ie: its material, or the image it depends on,
+/// is not yet loaded.
+#[allow(clippy::too_many_arguments)]
+fn compute_quads<M: SizedMaterial>(
+    item: &SpriteQueryItem<'_, M>,
+    sprite_transf: &GlobalTransform,
+    sprite_mat_size: Option<Vec2>,
+    color_space: VertexColorSpace,
+    rect_validation: RectValidation,
+    groups: &Sprite3dGroups,
+    views: &[SpriteView],
+    up_axis: UpAxis,
+) -> Option<Vec<SpriteQuad>> {
+    let sprite_mat_size = sprite_mat_size?;
+    let Some(sprite) = rect_validation.validate(&item.sprite, sprite_mat_size) else { return Some(Vec::new()) };
+    let sprite = groups.apply(item.group.as_deref(), sprite);
+    Some(item.quads(&sprite, sprite_transf, sprite_mat_size, color_space, views, up_axis).collect())
+}
+
+/// Sprite drawn by a part of a composite sprite, along with its transform.
+/// Parts mirror along with the sprite they belong to when it is flipped.
+fn part_sprite(sprite: &Sprite3d, part: &Sprite3dPart, sprite_transf: &GlobalTransform) -> (Sprite3d, GlobalTransform) {
+    let mut offset = part.offset;
+    if sprite.flip_x { offset.x = -offset.x };
+    if sprite.flip_y { offset.y = -offset.y };
+    let part_sprite = Sprite3d {
+        flip_x: sprite.flip_x != part.flip_x,
+        flip_y: sprite.flip_y != part.flip_y,
+        custom_size: part.custom_size,
+        rect: part.rect,
+        uv_rect: None,
+        secondary_rect: None,
+        ..sprite.clone()
+    };
+    (part_sprite, sprite_transf.mul_transform(Transform::from_translation(offset)))
+}
+
+/// Size of a sprite, given the size of its material.
+/// Diagonally flipped sprites without a custom size have their width and height swapped.
+fn sprite_size(sprite: &Sprite3d, sprite_mat_size: Vec2) -> Vec2 {
+    let size = match (sprite.custom_size, sprite.rect, sprite.uv_rect) {
+        (Some(custom_size), _, _)       => return custom_size,
+        (None, Some(rect), _)           => rect.size(),
+        (None, None, Some(uv_rect))     => uv_rect.size() * sprite_mat_size,
+        _ => sprite_mat_size,
+    };
+    match sprite.flip_d {
+        true => Vec2::new(size.y, size.x),
+        false => size,
+    }
 }
 
 #[derive(Component, Reflect, Clone, PartialEq, Debug)]
 pub struct SpriteMaterial3d<M: SizedMaterial>(pub Handle<M>);
 
-#[derive(Component, Reflect, Clone, PartialEq, Default, Debug)]
-#[require(Transform, Visibility, InheritedVisibility, ViewVisibility)]
+/// Marks an entity spawned by the plugin to render a batch of sprites.
+/// Maps the batch entity back to the material, render layers, draw order, [`Sprite3dBatchKey`] and chunk its sprites share.
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+pub struct Sprite3dBatch<M: SizedMaterial> {
+    pub material: AssetId<M>,
+    pub render_layers: RenderLayers,
+    pub draw_order: i32,
+    pub key: u32,
+    /// Spatial chunk of the batch, in multiples of [`Sprite3dPlugin::chunk_size`]. Zero if batches aren't chunked.
+    pub chunk: IVec3,
+}
+
+/// Statistics of a batch entity, updated whenever its sprites are batched, ie: for editors and in-game consoles
+/// that display how sprites get batched. Unlike [`Sprite3dBatch`], it doesn't depend on the material type, so
+/// the batches of every [`Sprite3dPlugin`] can be queried at once.
+/// The copies rendered for [`Sprite3dSortedView`] cameras don't have one, so that their sprites aren't counted twice.
+#[derive(Component, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Sprite3dBatchInfo {
+    pub material: UntypedAssetId,
+    /// Sprites in the batch, including queued sprites. Sprites with [`Sprite3dParts`] or repeated along a
+    /// [`SpritePath3d`] count once.
+    pub sprites: usize,
+    /// Quads in the batch.
+    pub quads: usize,
+    /// Estimated bytes the vertex and index data of the batch takes.
+    pub bytes: usize,
+}
+
+/// Splits sprites that share a material into separate batches, ie: one per room or per team.
+/// Batch entities can then be hidden or sorted independently, by looking up their [`Sprite3dBatch::key`].
+/// Sprites without this component have a key of 0.
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub struct Sprite3dBatchKey(pub u32);
+
+/// Orders the batch of a sprite relative to other batches, regardless of their material.
+/// Batches with a higher draw order are drawn after (on top of, for transparent materials) those with a lower one.
+/// Sprites without this component have a draw order of 0.
+/// Sprites with different draw orders are rendered in separate batches, using a copy of the material
+/// offset with [`SizedMaterial::with_depth_bias`].
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub struct Sprite3dDrawOrder(pub i32);
+
+/// Render layer of the batches of [`Sprite3dShadowOnly`] sprites.
+/// Cameras don't render it by default, so add it to the [`RenderLayers`] of the lights that should cast their shadows.
+pub const SHADOW_ONLY_LAYER: usize = 31;
+
+/// Makes a sprite cast shadows without being rendered itself, ie: invisible proxies casting cardboard cutout shadows.
+/// Shadow-only sprites are rendered in separate batches, on [`SHADOW_ONLY_LAYER`] instead of the sprite's own
+/// [`RenderLayers`]. Only lights with that layer cast their shadows.
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct Sprite3dShadowOnly;
+
+/// Excludes a sprite from depth, normal and motion vector prepasses, ie: soft transparent effects that would
+/// otherwise write depth that breaks SSAO or TAA.
+/// Such sprites are rendered in separate batches, using a copy of the material made with
+/// [`SizedMaterial::without_prepass`].
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct Sprite3dNoPrepass;
+
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+#[require(Transform, Visibility, InheritedVisibility, ViewVisibility, SpriteRenderTransform)]
 pub struct Sprite3d {
     pub color: Color,
     pub flip_x: bool,
     pub flip_y: bool,
+    /// Flips the sprite across its top-left to bottom-right diagonal (transposes it).
+    /// Applied before `flip_x` and `flip_y`, matching how Tiled and LDtk encode tile rotations.
+    pub flip_d: bool,
     pub custom_size: Option<Vec2>,
     pub rect: Option<Rect>,
+    /// Region of the texture to render, in normalized UV coordinates (0..1), with (0, 0) at the top left.
+    /// Alternative to `rect` for atlases described in UV space. Ignored if `rect` is set.
+    pub uv_rect: Option<Rect>,
+    /// Region of the texture, in pixels, written to the secondary UV channel (`Mesh::ATTRIBUTE_UV_1`).
+    /// Defaults to the same region as the primary UVs.
+    /// Materials can sample some textures with it, ie: [`StandardMaterial::emissive_channel`] set to
+    /// [`UvChannel::Uv1`](bevy_pbr::UvChannel::Uv1), for atlases that pack secondary maps at different coordinates.
+    pub secondary_rect: Option<Rect>,
+    /// Offset added to the UVs, in normalized UV coordinates, after the rect is applied.
+    /// Animate it to scroll the texture, ie: for conveyor belts and waterfalls. Scrolling past the
+    /// edges of the texture wraps only if its sampler uses [`ImageAddressMode::Repeat`](bevy_image::ImageAddressMode::Repeat).
+    pub uv_offset: Vec2,
+    /// Scale the UVs are multiplied by, after the rect is applied and before `uv_offset`. Defaults to [`Vec2::ONE`].
+    pub uv_scale: Vec2,
+    /// Point of the sprite placed at its transform, relative to its size, which is that of its rect if set.
+    /// See [`Sprite3dAnchorExt`] for anchors in percentages of the rect, or at a pixel of the texture.
     pub anchor: Anchor,
+    pub facing: Facing,
+    /// Normals written to the sprite's vertices, for lit materials.
+    pub normals: SpriteNormals,
+    /// Filtering to sample the sprite's texture with, overriding the texture's own sampler.
+    /// Sprites with different filters are rendered in separate batches, using a copy of the texture.
+    pub filter: Option<SpriteFilter>,
+    /// Layer of the texture array to sample, for materials using a `texture_2d_array`,
+    /// ie: [`TextureArrayMaterial`]. Ignored by other materials.
+    pub layer: u32,
+}
+
+impl Default for Sprite3d {
+    fn default() -> Self {
+        Self {
+            color: Color::default(),
+            flip_x: false,
+            flip_y: false,
+            flip_d: false,
+            custom_size: None,
+            rect: None,
+            uv_rect: None,
+            secondary_rect: None,
+            uv_offset: Vec2::ZERO,
+            uv_scale: Vec2::ONE,
+            anchor: Anchor::default(),
+            facing: Facing::default(),
+            normals: SpriteNormals::default(),
+            filter: None,
+            layer: 0,
+        }
+    }
+}
+
+/// Additional quads of a composite sprite, ie: a character assembled from a body, a weapon and a hat.
+/// Parts share the sprite's material, color, anchor and transform, and are batched along with it,
+/// so they move as one unit without needing child entities.
+#[derive(Component, Reflect, Clone, PartialEq, Default, Debug)]
+pub struct Sprite3dParts(pub Vec<Sprite3dPart>);
+
+/// A single quad of [`Sprite3dParts`].
+#[derive(Reflect, Clone, PartialEq, Default, Debug)]
+pub struct Sprite3dPart {
+    /// Region of the texture to render, in pixels. If None, renders the whole texture.
+    pub rect: Option<Rect>,
+    pub custom_size: Option<Vec2>,
+    /// Position of the part, relative to the sprite. Mirrored when the sprite is flipped.
+    /// Use the z axis to layer parts in front of or behind the sprite.
+    pub offset: Vec3,
+    /// Flips the part, on top of the sprite's own flip.
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+/// Overrides the corners of a sprite's quad, mapping its texture onto four arbitrary points,
+/// ie: for tarps, sails, projected UI panels and perspective tricks.
+/// Corners are in the sprite's local space, ordered bottom left, bottom right, top right, top left.
+/// The sprite's size and anchor are ignored, while its rect, flips and UV settings still apply.
+/// [`Sprite3dParts`] keep their regular shape.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dQuad {
+    pub corners: [Vec3; 4],
+}
+
+impl Sprite3dQuad {
+    pub fn new(corners: [Vec3; 4]) -> Self {
+        Self { corners }
+    }
+}
+
+/// Texture filtering preference of a sprite.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum SpriteFilter {
+    /// Sharp, pixelated filtering. Suited for pixel-art.
+    Nearest,
+    /// Smooth filtering.
+    Linear,
+}
+
+/// Side(s) of a sprite that are rendered, when its material uses back-face culling.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum Facing {
+    /// Visible from the front, ie: looking down the sprite's -Z axis.
+    #[default]
+    Front,
+    /// Visible from the back only.
+    Back,
+    /// Visible from both sides. Emits a second quad with reversed winding and normal.
+    Both,
+}
+
+/// Normals of a sprite's vertices.
+#[derive(Reflect, Copy, Clone, PartialEq, Default, Debug)]
+pub enum SpriteNormals {
+    /// A single normal, perpendicular to the sprite.
+    #[default]
+    Flat,
+    /// Normals curving outwards towards the edges, like on a ball, so that directionally lit billboards
+    /// shade with a soft rounded falloff instead of flipping between lit and unlit.
+    /// The value is how much the normals tilt at the corners, from 0 (flat) to 1 (45 degrees).
+    Spherical(f32),
+}
+
+/// Identifies a batch. Sprites with equal keys share a mesh.
+struct BatchKey<M: SizedMaterial> {
+    /// Weak handle to the material of the batch.
+    material: Handle<M>,
+    /// Render layers of the batch entity, so that cameras only see the sprites on their layers.
+    render_layers: RenderLayers,
+    /// Texture filtering of the batch, if it differs from the material's.
+    filter: Option<SpriteFilter>,
+    /// Draw order of the batch, relative to other batches.
+    draw_order: i32,
+    /// Key from [`Sprite3dBatchKey`].
+    user_key: u32,
+    /// Spatial chunk of the batch, if [`Sprite3dPlugin::chunk_size`] is set.
+    chunk: IVec3,
+    /// If true, the batch is excluded from prepasses, see [`Sprite3dNoPrepass`].
+    no_prepass: bool,
+    /// If true, the batch doesn't cast shadows, see [`Sprite3dLayerSettings::cast_shadows`].
+    no_shadows: bool,
+    /// If true, the batch renders [`Sprite3dSky`] sprites.
+    sky: bool,
+}
+
+impl<M: SizedMaterial> Clone for BatchKey<M> {
+    fn clone(&self) -> Self {
+        Self {
+            material: self.material.clone_weak(),
+            render_layers: self.render_layers.clone(),
+            filter: self.filter,
+            draw_order: self.draw_order,
+            user_key: self.user_key,
+            chunk: self.chunk,
+            no_prepass: self.no_prepass,
+            no_shadows: self.no_shadows,
+            sky: self.sky,
+        }
+    }
+}
+
+impl<M: SizedMaterial> PartialEq for BatchKey<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.material == other.material
+            && self.render_layers == other.render_layers
+            && self.filter == other.filter
+            && self.draw_order == other.draw_order
+            && self.user_key == other.user_key
+            && self.chunk == other.chunk
+            && self.no_prepass == other.no_prepass
+            && self.no_shadows == other.no_shadows
+            && self.sky == other.sky
+    }
+}
+
+impl<M: SizedMaterial> Eq for BatchKey<M> {}
+
+impl<M: SizedMaterial> Hash for BatchKey<M> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.material.hash(state);
+        self.render_layers.bits().hash(state);
+        self.filter.hash(state);
+        self.draw_order.hash(state);
+        self.user_key.hash(state);
+        self.chunk.hash(state);
+        self.no_prepass.hash(state);
+        self.no_shadows.hash(state);
+        self.sky.hash(state);
+    }
+}
+
+impl<M: SizedMaterial> PartialOrd for BatchKey<M> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<M: SizedMaterial> Ord for BatchKey<M> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.material.id().cmp(&other.material.id())
+            .then_with(|| self.render_layers.cmp(&other.render_layers))
+            .then_with(|| self.filter.cmp(&other.filter))
+            .then_with(|| self.draw_order.cmp(&other.draw_order))
+            .then_with(|| self.user_key.cmp(&other.user_key))
+            .then_with(|| self.chunk.to_array().cmp(&other.chunk.to_array()))
+            .then_with(|| self.no_prepass.cmp(&other.no_prepass))
+            .then_with(|| self.no_shadows.cmp(&other.no_shadows))
+            .then_with(|| self.sky.cmp(&other.sky))
+    }
+}
+
+impl<M: SizedMaterial> std::fmt::Debug for BatchKey<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchKey")
+            .field("material", &self.material)
+            .field("render_layers", &self.render_layers)
+            .field("filter", &self.filter)
+            .field("draw_order", &self.draw_order)
+            .field("user_key", &self.user_key)
+            .field("chunk", &self.chunk)
+            .field("no_prepass", &self.no_prepass)
+            .field("no_shadows", &self.no_shadows)
+            .field("sky", &self.sky)
+            .finish()
+    }
 }
 
 /// Maps materials to spawned meshes.
@@ -98,168 +1271,1428 @@ pub struct Sprite3d {
 ///     P players using the same material material (player_material.png),
 ///     E enemies using the same material (enemy_material.png), the batch will have mesh entries.
 #[derive(Resource, Reflect, Debug)]
-struct MeshBatch<M: SizedMaterial> {
-    meshes: HashMap<Handle<M>, (Entity, Handle<Mesh>)>,
+pub struct MeshBatch<M: SizedMaterial> {
+    #[reflect(ignore)]
+    meshes: HashMap<BatchKey<M>, (Entity, Handle<Mesh>)>,
+    budget: Option<BatchBudget>,
+    vertex_color_space: VertexColorSpace,
+    attributes: SpriteVertexAttributes,
+    packing: SpriteVertexPacking,
+    /// Vertex data of sprites from previous frames. Only used when batching with a budget.
+    #[reflect(ignore)]
+    cache: HashMap<Entity, (BatchKey<M>, Vec<SpriteQuad>)>,
+    /// Sprites that need their vertex data regenerated, but didn't fit in a previous frame's budget.
+    pending: HashSet<Entity>,
+    /// Sprites whose material or image wasn't loaded when they were last regenerated.
+    /// They are retried when an asset event arrives, rather than every frame.
+    waiting: HashSet<Entity>,
+    /// Last known sizes of materials, used to detect images changing dimensions, and to keep sizing sprites
+    /// once their images leave the main world.
+    material_sizes: HashMap<AssetId<M>, Vec2>,
+    /// If true, every batch gets rebuilt from scratch on the next run of [`Sprite3dSystems`].
+    invalidated_all: bool,
+    /// Materials whose batches get rebuilt from scratch on the next run of [`Sprite3dSystems`].
+    invalidated_materials: HashSet<AssetId<M>>,
+    /// Copies of materials used by batches with a [`SpriteFilter`], a [`Sprite3dDrawOrder`], [`Sprite3dNoPrepass`]
+    /// or [`Sprite3dSky`], keyed by source material and what differs from it.
+    #[reflect(ignore)]
+    material_variants: HashMap<MaterialVariantKey<M>, MaterialVariant<M>>,
+    dedup_materials: bool,
+    /// Weak handles to the material that batches each deduplicated material, keyed by material.
+    #[reflect(ignore)]
+    canonical_materials: HashMap<AssetId<M>, Handle<M>>,
+    /// Weak handles to canonical materials, keyed by [`SizedMaterial::content_hash`].
+    #[reflect(ignore)]
+    materials_by_content: HashMap<u64, Handle<M>>,
+    retain_materials: bool,
+    chunk_size: Option<f32>,
+    /// Last bounds given to batch entities.
+    batch_aabbs: HashMap<Entity, Aabb>,
+    /// Batches whose sprites changed this frame, and need to be rewritten when batching incrementally.
+    #[reflect(ignore)]
+    dirty_batches: HashSet<BatchKey<M>>,
+    /// Batches queued sprites were written to last frame.
+    #[reflect(ignore)]
+    queued_batches: HashSet<BatchKey<M>>,
+    /// Materials to warm up, with their expected sprite count, see [`MeshBatch::warm_up`].
+    #[reflect(ignore)]
+    warm_ups: Vec<(Handle<M>, usize)>,
+    /// Sprites written to each batch this frame.
+    #[reflect(ignore)]
+    sprite_counts: HashMap<BatchKey<M>, usize>,
+    /// Last statistics given to batch entities.
+    #[reflect(ignore)]
+    batch_infos: HashMap<Entity, Sprite3dBatchInfo>,
+    /// Strong handles to the materials of existing sprites, when retention is enabled.
+    #[reflect(ignore)]
+    retained_materials: HashMap<AssetId<M>, Handle<M>>,
+    memory_budget: Option<MemoryBudget>,
+    #[reflect(ignore)]
+    memory_state: MemoryBudgetState,
+    /// Hidden batch entities and their meshes, left over by materials that unloaded.
+    /// Reused by new batches, so that level changes don't reallocate them.
+    #[reflect(ignore)]
+    mesh_pool: Vec<(Entity, Handle<Mesh>)>,
+    double_buffered: bool,
+    /// Meshes of batch entities that aren't being written this frame, when double buffering.
+    #[reflect(ignore)]
+    back_meshes: HashMap<Entity, Handle<Mesh>>,
+    /// Index pattern of quads, shared by all batches.
+    #[reflect(ignore)]
+    quad_indices: QuadIndices,
+    /// Materials given to batch entities, used to render their copies for [`Sprite3dSortedView`] cameras.
+    #[reflect(ignore)]
+    batch_materials: HashMap<Entity, Handle<M>>,
+    /// Copies of transparent batches for [`Sprite3dSortedView`] cameras, keyed by batch entity and camera.
+    #[reflect(ignore)]
+    view_meshes: HashMap<(Entity, Entity), (Entity, Handle<Mesh>)>,
+    loading_policy: LoadingPolicy,
+    #[reflect(ignore)]
+    placeholders: Placeholders,
+    /// Vertex data of sprites the last frame they were rendered, with [`LoadingPolicy::LastFrame`].
+    #[reflect(ignore)]
+    last_quads: HashMap<Entity, (BatchKey<M>, Vec<SpriteQuad>)>,
+    /// Sprites that haven't been rendered yet, to send [`Sprite3dReady`] events for.
+    unready: HashSet<Entity>,
+    rect_validation: RectValidation,
+    deterministic: bool,
+    pub(crate) up_axis: UpAxis,
+    #[reflect(ignore)]
+    batch_bundle: Option<BatchBundleFactory>,
+    /// Overrides from [`Sprite3dBatchConfigs`], keyed by material.
+    #[reflect(ignore)]
+    configs: HashMap<AssetId<M>, Sprite3dBatchConfig>,
+    /// Lookups of the material of the last sprite batched this frame, reused by the sprites that follow with
+    /// the same material. Most scenes use a single material, which then gets hashed once per frame rather than
+    /// once per sprite.
+    #[reflect(ignore)]
+    last_material: Option<MaterialLookup<M>>,
+    /// Batch last written to this frame, reused by the sprites that follow with the same batch key.
+    #[reflect(ignore)]
+    last_batch: Option<(BatchKey<M>, Handle<Mesh>)>,
 }
 
-impl<M: SizedMaterial> Default for MeshBatch<M> {
-    fn default() -> Self {
+/// Source material of a [`MaterialVariant`], and what differs from it.
+type MaterialVariantKey<M> = (AssetId<M>, MaterialVariantParams);
+
+/// What differs between a [`MaterialVariant`] and its source material.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+struct MaterialVariantParams {
+    filter: Option<SpriteFilter>,
+    draw_order: i32,
+    no_prepass: bool,
+    sky: bool,
+    /// If true, the alpha mode is overridden by the material's [`Sprite3dBatchConfig`].
+    alpha_mode: bool,
+}
+
+/// What a sprite's material resolves to when batching.
+#[derive(Debug)]
+struct MaterialLookup<M: SizedMaterial> {
+    id: AssetId<M>,
+    /// Material the sprite is batched with, after deduplication.
+    material: Handle<M>,
+    config: Option<Sprite3dBatchConfig>,
+    size: Option<Vec2>,
+}
+
+#[derive(Debug)]
+struct MaterialVariant<M: SizedMaterial> {
+    material: Handle<M>,
+    /// Texture that was copied with a different filter, if any.
+    source_texture: Option<AssetId<Image>>,
+}
+
+impl<M: SizedMaterial> MeshBatch<M> {
+
+    fn new(plugin: &Sprite3dPlugin<M>) -> Self {
         Self {
             meshes: Default::default(),
+            budget: plugin.budget,
+            vertex_color_space: plugin.vertex_color_space,
+            attributes: M::required_vertex_attributes(),
+            packing: match plugin.packed_vertices {
+                true => M::supported_vertex_packing(),
+                false => SpriteVertexPacking::NONE,
+            },
+            cache: Default::default(),
+            pending: Default::default(),
+            waiting: Default::default(),
+            material_sizes: Default::default(),
+            invalidated_all: false,
+            invalidated_materials: Default::default(),
+            material_variants: Default::default(),
+            dedup_materials: plugin.dedup_materials,
+            canonical_materials: Default::default(),
+            materials_by_content: Default::default(),
+            retain_materials: plugin.retain_materials,
+            chunk_size: plugin.chunk_size,
+            batch_aabbs: Default::default(),
+            dirty_batches: Default::default(),
+            queued_batches: Default::default(),
+            warm_ups: Default::default(),
+            sprite_counts: Default::default(),
+            batch_infos: Default::default(),
+            retained_materials: Default::default(),
+            memory_budget: plugin.memory_budget,
+            memory_state: Default::default(),
+            mesh_pool: Default::default(),
+            double_buffered: plugin.double_buffered,
+            back_meshes: Default::default(),
+            quad_indices: Default::default(),
+            batch_materials: Default::default(),
+            view_meshes: Default::default(),
+            loading_policy: plugin.loading_policy,
+            placeholders: Default::default(),
+            last_quads: Default::default(),
+            unready: Default::default(),
+            rect_validation: plugin.rect_validation,
+            deterministic: plugin.deterministic,
+            up_axis: plugin.up_axis,
+            batch_bundle: plugin.batch_bundle.clone(),
+            configs: Default::default(),
+            last_material: None,
+            last_batch: None,
         }
     }
-}
 
-impl<M: SizedMaterial> MeshBatch<M> {
+    /// Rebuilds every batch from scratch the next time sprites are batched.
+    /// Batch entities and meshes are respawned, and cached sprite data is discarded.
+    /// Use this after modifying batch meshes or entities directly.
+    pub fn invalidate_all(&mut self) {
+        self.invalidated_all = true;
+    }
 
-    // Adds sprite vertex data to a mesh that is compatible with the sprite's material.
-    fn submit(
+    /// Rebuilds the batches of a single material from scratch the next time sprites are batched.
+    pub fn invalidate_material(&mut self, id: impl Into<AssetId<M>>) {
+        self.invalidated_materials.insert(id.into());
+    }
+
+    /// Replaces the overrides of batches, and rebuilds them.
+    pub(crate) fn set_configs(&mut self, configs: HashMap<AssetId<M>, Sprite3dBatchConfig>) {
+        self.configs = configs;
+        self.last_material = None;
+        self.material_variants.clear();
+        self.invalidated_all = true;
+    }
+
+    // Deduplicates the material of a sprite's batch key, and applies the overrides of its batch config.
+    fn resolve_batch_key(&mut self, mut batch_key: BatchKey<M>, materials: &Assets<M>, images: &Assets<Image>) -> BatchKey<M> {
+        let lookup = self.material_lookup(&batch_key.material, materials, images);
+        batch_key.material = lookup.material.clone_weak();
+        let Some(config) = &lookup.config else { return batch_key };
+        if let Some(draw_order) = config.draw_order {
+            batch_key.draw_order = draw_order;
+        }
+        if let Some(render_layers) = &config.render_layers {
+            if batch_key.render_layers != RenderLayers::layer(SHADOW_ONLY_LAYER) {
+                batch_key.render_layers = render_layers.clone();
+            }
+        }
+        batch_key
+    }
+
+    /// Entities of the batches currently in use, placeholder batches and copies for sorted views included.
+    pub(crate) fn batch_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.meshes
+            .values()
+            .chain(self.view_meshes.values())
+            .map(|(entity, _)| *entity)
+            .chain(self.placeholders.entities())
+    }
+
+    /// Preallocates the batch of a material for a number of sprites, ie: while loading a level, so that the first frame
+    /// with thousands of sprites doesn't hitch on growing vertex data and creating GPU buffers.
+    /// The batch is spawned the next time sprites are batched after the material is loaded, with room for `count`
+    /// sprites on the default render layer, and filled with empty quads for a frame so that its GPU buffers are
+    /// allocated at full size.
+    pub fn warm_up(&mut self, material: Handle<M>, count: usize) {
+        self.warm_ups.push((material, count));
+    }
+
+    /// Hash of the vertex and index data of every batch, in batch order, ie: to check that runs of a replay render
+    /// the same sprites. Uses 64-bit FNV-1a over the raw bytes, which doesn't depend on the Rust version, so hashes
+    /// can be recorded and compared across runs when batching with [`Sprite3dPlugin::deterministic`].
+    pub fn vertex_hash(&self, meshes: &Assets<Mesh>) -> u64 {
+        let mut batches: Vec<_> = self.meshes.iter().collect();
+        batches.sort_unstable_by_key(|(batch_key, _)| *batch_key);
+        let mut hash = FNV_OFFSET_BASIS;
+        for (_, (_, mesh_handle)) in batches {
+            let Some(mesh) = meshes.get(mesh_handle) else { continue };
+            hash = fnv1a(hash, &mesh.create_packed_vertex_buffer_data());
+            hash = fnv1a(hash, mesh.get_index_buffer_bytes().unwrap_or_default());
+        }
+        hash
+    }
+
+    /// Despawns the batch entities kept for reuse after their materials unloaded, freeing their meshes.
+    pub fn clear_mesh_pool(&mut self, commands: &mut Commands) {
+        for (entity, _) in self.mesh_pool.drain(..) {
+            if let Some(mut entity_commands) = commands.get_entity(entity) {
+                entity_commands.despawn();
+            }
+        }
+    }
+
+    fn remove_invalidated_meshes(&mut self, commands: &mut Commands) {
+        if !self.invalidated_all && self.invalidated_materials.is_empty() { return };
+        let invalidated_all = self.invalidated_all;
+        let invalidated_materials = std::mem::take(&mut self.invalidated_materials);
+        let is_invalidated = |batch_key: &BatchKey<M>| {
+            invalidated_all || invalidated_materials.contains(&batch_key.material.id())
+        };
+        self.meshes.retain(|batch_key, (mesh_entity, _)| {
+            if !is_invalidated(batch_key) { return true };
+            self.back_meshes.remove(mesh_entity);
+            self.batch_materials.remove(mesh_entity);
+            if let Some(mut mesh_entity) = commands.get_entity(*mesh_entity) {
+                mesh_entity.despawn();
+            }
+            false
+        });
+        self.cache.retain(|_, (batch_key, _)| !is_invalidated(batch_key));
+        self.material_sizes.retain(|id, _| !invalidated_all && !invalidated_materials.contains(id));
+        self.invalidated_all = false;
+    }
+
+    // Marks cached sprites as pending when the assets they were generated from change.
+    fn handle_asset_events(
         &mut self,
-        sprite: &Sprite3d,
-        sprite_mat_handle: &Handle<M>,
-        sprite_transf: &GlobalTransform,
-        meshes: &mut Assets<Mesh>,
+        changed_images: &HashSet<AssetId<Image>>,
+        changed_materials: HashSet<AssetId<M>>,
         materials: &Assets<M>,
         images: &Assets<Image>,
+    ) {
+        let images_changed = !changed_images.is_empty();
+        if !images_changed && changed_materials.is_empty() { return };
+
+        // Sprites waiting on assets get another chance
+        self.pending.extend(self.waiting.drain());
+
+        // Detects materials whose size changed, ie: a texture was hot-reloaded with new dimensions
+        let mut stale_materials = changed_materials;
+        if images_changed {
+            for mat_handle in self.meshes.keys().map(|batch_key| &batch_key.material) {
+                let size = materials.get(mat_handle).and_then(|mat| mat.size(images));
+                let Some(size) = size else { continue };
+                if self.material_sizes.insert(mat_handle.id(), size) != Some(size) {
+                    stale_materials.insert(mat_handle.id());
+                }
+            }
+        }
+        let stale_sprites = self.cache
+            .iter()
+            .filter(|(_, (batch_key, _))| stale_materials.contains(&batch_key.material.id()))
+            .map(|(entity, _)| *entity);
+        self.pending.extend(stale_sprites);
+    }
+
+    // Resolves a sprite's material, or reuses the lookups of the last one if it has the same material.
+    fn material_lookup(&mut self, material: &Handle<M>, materials: &Assets<M>, images: &Assets<Image>) -> &MaterialLookup<M> {
+        let id = material.id();
+        if self.last_material.as_ref().is_none_or(|last| last.id != id) {
+            let size = self.material_size(id, materials, images);
+            let material = self.canonical_material(material, materials);
+            let config = self.configs.get(&material.id()).cloned();
+            self.last_material = Some(MaterialLookup { id, material, config, size });
+        }
+        self.last_material.as_ref().unwrap()
+    }
+
+    /// Size of a material, remembered so that it stays known once the material's image leaves the main world,
+    /// ie: images loaded with [`RenderAssetUsages::RENDER_WORLD`] only, which move to the render world once uploaded.
+    fn material_size(&mut self, id: AssetId<M>, materials: &Assets<M>, images: &Assets<Image>) -> Option<Vec2> {
+        let sprite_mat = materials.get(id)?;
+        match sprite_mat.size(images) {
+            Some(size) => {
+                self.material_sizes.insert(id, size);
+                Some(size)
+            },
+            None => self.last_material_size(id),
+        }
+    }
+
+    /// Size of a material the last time its sprites were batched, which stays known once the material's image leaves
+    /// the main world after being uploaded, ie: to size colliders or UI after [`SizedMaterial::size`] returns None.
+    pub fn last_material_size(&self, id: AssetId<M>) -> Option<Vec2> {
+        self.material_sizes.get(&id).copied()
+    }
+
+    // Gets the mesh (sprite batch) associated with a material.
+    // Creates and spawns it on-the-fly if there's no entry.
+    fn get_or_spawn_mesh<'a>(
+        &mut self,
+        batch_key: &BatchKey<M>,
+        meshes: &'a mut Assets<Mesh>,
+        materials: &mut Assets<M>,
+        images: &mut Assets<Image>,
+        asset_server: Option<&AssetServer>,
+        commands: &mut Commands,
+    ) -> &'a mut Mesh {
+        if let Some((last_key, mesh_handle)) = &self.last_batch {
+            if last_key == batch_key {
+                return meshes.get_mut(mesh_handle).expect("Sprite batch entity is missing a Handle<Mesh> component");
+            }
+        }
+        if !self.meshes.contains_key(batch_key) {
+            let name = batch_name(&batch_key.material, materials, asset_server);
+            let sprite_mat_handle = self.material_variant(batch_key, materials, images)
+                .or_else(|| materials.get_strong_handle(batch_key.material.id()))
+                .unwrap_or_else(|| batch_key.material.clone());
+            let batch = (
+                MeshMaterial3d(sprite_mat_handle.clone()),
+                batch_key.render_layers.clone(),
+                Aabb { center: Vec3A::ZERO, half_extents: Vec3A::INFINITY },
+                Name::new(name),
+                Sprite3dBatch::<M> {
+                    material: batch_key.material.id(),
+                    render_layers: batch_key.render_layers.clone(),
+                    draw_order: batch_key.draw_order,
+                    key: batch_key.user_key,
+                    chunk: batch_key.chunk,
+                },
+            );
+            let (entity, handle) = match self.pop_pooled_mesh(meshes, commands) {
+                Some((entity, handle)) => {
+                    let mut entity_commands = commands.entity(entity);
+                    entity_commands.insert((batch, Visibility::Inherited));
+                    if let Some(batch_bundle) = &self.batch_bundle {
+                        (batch_bundle.0)(&mut entity_commands);
+                    }
+                    (entity, handle)
+                },
+                None => {
+                    let handle = meshes.add(create_packed_sprite_mesh(self.attributes, self.packing));
+                    let mut entity_commands = commands.spawn((Mesh3d(handle.clone()), batch));
+                    if let Some(batch_bundle) = &self.batch_bundle {
+                        (batch_bundle.0)(&mut entity_commands);
+                    }
+                    (entity_commands.id(), handle)
+                },
+            };
+            let config_shadows = self.configs.get(&batch_key.material.id()).is_none_or(|config| config.cast_shadows);
+            if batch_key.no_shadows || !config_shadows {
+                commands.entity(entity).insert(NotShadowCaster);
+            }
+            self.batch_materials.insert(entity, sprite_mat_handle);
+            self.meshes.insert(batch_key.clone(), (entity, handle));
+        }
+        let (_, mesh_handle) = &self.meshes[batch_key];
+        self.last_batch = Some((batch_key.clone(), mesh_handle.clone_weak()));
+        meshes
+            .get_mut(mesh_handle)
+            .expect("Sprite batch entity is missing a Handle<Mesh> component")
+    }
+
+    // Renders sprites whose material isn't loaded according to the loading policy.
+    fn write_loading_sprites(
+        &mut self,
+        loading_sprites: &[(&BatchKey<M>, &SpriteQueryItem<'_, M>)],
+        (meshes, materials, images): (&mut Assets<Mesh>, &mut Assets<M>, &mut Assets<Image>),
+        asset_server: Option<&AssetServer>,
         commands: &mut Commands,
     ) {
+        match self.loading_policy {
+            LoadingPolicy::Skip => {},
+            LoadingPolicy::Placeholder => {
+                for (batch_key, item) in loading_sprites {
+                    let sprite_transf = item.render.transform;
+                    self.placeholders.push(&item.sprite, &sprite_transf, &batch_key.render_layers, self.vertex_color_space);
+                }
+            },
+            LoadingPolicy::LastFrame => {
+                let last_quads = std::mem::take(&mut self.last_quads);
+                for (_, item) in loading_sprites {
+                    let Some((batch_key, quads)) = last_quads.get(&item.entity) else { continue };
+                    if !materials.contains(&batch_key.material) { continue };
+                    self.count_sprites(batch_key, 1);
+                    let mesh = self.get_or_spawn_mesh(batch_key, meshes, materials, images, asset_server, commands);
+                    let mut writer = SpriteVertexWriter::new(mesh);
+                    for quad in quads {
+                        writer.write(quad);
+                    }
+                }
+                self.last_quads = last_quads;
+            },
+        }
+    }
 
-        // Gets sizes of sprite
-        let Some(sprite_mat) = materials.get(sprite_mat_handle) else { return };
-        let Some(sprite_mat_size) = sprite_mat.size(images) else { return };
-        let sprite_size = match (sprite.custom_size, sprite.rect) {
-            (Some(custom_size), _)  => custom_size,
-            (None, Some(rect))       => rect.size(),
-            _ => sprite_mat_size,
-        };
+    // Forgets the sprites that were despawned before being rendered, and the last frame of sprites that are no
+    // longer visible.
+    fn forget_removed_sprites(&mut self, sprites: &Query<SpriteQuery<M>>) {
+        if !self.unready.is_empty() {
+            self.unready.retain(|&entity| sprites.contains(entity));
+        }
+        if !self.last_quads.is_empty() {
+            self.last_quads.retain(|&entity, _| sprites.get(entity).is_ok_and(|item| item.visibility.get()));
+        }
+    }
 
-        // Gets existing mesh (sprite batch) associated with sprite's material.
-        // Creates and spawns it on-the-fly if there's no entry.
-        let (_, mesh_handle) = self.meshes
-            .entry(sprite_mat_handle.clone_weak())
-            .or_insert_with(|| {
-                let handle = meshes.add(create_mesh());
-                let entity = commands.spawn((
-                    Mesh3d(handle.clone()),
-                    MeshMaterial3d(sprite_mat_handle.clone()),
-                    Aabb { center: Vec3A::ZERO, half_extents: Vec3A::INFINITY },
-                )).id();
-                (entity, handle)
+    // Writes the sprites of a queue to their batches, and empties it.
+    fn submit_queued_sprites(
+        &mut self,
+        queue: &mut Sprite3dQueue<M>,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<M>,
+        images: &mut Assets<Image>,
+        asset_server: Option<&AssetServer>,
+        commands: &mut Commands,
+    ) {
+        for queued in queue.sprites.drain(..) {
+            let lookup = self.material_lookup(&queued.material, materials, images);
+            let (Some(sprite_mat_size), material) = (lookup.size, lookup.material.clone_weak()) else { continue };
+            let Some(sprite) = self.rect_validation.validate(&queued.sprite, sprite_mat_size) else { continue };
+            let transform = queued.transform.mul_transform(Transform::from_rotation(self.up_axis.rotation()));
+            let quad = SpriteQuad::new(&sprite, &transform, sprite_mat_size, self.vertex_color_space);
+            let batch_key = self.queued_batch_key(&queued, material);
+            if self.is_incremental() {
+                self.queued_batches.insert(batch_key.clone());
+            }
+            self.count_sprites(&batch_key, 1);
+            let mesh = self.get_or_spawn_mesh(&batch_key, meshes, materials, images, asset_server, commands);
+            write_sprite_quad_vertices(mesh, &quad);
+        }
+    }
+
+    // Spawns and fills the batches of materials being warmed up, once loaded.
+    // Incrementally, their batches are rewritten without the empty quads next frame, like those of queued sprites.
+    fn write_warm_ups(
+        &mut self,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<M>,
+        images: &mut Assets<Image>,
+        asset_server: Option<&AssetServer>,
+        commands: &mut Commands,
+    ) {
+        for (handle, count) in std::mem::take(&mut self.warm_ups) {
+            let lookup = self.material_lookup(&handle, materials, images);
+            let (Some(sprite_mat_size), material) = (lookup.size, lookup.material.clone_weak()) else {
+                self.warm_ups.push((handle, count));
+                continue;
+            };
+            let queued = QueuedSprite { sprite: Sprite3d::default(), transform: GlobalTransform::IDENTITY, material: handle };
+            let batch_key = self.queued_batch_key(&queued, material);
+            if self.is_incremental() {
+                self.queued_batches.insert(batch_key.clone());
+                self.dirty_batches.insert(batch_key.clone());
+            }
+            let mut quad = SpriteQuad::new(&queued.sprite, &queued.transform, sprite_mat_size, self.vertex_color_space);
+            quad.positions = [[0.0; 3]; 4];
+            let mesh = self.get_or_spawn_mesh(&batch_key, meshes, materials, images, asset_server, commands);
+            reserve_sprite_quads(mesh, count);
+            for _ in 0..count {
+                write_sprite_quad_vertices(mesh, &quad);
+            }
+        }
+    }
+
+    fn queued_batch_key(&self, queued: &QueuedSprite<M>, material: Handle<M>) -> BatchKey<M> {
+        BatchKey {
+            material,
+            render_layers: RenderLayers::default(),
+            filter: queued.sprite.filter,
+            draw_order: 0,
+            user_key: 0,
+            chunk: batch_chunk(queued.transform.translation(), self.chunk_size),
+            no_prepass: false,
+            no_shadows: false,
+            sky: false,
+        }
+    }
+
+    // Marks the batches queued sprites get written to as dirty, along with those they were written to last frame,
+    // so that they are rewritten without them.
+    fn mark_queued_batches(&mut self, queue: &Sprite3dQueue<M>, materials: &Assets<M>, images: &Assets<Image>) {
+        self.dirty_batches.extend(self.queued_batches.drain());
+        for queued in &queue.sprites {
+            let material = self.material_lookup(&queued.material, materials, images).material.clone_weak();
+            let batch_key = self.queued_batch_key(queued, material);
+            self.dirty_batches.insert(batch_key);
+        }
+    }
+
+    // Gets a copy of a batch's material, with its texture sampled with a different filter, its depth
+    // biased by the batch's draw order, and excluded from prepasses. None if the batch uses the material as-is.
+    // The copy (and its texture) is created on first use, and reused afterwards.
+    fn material_variant(
+        &mut self,
+        batch_key: &BatchKey<M>,
+        materials: &mut Assets<M>,
+        images: &mut Assets<Image>,
+    ) -> Option<Handle<M>> {
+        let params = MaterialVariantParams {
+            filter: batch_key.filter,
+            draw_order: batch_key.draw_order,
+            no_prepass: batch_key.no_prepass,
+            sky: batch_key.sky,
+            alpha_mode: self.configs.get(&batch_key.material.id()).is_some_and(|config| config.alpha_mode.is_some()),
+        };
+        if params == MaterialVariantParams::default() { return None };
+        let key = (batch_key.material.id(), params);
+        if let Some(variant) = self.material_variants.get(&key) {
+            return Some(variant.material.clone());
+        }
+        let sprite_mat = materials.get(&batch_key.material)?;
+        let mut variant_mat = None;
+        let mut source_texture = None;
+        if let Some(filter) = batch_key.filter {
+            let filtered = sprite_mat.texture().and_then(|texture| {
+                let mut filtered_image = images.get(texture)?.clone();
+                filtered_image.sampler = match filter {
+                    SpriteFilter::Nearest => ImageSampler::nearest(),
+                    SpriteFilter::Linear => ImageSampler::linear(),
+                };
+                let filtered_mat = sprite_mat.with_texture(images.add(filtered_image))?;
+                Some((filtered_mat, texture.id()))
             });
-        let mesh = meshes
-            .get_mut(mesh_handle)
-            .expect("Sprite batch entity is missing a Handle<Mesh> component");
+            if let Some((filtered_mat, texture)) = filtered {
+                variant_mat = Some(filtered_mat);
+                source_texture = Some(texture);
+            }
+        }
+        let depth_bias = batch_key.draw_order as f32 + if batch_key.sky { SKY_DEPTH_BIAS } else { 0.0 };
+        if depth_bias != 0.0 {
+            let base_mat = variant_mat.as_ref().unwrap_or(sprite_mat);
+            if let Some(biased_mat) = base_mat.with_depth_bias(depth_bias) {
+                variant_mat = Some(biased_mat);
+            }
+        }
+        if batch_key.sky {
+            let base_mat = variant_mat.as_ref().unwrap_or(sprite_mat);
+            if let Some(fogless_mat) = base_mat.without_fog() {
+                variant_mat = Some(fogless_mat);
+            }
+        }
+        if batch_key.no_prepass {
+            let base_mat = variant_mat.as_ref().unwrap_or(sprite_mat);
+            if let Some(no_prepass_mat) = base_mat.without_prepass() {
+                variant_mat = Some(no_prepass_mat);
+            }
+        }
+        if let Some(alpha_mode) = self.configs.get(&batch_key.material.id()).and_then(|config| config.alpha_mode) {
+            let base_mat = variant_mat.as_ref().unwrap_or(sprite_mat);
+            if let Some(blended_mat) = base_mat.with_alpha_mode(alpha_mode) {
+                variant_mat = Some(blended_mat);
+            }
+        }
+        let variant_mat_handle = materials.add(variant_mat?);
+        self.material_variants.insert(key, MaterialVariant {
+            material: variant_mat_handle.clone(),
+            source_texture,
+        });
+        Some(variant_mat_handle)
+    }
+
+    // Discards copies of materials whose source material or texture changed, and rebuilds their batches.
+    fn remove_stale_material_variants(
+        &mut self,
+        changed_images: &HashSet<AssetId<Image>>,
+        changed_materials: &HashSet<AssetId<M>>,
+    ) {
+        if self.material_variants.is_empty() { return };
+        let invalidated_materials = &mut self.invalidated_materials;
+        self.material_variants.retain(|(mat_id, _), variant| {
+            let is_stale = changed_materials.contains(mat_id)
+                || variant.source_texture.is_some_and(|texture| changed_images.contains(&texture));
+            if is_stale {
+                invalidated_materials.insert(*mat_id);
+            }
+            !is_stale
+        });
+    }
+
+    // Weak handle to the first batched material of equal content.
+    // The material itself if deduplication is disabled, or the material can't be hashed.
+    fn canonical_material(&mut self, material: &Handle<M>, materials: &Assets<M>) -> Handle<M> {
+        if !self.dedup_materials { return material.clone_weak() };
+        let id = material.id();
+        if let Some(canonical) = self.canonical_materials.get(&id) {
+            return canonical.clone_weak();
+        }
+        let Some(content_hash) = materials.get(id).and_then(SizedMaterial::content_hash) else { return material.clone_weak() };
+        let canonical = self.materials_by_content
+            .entry(content_hash)
+            .or_insert_with(|| material.clone_weak())
+            .clone_weak();
+        self.canonical_materials.insert(id, canonical.clone_weak());
+        canonical
+    }
+
+    // Forgets the canonical materials of materials that changed or got removed, and rebuilds the batches
+    // they were deduplicated into.
+    fn remove_stale_canonical_materials(&mut self, changed_materials: &HashSet<AssetId<M>>, materials: &Assets<M>) {
+        if self.canonical_materials.is_empty() { return };
+        let is_stale = |id: AssetId<M>| changed_materials.contains(&id) || !materials.contains(id);
+        let invalidated_materials = &mut self.invalidated_materials;
+        self.canonical_materials.retain(|&id, canonical| {
+            let canonical_id = canonical.id();
+            if !is_stale(id) && !is_stale(canonical_id) { return true };
+            invalidated_materials.insert(canonical_id);
+            false
+        });
+        self.materials_by_content.retain(|_, canonical| !is_stale(canonical.id()));
+    }
+
+    // Holds strong handles to the materials sprites use, and releases those of materials no sprite uses anymore.
+    fn retain_sprite_materials(
+        &mut self,
+        sprite_materials: impl Iterator<Item = AssetId<M>>,
+        materials: &mut Assets<M>,
+    ) {
+        let sprite_materials: HashSet<AssetId<M>> = sprite_materials.collect();
+        self.retained_materials.retain(|id, _| sprite_materials.contains(id));
+        for id in sprite_materials {
+            if self.retained_materials.contains_key(&id) { continue };
+            if let Some(handle) = materials.get_strong_handle(id) {
+                self.retained_materials.insert(id, handle);
+            }
+        }
+    }
+
+    fn remove_unloaded_meshes(&mut self, materials: &Assets<M>, commands: &mut Commands,) {
+        self.material_variants.retain(|(mat_id, _), _| materials.contains(*mat_id));
+        self.material_sizes.retain(|mat_id, _| materials.contains(*mat_id));
+        self.meshes.retain(|batch_key, (mesh_entity, mesh_handle)| {
+            if materials.contains(&batch_key.material) { true }
+            else {
+                self.material_sizes.remove(&batch_key.material.id());
+                self.back_meshes.remove(mesh_entity);
+                self.batch_materials.remove(mesh_entity);
+                self.batch_infos.remove(mesh_entity);
+                if let Some(mut entity_commands) = commands.get_entity(*mesh_entity) {
+                    entity_commands
+                        .insert(Visibility::Hidden)
+                        .remove::<(MeshMaterial3d<M>, Sprite3dBatch<M>, Sprite3dBatchInfo, NotShadowCaster)>();
+                    self.mesh_pool.push((*mesh_entity, mesh_handle.clone()));
+                }
+                false
+            }
+        });
+    }
+
+    // Takes a batch entity and its mesh out of the pool, emptied, skipping entities despawned since.
+    fn pop_pooled_mesh(&mut self, mesh_assets: &mut Assets<Mesh>, commands: &mut Commands) -> Option<(Entity, Handle<Mesh>)> {
+        while let Some((entity, handle)) = self.mesh_pool.pop() {
+            if commands.get_entity(entity).is_none() { continue };
+            let Some(mesh) = mesh_assets.get_mut(&handle) else { continue };
+            self.clear_mesh(mesh);
+            return Some((entity, handle));
+        }
+        None
+    }
+
+    // Indices are kept, as they only depend on the quad count, and get fitted to it in finish_meshes.
+    fn clear_mesh(&self, mesh: &mut Mesh) {
+        clear_sprite_vertices(mesh);
+        if self.attributes.colors && !mesh.contains_attribute(Mesh::ATTRIBUTE_COLOR) {
+            let colors = match self.packing.colors {
+                false => VertexAttributeValues::Float32x4(vec![]),
+                true => VertexAttributeValues::Unorm8x4(vec![]),
+            };
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
+    }
+
+    fn clear_meshes(&mut self, mesh_assets: &mut Assets<Mesh>) {
+        self.last_material = None;
+        self.last_batch = None;
+        if self.is_incremental() { return };
+        self.sprite_counts.clear();
+        if self.double_buffered {
+            self.swap_meshes(mesh_assets);
+        }
+        for (_mesh_entity, mesh_handle) in self.meshes.values() {
+            let mesh = mesh_assets.get_mut(mesh_handle).unwrap();
+            self.clear_mesh(mesh);
+        }
+    }
+
+    // Swaps the mesh of each batch with its back mesh, so that the mesh last given to the renderer is left untouched.
+    fn swap_meshes(&mut self, mesh_assets: &mut Assets<Mesh>) {
+        for (mesh_entity, mesh_handle) in self.meshes.values_mut() {
+            let back_handle = self.back_meshes
+                .entry(*mesh_entity)
+                .or_insert_with(|| mesh_assets.add(create_packed_sprite_mesh(self.attributes, self.packing)));
+            std::mem::swap(mesh_handle, back_handle);
+        }
+    }
+
+    /// If true, batches are only rewritten when their sprites change. Cached sprite data is otherwise rewritten
+    /// every frame, so that batches can alternate meshes, or sprites can be dropped to fit the memory budget.
+    fn is_incremental(&self) -> bool {
+        self.budget.is_some() && !self.double_buffered && self.memory_budget.is_none()
+    }
+
+    // Forgets the cached data of a sprite, and marks the batch it was in as dirty.
+    fn forget_cached_sprite(&mut self, entity: Entity) {
+        if let Some((batch_key, _)) = self.cache.remove(&entity) {
+            self.dirty_batches.insert(batch_key);
+        }
+    }
+
+    // Clears dirty batches before they get rewritten, when batching incrementally.
+    fn clear_dirty_meshes(&mut self, mesh_assets: &mut Assets<Mesh>) {
+        for batch_key in &self.dirty_batches {
+            self.sprite_counts.remove(batch_key);
+            let Some((_, mesh_handle)) = self.meshes.get(batch_key) else { continue };
+            let Some(mesh) = mesh_assets.get_mut(mesh_handle) else { continue };
+            self.clear_mesh(mesh);
+        }
+    }
+
+    // Counts sprites written to a batch this frame.
+    fn count_sprites(&mut self, batch_key: &BatchKey<M>, count: usize) {
+        match self.sprite_counts.get_mut(batch_key) {
+            Some(sprites) => *sprites += count,
+            None => { self.sprite_counts.insert(batch_key.clone(), count); },
+        }
+    }
+
+    // Fits the indices of batches to their quads, strips vertex colors from batches whose sprites are all untinted
+    // when the material doesn't need them, fits the bounds of batch entities to their sprites, and updates their
+    // statistics.
+    fn finish_meshes(
+        &mut self,
+        mesh_assets: &mut Assets<Mesh>,
+        materials: &Assets<M>,
+        sorted_views: &[(Entity, Vec3A)],
+        commands: &mut Commands,
+    ) {
+        let mut previous_aabbs = std::mem::take(&mut self.batch_aabbs);
+        let incremental = self.is_incremental();
+        for (batch_key, (mesh_entity, mesh_handle)) in &self.meshes {
+            if incremental && !self.dirty_batches.contains(batch_key) {
+                if let Some(aabb) = previous_aabbs.remove(mesh_entity) {
+                    self.batch_aabbs.insert(*mesh_entity, aabb);
+                }
+                continue;
+            }
+            let Some(mesh) = mesh_assets.get_mut(mesh_handle) else { continue };
+            fit_sprite_indices(mesh, &mut self.quad_indices);
+            if M::vertex_colors_optional() && is_untinted(mesh) {
+                mesh.remove_attribute(Mesh::ATTRIBUTE_COLOR);
+            }
+            let quads = mesh.count_vertices() / 4;
+            let info = Sprite3dBatchInfo {
+                material: batch_key.material.id().untyped(),
+                sprites: self.sprite_counts.get(batch_key).copied().unwrap_or_default(),
+                quads,
+                bytes: quads * (4 * mesh.get_vertex_size() as usize + QUAD_INDEX_BYTES),
+            };
+            if self.batch_infos.insert(*mesh_entity, info) != Some(info) {
+                commands.entity(*mesh_entity).insert(info);
+            }
+            let aabb = batch_aabb(mesh);
+            if previous_aabbs.remove(mesh_entity) != Some(aabb) {
+                commands.entity(*mesh_entity).insert(aabb);
+            }
+            if self.double_buffered {
+                commands.entity(*mesh_entity).insert(Mesh3d(mesh_handle.clone()));
+            }
+            self.batch_aabbs.insert(*mesh_entity, aabb);
+        }
+        self.dirty_batches.clear();
+        self.write_view_meshes(mesh_assets, materials, sorted_views, &mut previous_aabbs, commands);
+    }
+
+    // Copies transparent batches for each Sprite3dSortedView camera, with their quads sorted back-to-front from it,
+    // and despawns copies that are no longer needed.
+    fn write_view_meshes(
+        &mut self,
+        mesh_assets: &mut Assets<Mesh>,
+        materials: &Assets<M>,
+        sorted_views: &[(Entity, Vec3A)],
+        previous_aabbs: &mut HashMap<Entity, Aabb>,
+        commands: &mut Commands,
+    ) {
+        let mut view_meshes = HashMap::new();
+        for &(view, view_position) in sorted_views {
+            for (batch_key, (mesh_entity, mesh_handle)) in &self.meshes {
+                let is_transparent = materials.get(&batch_key.material).is_some_and(SizedMaterial::is_transparent);
+                let Some(material) = self.batch_materials.get(mesh_entity).filter(|_| is_transparent) else { continue };
+                let Some(source) = mesh_assets.get(mesh_handle) else { continue };
+                let order = back_to_front_quads(source, view_position);
+                if order.is_empty() { continue };
+                let aabb = self.batch_aabbs.get(mesh_entity).copied().unwrap_or_default();
+                let (copy_entity, copy_handle) = match self.view_meshes.remove(&(*mesh_entity, view)) {
+                    Some(copy) => copy,
+                    None => {
+                        let handle = mesh_assets.add(create_packed_sprite_mesh(self.attributes, self.packing));
+                        let mut entity_commands = commands.spawn((
+                            Mesh3d(handle.clone()),
+                            MeshMaterial3d(material.clone()),
+                            batch_key.render_layers.clone(),
+                            Aabb { center: Vec3A::ZERO, half_extents: Vec3A::INFINITY },
+                            Name::new(format!("Sprite3d View Batch ({mesh_entity} for {view})")),
+                            Sprite3dBatch::<M> {
+                                material: batch_key.material.id(),
+                                render_layers: batch_key.render_layers.clone(),
+                                draw_order: batch_key.draw_order,
+                                key: batch_key.user_key,
+                                chunk: batch_key.chunk,
+                            },
+                            Sprite3dViewBatch { view, source: *mesh_entity },
+                        ));
+                        if let Some(batch_bundle) = &self.batch_bundle {
+                            (batch_bundle.0)(&mut entity_commands);
+                        }
+                        (entity_commands.id(), handle)
+                    },
+                };
+                // Takes the copy out of its asset while writing it, as both meshes can't be borrowed at once
+                let Some(copy) = mesh_assets.get_mut(&copy_handle) else { continue };
+                let mut copy = std::mem::replace(copy, create_sprite_mesh(SpriteVertexAttributes::NONE));
+                write_sorted_quads(mesh_assets.get(mesh_handle).unwrap(), &mut copy, &order);
+                fit_sprite_indices(&mut copy, &mut self.quad_indices);
+                *mesh_assets.get_mut(&copy_handle).unwrap() = copy;
+                if previous_aabbs.remove(&copy_entity) != Some(aabb) {
+                    commands.entity(copy_entity).insert(aabb);
+                }
+                self.batch_aabbs.insert(copy_entity, aabb);
+                view_meshes.insert((*mesh_entity, view), (copy_entity, copy_handle));
+            }
+        }
+        for (copy_entity, _) in std::mem::replace(&mut self.view_meshes, view_meshes).into_values() {
+            if let Some(mut entity_commands) = commands.get_entity(copy_entity) {
+                entity_commands.despawn();
+            }
+        }
+    }
+}
+
+/// Quads of a sprite mesh, ordered from farthest to nearest to a position.
+fn back_to_front_quads(mesh: &Mesh, position: Vec3A) -> Vec<usize> {
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        return Vec::new();
+    };
+    let mut distances: Vec<(usize, f32)> = positions
+        .chunks_exact(4)
+        .map(|corners| corners.iter().map(|&corner| Vec3A::from(corner)).sum::<Vec3A>() / 4.0)
+        .map(|center| center.distance_squared(position))
+        .enumerate()
+        .collect();
+    distances.sort_unstable_by(|(_, a), (_, b)| b.total_cmp(a));
+    distances.into_iter().map(|(quad, _)| quad).collect()
+}
+
+// Replaces the vertices of a mesh with the quads of another, in the given order, reusing its allocations.
+fn write_sorted_quads(source: &Mesh, target: &mut Mesh, order: &[usize]) {
+    fn gather<T: Copy>(source: &[T], target: &mut Vec<T>, order: &[usize]) {
+        target.clear();
+        target.extend(order.iter().flat_map(|&quad| &source[quad * 4..quad * 4 + 4]));
+    }
+    let stale: Vec<_> = target
+        .attributes()
+        .map(|(attribute, _)| attribute.id)
+        .filter(|&id| !source.contains_attribute(id))
+        .collect();
+    for id in stale {
+        target.remove_attribute(id);
+    }
+    for (attribute, values) in source.attributes() {
+        if !target.contains_attribute(attribute.id) {
+            target.insert_attribute(*attribute, values.clone());
+        }
+        let target_values = target.attribute_mut(attribute.id).unwrap();
+        match (values, target_values) {
+            (VertexAttributeValues::Float32x2(source), VertexAttributeValues::Float32x2(target)) => gather(source, target, order),
+            (VertexAttributeValues::Float32x3(source), VertexAttributeValues::Float32x3(target)) => gather(source, target, order),
+            (VertexAttributeValues::Float32x4(source), VertexAttributeValues::Float32x4(target)) => gather(source, target, order),
+            (VertexAttributeValues::Float32(source), VertexAttributeValues::Float32(target)) => gather(source, target, order),
+            (VertexAttributeValues::Uint32(source), VertexAttributeValues::Uint32(target)) => gather(source, target, order),
+            (VertexAttributeValues::Unorm16x2(source), VertexAttributeValues::Unorm16x2(target)) => gather(source, target, order),
+            (VertexAttributeValues::Snorm16x2(source), VertexAttributeValues::Snorm16x2(target)) => gather(source, target, order),
+            (VertexAttributeValues::Snorm8x4(source), VertexAttributeValues::Snorm8x4(target)) => gather(source, target, order),
+            (VertexAttributeValues::Unorm8x4(source), VertexAttributeValues::Unorm8x4(target)) => gather(source, target, order),
+            (values, target_values) => *target_values = values.clone(),
+        }
+    }
+}
+
+// True if the vertex colors of a batch are all white, so they don't affect rendering.
+fn is_untinted(mesh: &Mesh) -> bool {
+    match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+        Some(VertexAttributeValues::Float32x4(colors)) => colors.iter().all(|&color| color == [1.0; 4]),
+        Some(VertexAttributeValues::Unorm8x4(colors)) => colors.iter().all(|&color| color == [u8::MAX; 4]),
+        _ => false,
+    }
+}
+
+/// Bounds of the sprites of a batch, grown by how far they can sway.
+fn batch_aabb(mesh: &Mesh) -> Aabb {
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        return Aabb::default();
+    };
+    if positions.is_empty() { return Aabb::default() };
+    let (min, max) = positions.iter().fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), &position| {
+        (min.min(position.into()), max.max(position.into()))
+    });
+    let sway = match mesh.attribute(ATTRIBUTE_SWAY) {
+        Some(VertexAttributeValues::Float32x2(sway)) => sway.iter().fold(0.0, |max, [strength, _]| strength.abs().max(max)),
+        _ => 0.0,
+    };
+    Aabb::from_min_max(min - sway, max + sway)
+}
+
+/// Chunk a sprite at a given position is batched in.
+fn batch_chunk(position: Vec3, chunk_size: Option<f32>) -> IVec3 {
+    match chunk_size {
+        Some(chunk_size) => (position / chunk_size).floor().as_ivec3(),
+        None => IVec3::ZERO,
+    }
+}
+
+/// Batch bounds are only known once sprites are batched, after Bevy computed the visibility of batch entities
+/// with their previous bounds. Recomputes the visibility of batches whose bounds changed, so that sprites moving
+/// into view don't disappear for a frame.
+#[allow(clippy::type_complexity)]
+fn refresh_batch_visibility<M: SizedMaterial>(
+    mut views: Query<(&mut VisibleEntities, &Frustum, Option<&RenderLayers>, &Camera)>,
+    mut batches: Query<
+        (Entity, &InheritedVisibility, &mut ViewVisibility, Option<&RenderLayers>, &Aabb),
+        (With<Sprite3dBatch<M>>, Changed<Aabb>),
+    >,
+) {
+    if batches.is_empty() { return };
+    for (mut visible_entities, frustum, view_layers, camera) in &mut views {
+        if !camera.is_active { continue };
+        let view_layers = view_layers.unwrap_or_default();
+        for (entity, inherited_visibility, mut view_visibility, batch_layers, aabb) in &mut batches {
+            if !inherited_visibility.get() || !view_layers.intersects(batch_layers.unwrap_or_default()) { continue };
+            if !frustum.intersects_obb(aabb, &Affine3A::IDENTITY, true, false) { continue };
+            view_visibility.set();
+            let visible_meshes = visible_entities.get_mut::<With<Mesh3d>>();
+            if !visible_meshes.contains(&entity) {
+                visible_meshes.push(entity);
+            }
+        }
+    }
+}
 
-        // Submits sprite vertex data to mesh
-        submit_sprite(mesh, sprite, sprite_transf, sprite_mat_size, sprite_size);
+/// Name of a batch entity, derived from the path of its material's texture, or the material itself.
+fn batch_name<M: SizedMaterial>(
+    sprite_mat_handle: &Handle<M>,
+    materials: &Assets<M>,
+    asset_server: Option<&AssetServer>,
+) -> String {
+    let texture_path = materials
+        .get(sprite_mat_handle)
+        .and_then(SizedMaterial::texture)
+        .and_then(|texture| asset_server?.get_path(texture));
+    let material_path = asset_server.and_then(|server| server.get_path(sprite_mat_handle));
+    match texture_path.or(material_path) {
+        Some(path) => format!("Sprite3d Batch ({})", path.path().display()),
+        None => format!("Sprite3d Batch ({})", sprite_mat_handle.id()),
     }
+}
 
-    fn remove_unloaded_meshes(&mut self, materials: &Assets<M>, commands: &mut Commands,) {
-        self.meshes.retain(|mat_handle, (mesh_entity, _)| {
-            if materials.contains(mat_handle) { true }
-            else {
-                commands.entity(*mesh_entity).despawn();
-                false
-            }
-        });
+fn asset_event_id<A: Asset>(event: &AssetEvent<A>) -> AssetId<A> {
+    match *event {
+        AssetEvent::Added { id }
+        | AssetEvent::Modified { id }
+        | AssetEvent::LoadedWithDependencies { id }
+        | AssetEvent::Removed { id }
+        | AssetEvent::Unused { id } => id,
     }
+}
 
-    fn clear_meshes(&mut self, mesh_assets: &mut Assets<Mesh>) {
-        for (_mesh_entity, mesh_handle) in self.meshes.values_mut() {
-            let mesh = mesh_assets.get_mut(mesh_handle).unwrap();
-            clear_mesh(mesh);
-        }
-    }
+fn is_asset_changed<A: Asset>(event: &AssetEvent<A>) -> bool {
+    matches!(
+        event,
+        AssetEvent::Added { .. } | AssetEvent::Modified { .. } | AssetEvent::LoadedWithDependencies { .. }
+    )
+}
+
+/// Empty mesh with the vertex layout of sprite batches, for the given attributes.
+/// Use it with [`write_sprite_quad`] to generate geometry compatible with sprite batches, ie: from custom particle systems.
+pub fn create_sprite_mesh(attributes: SpriteVertexAttributes) -> Mesh {
+    create_packed_sprite_mesh(attributes, SpriteVertexPacking::NONE)
 }
 
-fn create_mesh() -> Mesh {
+/// Empty mesh with the vertex layout of sprite batches, for the given attributes, in the given formats.
+pub fn create_packed_sprite_mesh(attributes: SpriteVertexAttributes, packing: SpriteVertexPacking) -> Mesh {
+    let uvs = || match packing.uvs {
+        false => VertexAttributeValues::Float32x2(vec![]),
+        true => VertexAttributeValues::Unorm16x2(vec![]),
+    };
     let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all());
     mesh.insert_indices(Indices::U32(vec![]));
     mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, VertexAttributeValues::Float32x3(vec![]));
-    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, VertexAttributeValues::Float32x2(vec![]));
-    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, VertexAttributeValues::Float32x3(vec![]));
-    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, VertexAttributeValues::Float32x4(vec![]));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs());
+    if attributes.secondary_uvs {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_1, uvs());
+    }
+    if attributes.normals {
+        match (packing.octahedral_normals, packing.normals) {
+            (true, _) => mesh.insert_attribute(ATTRIBUTE_NORMAL_OCTAHEDRAL, VertexAttributeValues::Snorm16x2(vec![])),
+            (false, true) => mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, VertexAttributeValues::Snorm8x4(vec![])),
+            (false, false) => mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, VertexAttributeValues::Float32x3(vec![])),
+        }
+    }
+    if attributes.colors {
+        let colors = match packing.colors {
+            false => VertexAttributeValues::Float32x4(vec![]),
+            true => VertexAttributeValues::Unorm8x4(vec![]),
+        };
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+    if attributes.sway {
+        mesh.insert_attribute(ATTRIBUTE_SWAY, VertexAttributeValues::Float32x2(vec![]));
+    }
+    if attributes.layers {
+        mesh.insert_attribute(ATTRIBUTE_LAYER, VertexAttributeValues::Uint32(vec![]));
+    }
+    if attributes.dissolve {
+        mesh.insert_attribute(ATTRIBUTE_DISSOLVE, VertexAttributeValues::Float32(vec![]));
+    }
+    if attributes.palettes {
+        mesh.insert_attribute(ATTRIBUTE_PALETTE, VertexAttributeValues::Uint32(vec![]));
+    }
     mesh
 }
 
-fn submit_sprite(
-    mesh: &mut Mesh,
+/// Vertex data of a single sprite, in world space.
+/// Corners are ordered bottom left, bottom right, top right, top left.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SpriteQuad {
+    pub positions: [[f32; 3]; 4],
+    pub uvs: [[f32; 2]; 4],
+    /// UVs written to `Mesh::ATTRIBUTE_UV_1`.
+    pub secondary_uvs: [[f32; 2]; 4],
+    pub normal: [f32; 3],
+    /// In-plane tilt added to the normal at each corner, for [`SpriteNormals::Spherical`].
+    pub normal_tilts: [[f32; 3]; 4],
+    pub color: [f32; 4],
+    /// Sway strength and phase of each corner, see [`Sprite3dSway`].
+    pub sway: [[f32; 2]; 4],
+    /// Texture array layer, see [`Sprite3d::layer`].
+    pub layer: u32,
+    /// How far the sprite is dissolved, see [`Sprite3dDissolve`].
+    pub dissolve: f32,
+    /// Palette row, see [`Sprite3dPalette`].
+    pub palette: u32,
+    pub facing: Facing,
+}
+
+impl SpriteQuad {
+    /// Vertex data of a sprite, given its transform and the size of its material.
+    pub fn new(
+        sprite: &Sprite3d,
+        sprite_transf: &GlobalTransform,
+        sprite_mat_size: Vec2,
+        color_space: VertexColorSpace,
+    ) -> Self {
+        sprite_quad(sprite, sprite_transf, sprite_mat_size, sprite_size(sprite, sprite_mat_size), color_space, None)
+    }
+
+    pub fn with_sway(mut self, sway: Option<&Sprite3dSway>) -> Self {
+        // Only the top corners sway, the bottom ones stay rooted
+        if let Some(sway) = sway {
+            let [strength, phase] = [sway.strength, sway.phase];
+            self.sway = [[0.0, phase], [0.0, phase], [strength, phase], [strength, phase]];
+        }
+        self
+    }
+}
+
+fn sprite_quad(
     sprite: &Sprite3d,
     sprite_transf: &GlobalTransform,
     sprite_mat_size: Vec2,
     sprite_size: Vec2,
-) {
+    color_space: VertexColorSpace,
+    corners: Option<[Vec3; 4]>,
+) -> SpriteQuad {
     let isize = 1.0 / sprite_mat_size;
     let hsize = sprite_size * 0.5;
     let transf = sprite_transf.affine();
     let offset = -sprite.anchor.as_vec() * sprite_size;
     let offset = Vec3A::new(offset.x, offset.y, 0.0);
     
-    let bl = transf.transform_point3a(Vec3A::new(-hsize.x, -hsize.y, 0.0) + offset);
-    let br = transf.transform_point3a(Vec3A::new(hsize.x, -hsize.y, 0.0) + offset);
-    let tr = transf.transform_point3a(Vec3A::new(hsize.x, hsize.y, 0.0) + offset);
-    let tl = transf.transform_point3a(Vec3A::new(-hsize.x, hsize.y, 0.0) + offset);
+    // Rectangular quads transform their center and half axes once, and offset the corners from them
+    let [bl, br, tr, tl] = match corners {
+        Some(corners) => corners.map(|corner| transf.transform_point3a(corner.into())),
+        None => {
+            let center = transf.transform_point3a(offset);
+            let right = transf.matrix3.x_axis * hsize.x;
+            let up = transf.matrix3.y_axis * hsize.y;
+            [center - right - up, center + right - up, center + right + up, center - right + up]
+        },
+    };
     let norm = (br - bl).cross(tl - bl).normalize();
+    let normal_tilts = match sprite.normals {
+        SpriteNormals::Flat => [[0.0; 3]; 4],
+        SpriteNormals::Spherical(tilt) => {
+            let center = (bl + br + tr + tl) * 0.25;
+            [bl, br, tr, tl].map(|corner| ((corner - center).normalize_or_zero() * tilt).to_array())
+        },
+    };
     
-    let (mut bl_uv, mut br_uv, mut tr_uv, mut tl_uv) = match sprite.rect {
+    let uv_rect = match (sprite.rect, sprite.uv_rect) {
+        (Some(rect), _) => Some(Rect { min: rect.min * isize, max: rect.max * isize }),
+        (None, uv_rect) => uv_rect,
+    };
+    let secondary_uv_rect = sprite.secondary_rect
+        .map(|rect| Rect { min: rect.min * isize, max: rect.max * isize })
+        .or(uv_rect);
+
+    SpriteQuad {
+        positions: [bl.to_array(), br.to_array(), tr.to_array(), tl.to_array()],
+        uvs: quad_uvs(sprite, uv_rect),
+        secondary_uvs: quad_uvs(sprite, secondary_uv_rect),
+        normal: norm.to_array(),
+        normal_tilts,
+        color: color_space.convert(sprite.color),
+        sway: [[0.0; 2]; 4],
+        layer: sprite.layer,
+        dissolve: 0.0,
+        palette: 0,
+        facing: sprite.facing,
+    }
+}
+
+/// UVs of a quad's corners (bl, br, tr, tl), given the normalized region of the texture to render.
+fn quad_uvs(sprite: &Sprite3d, uv_rect: Option<Rect>) -> [[f32; 2]; 4] {
+    let (mut bl_uv, mut br_uv, mut tr_uv, mut tl_uv) = match uv_rect {
         Some(rect) => {
             (
-                [rect.min.x*isize.x, rect.max.y*isize.y],
-                [rect.max.x*isize.x, rect.max.y*isize.y],
-                [rect.max.x*isize.x, rect.min.y*isize.y],
-                [rect.min.x*isize.x, rect.min.y*isize.y],
+                [rect.min.x, rect.max.y],
+                [rect.max.x, rect.max.y],
+                [rect.max.x, rect.min.y],
+                [rect.min.x, rect.min.y],
             )
         },
         None => ([0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]),
     };
+    // Diagonal flip happens first, like in Tiled
+    if sprite.flip_d {
+        std::mem::swap(&mut tr_uv, &mut bl_uv);
+    }
     if sprite.flip_x {
-        std::mem::swap(&mut tl_uv[0], &mut tr_uv[0]);
-        std::mem::swap(&mut bl_uv[0], &mut br_uv[0]);
+        std::mem::swap(&mut tl_uv, &mut tr_uv);
+        std::mem::swap(&mut bl_uv, &mut br_uv);
     }
     if sprite.flip_y {
-        std::mem::swap(&mut tl_uv[1], &mut bl_uv[1]);
-        std::mem::swap(&mut tr_uv[1], &mut br_uv[1]);
+        std::mem::swap(&mut tl_uv, &mut bl_uv);
+        std::mem::swap(&mut tr_uv, &mut br_uv);
     }
+    let uvs = [bl_uv, br_uv, tr_uv, tl_uv];
+    if sprite.uv_offset == Vec2::ZERO && sprite.uv_scale == Vec2::ONE {
+        return uvs;
+    }
+    uvs.map(|uv| (Vec2::from(uv) * sprite.uv_scale + sprite.uv_offset).to_array())
+}
 
-    let mesh_positions = match mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION) {
-        Some(VertexAttributeValues::Float32x3(values)) => values,
-        _ => panic!("Missing mesh positions"),
-    };
-    let i = mesh_positions.len() as u32;
-    mesh_positions.extend([bl.to_array(), br.to_array(), tr.to_array(), tl.to_array()]);
-
-    let mesh_uvs = match mesh.attribute_mut(Mesh::ATTRIBUTE_UV_0) {
-        Some(VertexAttributeValues::Float32x2(values)) => values,
-        _ => panic!("Missing mesh uvs"),
-    };
-    mesh_uvs.extend([bl_uv, br_uv, tr_uv, tl_uv]);
-
-    let mesh_norms = match mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL) {
-        Some(VertexAttributeValues::Float32x3(values)) => values,
-        _ => panic!("Missing mesh normals"),
-    };
-    mesh_norms.extend([norm.to_array(), norm.to_array(), norm.to_array(), norm.to_array()]);
-
-    let mesh_colors = match mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR) {
-        Some(VertexAttributeValues::Float32x4(values)) => values,
-        _ => panic!("Missing mesh colors"),
-    };
-    let color = sprite.color.to_linear().to_f32_array();
-    mesh_colors.extend([color, color, color, color]);
+/// Reserves space in a mesh for an additional number of quads.
+pub fn reserve_sprite_quads(mesh: &mut Mesh, quad_count: usize) {
+    if let Some(Indices::U32(indices)) = mesh.indices_mut() {
+        indices.reserve(quad_count * 6);
+    }
+    for (_, values) in mesh.attributes_mut() {
+        match values {
+            VertexAttributeValues::Float32x2(values) => values.reserve(quad_count * 4),
+            VertexAttributeValues::Float32x3(values) => values.reserve(quad_count * 4),
+            VertexAttributeValues::Float32x4(values) => values.reserve(quad_count * 4),
+            VertexAttributeValues::Float32(values) => values.reserve(quad_count * 4),
+            VertexAttributeValues::Uint32(values) => values.reserve(quad_count * 4),
+            VertexAttributeValues::Unorm16x2(values) => values.reserve(quad_count * 4),
+            VertexAttributeValues::Snorm16x2(values) => values.reserve(quad_count * 4),
+            VertexAttributeValues::Snorm8x4(values) => values.reserve(quad_count * 4),
+            VertexAttributeValues::Unorm8x4(values) => values.reserve(quad_count * 4),
+            _ => {},
+        }
+    }
+}
 
+/// Appends a quad to a mesh created with [`create_sprite_mesh`] or [`create_packed_sprite_mesh`], writing the
+/// attributes the mesh has.
+/// Panics if the mesh is missing positions, UVs or `u32` indices.
+pub fn write_sprite_quad(mesh: &mut Mesh, quad: &SpriteQuad) {
+    let first_quad = mesh.count_vertices() / 4;
+    write_sprite_quad_vertices(mesh, quad);
+    let quad_count = mesh.count_vertices() / 4;
     let mesh_indices = match mesh.indices_mut() {
         Some(Indices::U32(mesh_indices)) => mesh_indices,
         _ => panic!("Missing mesh indices"),
     };
-    mesh_indices.extend([i+0, i+1, i+2, i+2, i+3, i+0]);
+    mesh_indices.truncate(first_quad * 6);
+    mesh_indices.extend((first_quad..quad_count).flat_map(quad_indices));
+}
+
+// Appends the vertices of a quad to a mesh, leaving its indices to be fitted afterwards.
+fn write_sprite_quad_vertices(mesh: &mut Mesh, quad: &SpriteQuad) {
+    SpriteVertexWriter::new(mesh).write(quad);
+}
+
+// Attributes of a sprite mesh, looked up once to append the vertices of many quads.
+struct SpriteVertexWriter<'a> {
+    positions: &'a mut Vec<[f32; 3]>,
+    uvs: &'a mut VertexAttributeValues,
+    secondary_uvs: Option<&'a mut VertexAttributeValues>,
+    normals: Option<&'a mut VertexAttributeValues>,
+    octahedral_normals: Option<&'a mut Vec<[i16; 2]>>,
+    colors: Option<&'a mut VertexAttributeValues>,
+    sway: Option<&'a mut Vec<[f32; 2]>>,
+    layers: Option<&'a mut Vec<u32>>,
+    dissolve: Option<&'a mut Vec<f32>>,
+    palettes: Option<&'a mut Vec<u32>>,
+}
+
+impl<'a> SpriteVertexWriter<'a> {
+
+    // Panics if the mesh is missing positions or UVs.
+    fn new(mesh: &'a mut Mesh) -> Self {
+        let (mut positions, mut uvs, mut secondary_uvs, mut normals, mut octahedral_normals) = (None, None, None, None, None);
+        let (mut colors, mut sway, mut layers, mut dissolve, mut palettes) = (None, None, None, None, None);
+        for (attribute, values) in mesh.attributes_mut() {
+            let id = attribute.id;
+            if id == Mesh::ATTRIBUTE_UV_0.id {
+                uvs = Some(values);
+            } else if id == Mesh::ATTRIBUTE_UV_1.id {
+                secondary_uvs = Some(values);
+            } else if id == Mesh::ATTRIBUTE_NORMAL.id {
+                normals = Some(values);
+            } else if id == Mesh::ATTRIBUTE_COLOR.id {
+                colors = Some(values);
+            } else {
+                match values {
+                    VertexAttributeValues::Float32x3(values) if id == Mesh::ATTRIBUTE_POSITION.id => positions = Some(values),
+                    VertexAttributeValues::Snorm16x2(values) if id == ATTRIBUTE_NORMAL_OCTAHEDRAL.id => octahedral_normals = Some(values),
+                    VertexAttributeValues::Float32x2(values) if id == ATTRIBUTE_SWAY.id => sway = Some(values),
+                    VertexAttributeValues::Float32(values) if id == ATTRIBUTE_DISSOLVE.id => dissolve = Some(values),
+                    VertexAttributeValues::Uint32(values) => match id {
+                        id if id == ATTRIBUTE_LAYER.id => layers = Some(values),
+                        id if id == ATTRIBUTE_PALETTE.id => palettes = Some(values),
+                        _ => {},
+                    },
+                    _ => {},
+                }
+            }
+        }
+        Self {
+            positions: positions.expect("Missing mesh positions"),
+            uvs: uvs.expect("Missing mesh uvs"),
+            secondary_uvs,
+            normals,
+            octahedral_normals,
+            colors,
+            sway,
+            layers,
+            dissolve,
+            palettes,
+        }
+    }
+
+    fn write(&mut self, quad: &SpriteQuad) {
+        match quad.facing {
+            Facing::Front => self.write_face(quad, false),
+            Facing::Back => self.write_face(quad, true),
+            Facing::Both => {
+                self.write_face(quad, false);
+                self.write_face(quad, true);
+            },
+        }
+    }
+
+    // Writes the vertices of one side of a quad, in the formats the mesh was created with. The back side has its
+    // vertex order and normal reversed, so that every quad shares the same index pattern.
+    fn write_face(&mut self, quad: &SpriteQuad, back: bool) {
+        let order = match back {
+            false => [0, 1, 2, 3],
+            true => [0, 3, 2, 1],
+        };
+        self.positions.extend(order.map(|v| quad.positions[v]));
+        match &mut self.uvs {
+            VertexAttributeValues::Float32x2(mesh_uvs) => mesh_uvs.extend(order.map(|v| quad.uvs[v])),
+            VertexAttributeValues::Unorm16x2(mesh_uvs) => mesh_uvs.extend(order.map(|v| pack_unorm16(quad.uvs[v]))),
+            _ => panic!("Missing mesh uvs"),
+        }
+
+        // Optional attributes, depending on the material
+        match self.secondary_uvs.as_deref_mut() {
+            Some(VertexAttributeValues::Float32x2(mesh_secondary_uvs)) => {
+                mesh_secondary_uvs.extend(order.map(|v| quad.secondary_uvs[v]));
+            },
+            Some(VertexAttributeValues::Unorm16x2(mesh_secondary_uvs)) => {
+                mesh_secondary_uvs.extend(order.map(|v| pack_unorm16(quad.secondary_uvs[v])));
+            },
+            _ => {},
+        }
+        let normal = match back {
+            false => Vec3A::from(quad.normal),
+            true => -Vec3A::from(quad.normal),
+        };
+        let normals = || order.map(|v| (normal + Vec3A::from(quad.normal_tilts[v])).normalize());
+        match self.normals.as_deref_mut() {
+            Some(VertexAttributeValues::Float32x3(mesh_norms)) => mesh_norms.extend(normals().map(|normal| normal.to_array())),
+            Some(VertexAttributeValues::Snorm8x4(mesh_norms)) => mesh_norms.extend(normals().map(pack_snorm8)),
+            _ => {},
+        }
+        if let Some(mesh_norms) = &mut self.octahedral_normals {
+            mesh_norms.extend(normals().map(pack_octahedral));
+        }
+        match self.colors.as_deref_mut() {
+            Some(VertexAttributeValues::Float32x4(mesh_colors)) => mesh_colors.extend([quad.color; 4]),
+            Some(VertexAttributeValues::Unorm8x4(mesh_colors)) => mesh_colors.extend([pack_unorm8(quad.color); 4]),
+            _ => {},
+        }
+        if let Some(mesh_sway) = &mut self.sway {
+            mesh_sway.extend(order.map(|v| quad.sway[v]));
+        }
+        if let Some(mesh_layers) = &mut self.layers {
+            mesh_layers.extend([quad.layer; 4]);
+        }
+        if let Some(mesh_dissolve) = &mut self.dissolve {
+            mesh_dissolve.extend([quad.dissolve; 4]);
+        }
+        if let Some(mesh_palettes) = &mut self.palettes {
+            mesh_palettes.extend([quad.palette; 4]);
+        }
+    }
+}
+
+/// Indices of the two triangles of a quad, given its position in a mesh.
+fn quad_indices(quad: usize) -> [u32; 6] {
+    let i = quad as u32 * 4;
+    [i, i+1, i+2, i+2, i+3, i]
+}
+
+/// Indices of consecutive quads, generated once up to the largest quad count seen, and copied from afterwards.
+#[derive(Default, Debug)]
+struct QuadIndices(Vec<u32>);
+
+impl QuadIndices {
+    fn get(&mut self, quad_count: usize) -> &[u32] {
+        let generated = self.0.len() / 6;
+        if generated < quad_count {
+            self.0.extend((generated..quad_count).flat_map(quad_indices));
+        }
+        &self.0[..quad_count * 6]
+    }
+}
+
+// Sizes the indices of a sprite mesh to its quads. Indices already written are kept, as they don't depend
+// on what the quads contain, so a batch only copies indices when it grows.
+fn fit_sprite_indices(mesh: &mut Mesh, quad_indices: &mut QuadIndices) {
+    let quad_count = mesh.count_vertices() / 4;
+    let Some(Indices::U32(mesh_indices)) = mesh.indices_mut() else { return };
+    let written = mesh_indices.len().min(quad_count * 6);
+    mesh_indices.truncate(written);
+    mesh_indices.extend_from_slice(&quad_indices.get(quad_count)[written..]);
 }
 
-fn clear_mesh(mesh: &mut Mesh) {
+/// Removes all vertices and indices from a mesh, keeping its attributes and their allocations.
+pub fn clear_sprite_mesh(mesh: &mut Mesh) {
     match mesh.indices_mut() {
         Some(Indices::U16(indices)) => indices.clear(),
         Some(Indices::U32(indices)) => indices.clear(),
         None => {},
     }
+    clear_sprite_vertices(mesh);
+}
+
+// Removes all vertices from a mesh, keeping its indices, its attributes and their allocations.
+fn clear_sprite_vertices(mesh: &mut Mesh) {
     for (_, values) in mesh.attributes_mut() {
         match values {
             VertexAttributeValues::Float32(values)      => values.clear(),
@@ -294,16 +2727,628 @@ fn clear_mesh(mesh: &mut Mesh) {
     }
 }
 
+/// Optional vertex attributes of sprite batches.
+/// Positions and UVs (`Mesh::ATTRIBUTE_UV_0`) are always present.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct SpriteVertexAttributes {
+    /// `Mesh::ATTRIBUTE_NORMAL`, needed for lighting.
+    pub normals: bool,
+    /// `Mesh::ATTRIBUTE_COLOR`, needed for tinting. Left out of untinted batches if
+    /// [`SizedMaterial::vertex_colors_optional`].
+    pub colors: bool,
+    /// `Mesh::ATTRIBUTE_UV_1`, written from [`Sprite3d::secondary_rect`].
+    pub secondary_uvs: bool,
+    /// [`ATTRIBUTE_SWAY`], written from [`Sprite3dSway`].
+    pub sway: bool,
+    /// [`ATTRIBUTE_LAYER`], written from [`Sprite3d::layer`].
+    pub layers: bool,
+    /// [`ATTRIBUTE_DISSOLVE`], written from [`Sprite3dDissolve`].
+    pub dissolve: bool,
+    /// [`ATTRIBUTE_PALETTE`], written from [`Sprite3dPalette`].
+    pub palettes: bool,
+}
+
+impl SpriteVertexAttributes {
+    pub const ALL: Self = Self {
+        normals: true,
+        colors: true,
+        secondary_uvs: true,
+        sway: true,
+        layers: true,
+        dissolve: true,
+        palettes: true,
+    };
+    pub const NONE: Self = Self {
+        normals: false,
+        colors: false,
+        secondary_uvs: false,
+        sway: false,
+        layers: false,
+        dissolve: false,
+        palettes: false,
+    };
+
+    /// Attributes required by either `self` or `other`.
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            normals: self.normals || other.normals,
+            colors: self.colors || other.colors,
+            secondary_uvs: self.secondary_uvs || other.secondary_uvs,
+            sway: self.sway || other.sway,
+            layers: self.layers || other.layers,
+            dissolve: self.dissolve || other.dissolve,
+            palettes: self.palettes || other.palettes,
+        }
+    }
+}
+
+/// The attributes [`StandardMaterial`] reads.
+impl Default for SpriteVertexAttributes {
+    fn default() -> Self {
+        Self { sway: false, layers: false, dissolve: false, palettes: false, ..Self::ALL }
+    }
+}
+
+/// Size in pixels of the full resolution level of a texture, read from its descriptor rather than its pixel data.
+/// Compressed (ie: BCn, ASTC, ETC2 from KTX2 or Basis files) and mipmapped textures report it as soon as they are
+/// loaded, whatever the size of their data. Array textures report the size of a single layer.
+pub fn texture_size(image: &Image) -> Vec2 {
+    let size = image.texture_descriptor.size;
+    Vec2::new(size.width as f32, size.height as f32)
+}
+
 /// Material that is able to report its size in pixels.
 pub trait SizedMaterial: Material {
     fn size(&self, images: &Assets<Image>) -> Option<Vec2>;
+
+    /// Texture that best identifies the material, if any.
+    /// Used to name batch entities in inspectors.
+    fn texture(&self) -> Option<&Handle<Image>> {
+        None
+    }
+
+    /// Optional vertex attributes the material's shader reads.
+    /// Leaving out unused attributes shrinks batch meshes, and the bandwidth needed to upload them.
+    fn required_vertex_attributes() -> SpriteVertexAttributes {
+        SpriteVertexAttributes::default()
+    }
+
+    /// If true, the material's shaders handle batches without `Mesh::ATTRIBUTE_COLOR`, ie: behind
+    /// `#ifdef VERTEX_COLORS`, so it is left out of batches whose sprites are all untinted.
+    fn vertex_colors_optional() -> bool {
+        false
+    }
+
+    /// Compact vertex formats the material's shaders can read, used when batching with
+    /// [`Sprite3dPlugin::packed_vertices`]. Defaults to formats that every shader reads unchanged, which loses
+    /// precision, ie: HDR vertex colors. Return [`SpriteVertexPacking::NONE`] if the material relies on it.
+    fn supported_vertex_packing() -> SpriteVertexPacking {
+        SpriteVertexPacking::default()
+    }
+
+    /// Copy of the material with [`SizedMaterial::texture`] replaced.
+    /// Used to render sprites with a [`SpriteFilter`]. If None, such sprites use the material as-is.
+    fn with_texture(&self, _texture: Handle<Image>) -> Option<Self> {
+        None
+    }
+
+    /// Copy of the material with its depth biased by an additional amount, so that it sorts in front of
+    /// (or behind, if negative) other materials.
+    /// Used to render sprites with a [`Sprite3dDrawOrder`]. If None, such sprites use the material as-is.
+    fn with_depth_bias(&self, _depth_bias: f32) -> Option<Self> {
+        None
+    }
+
+    /// Copy of the material that isn't rendered in depth, normal and motion vector prepasses.
+    /// Used to render sprites with [`Sprite3dNoPrepass`]. If None, such sprites use the material as-is.
+    fn without_prepass(&self) -> Option<Self> {
+        None
+    }
+
+    /// Copy of the material that isn't affected by distance fog.
+    /// Used to render [`Sprite3dSky`] sprites. If None, such sprites use the material as-is.
+    fn without_fog(&self) -> Option<Self> {
+        None
+    }
+
+    /// Copy of the material with a different alpha mode.
+    /// Used by [`Sprite3dBatchConfig::alpha_mode`]. If None, batches use the material as-is.
+    fn with_alpha_mode(&self, _alpha_mode: AlphaMode) -> Option<Self> {
+        None
+    }
+
+    /// Hash of the material's parameters and textures, equal for materials that render identically.
+    /// Used to batch such materials together, when [`Sprite3dPlugin::dedup_materials`] is enabled.
+    /// If None, the material is only batched with sprites that use the same handle.
+    fn content_hash(&self) -> Option<u64> {
+        None
+    }
+
+    /// If true, the material blends with what's behind it, so its sprites need sorting back-to-front.
+    /// Used to copy batches for [`Sprite3dSortedView`] cameras.
+    fn is_transparent(&self) -> bool {
+        false
+    }
 }
 
 impl SizedMaterial for StandardMaterial {
-    /// Attempts to report its size as the size of its base color texture.
+    /// Attempts to report its size as the [`texture_size`] of its base color texture.
     fn size(&self, images: &Assets<Image>) -> Option<Vec2> {
         let base_color_texture = self.base_color_texture.as_ref()?;
-        let image = images.get(base_color_texture)?;
-        Some(image.size_f32()) 
+        images.get(base_color_texture).map(texture_size)
+    }
+
+    fn texture(&self) -> Option<&Handle<Image>> {
+        self.base_color_texture.as_ref()
+    }
+
+    fn vertex_colors_optional() -> bool {
+        true
+    }
+
+    fn with_texture(&self, texture: Handle<Image>) -> Option<Self> {
+        Some(Self {
+            base_color_texture: Some(texture),
+            ..self.clone()
+        })
+    }
+
+    fn with_depth_bias(&self, depth_bias: f32) -> Option<Self> {
+        Some(Self {
+            depth_bias: self.depth_bias + depth_bias,
+            ..self.clone()
+        })
+    }
+
+    /// Prepasses skip blended materials, so opaque and masked materials are blended instead.
+    fn without_prepass(&self) -> Option<Self> {
+        match self.alpha_mode {
+            AlphaMode::Opaque | AlphaMode::Mask(_) | AlphaMode::AlphaToCoverage => Some(Self {
+                alpha_mode: AlphaMode::Blend,
+                ..self.clone()
+            }),
+            _ => None,
+        }
+    }
+
+    fn without_fog(&self) -> Option<Self> {
+        Some(Self {
+            fog_enabled: false,
+            ..self.clone()
+        })
+    }
+
+    fn with_alpha_mode(&self, alpha_mode: AlphaMode) -> Option<Self> {
+        Some(Self {
+            alpha_mode,
+            ..self.clone()
+        })
+    }
+
+    /// Hashes each field of the material: textures by asset id, so strong and weak handles to a texture hash
+    /// equal, floats and colors by their bits, and enums by their variant and fields.
+    fn content_hash(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        for color in [self.base_color, self.attenuation_color] {
+            hash_floats(&mut hasher, &color.to_linear().to_f32_array());
+        }
+        hash_floats(&mut hasher, &self.emissive.to_f32_array());
+        hash_floats(&mut hasher, &[
+            self.emissive_exposure_weight,
+            self.perceptual_roughness,
+            self.metallic,
+            self.reflectance,
+            self.diffuse_transmission,
+            self.specular_transmission,
+            self.thickness,
+            self.ior,
+            self.attenuation_distance,
+            self.clearcoat,
+            self.clearcoat_perceptual_roughness,
+            self.anisotropy_strength,
+            self.anisotropy_rotation,
+            self.depth_bias,
+            self.parallax_depth_scale,
+            self.max_parallax_layer_count,
+            self.lightmap_exposure,
+        ]);
+        hash_floats(&mut hasher, &self.uv_transform.to_cols_array());
+        (self.flip_normal_map_y, self.double_sided, self.unlit, self.fog_enabled).hash(&mut hasher);
+        (self.cull_mode, self.deferred_lighting_pass_id).hash(&mut hasher);
+        std::mem::discriminant(&self.alpha_mode).hash(&mut hasher);
+        if let AlphaMode::Mask(cutoff) = self.alpha_mode {
+            cutoff.to_bits().hash(&mut hasher);
+        }
+        std::mem::discriminant(&self.parallax_mapping_method).hash(&mut hasher);
+        if let ParallaxMappingMethod::Relief { max_steps } = self.parallax_mapping_method {
+            max_steps.hash(&mut hasher);
+        }
+        std::mem::discriminant(&self.opaque_render_method).hash(&mut hasher);
+
+        // Textures and their UV channels are found through reflection, as some only exist with Bevy's optional
+        // `pbr_*` features
+        for field in self.iter_fields() {
+            if let Some(texture) = field.try_downcast_ref::<Option<Handle<Image>>>() {
+                texture.as_ref().map(Handle::id).hash(&mut hasher);
+            } else if let Some(channel) = field.try_downcast_ref::<UvChannel>() {
+                std::mem::discriminant(channel).hash(&mut hasher);
+            }
+        }
+        Some(hasher.finish())
+    }
+
+    fn is_transparent(&self) -> bool {
+        matches!(
+            self.alpha_mode,
+            AlphaMode::Blend | AlphaMode::Premultiplied | AlphaMode::Add | AlphaMode::Multiply
+        )
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Continues a 64-bit FNV-1a hash with the given bytes.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Hashes floats by their bits, as they don't implement [`Hash`].
+fn hash_floats(hasher: &mut impl Hasher, floats: &[f32]) {
+    for float in floats {
+        float.to_bits().hash(hasher);
+    }
+}
+
+/// [`MaterialExtension`] that can be used in sprite materials, ie: [`ExtendedMaterial<StandardMaterial, E>`].
+pub trait SpriteMaterialExtension: MaterialExtension {
+    /// Optional vertex attributes the extension's shaders read, on top of the base material's.
+    fn required_vertex_attributes() -> SpriteVertexAttributes {
+        SpriteVertexAttributes::NONE
+    }
+
+    /// If true, the extension's shaders handle batches without `Mesh::ATTRIBUTE_COLOR`, see
+    /// [`SizedMaterial::vertex_colors_optional`]. Extensions without their own vertex shader can return true.
+    fn vertex_colors_optional() -> bool {
+        false
+    }
+}
+
+impl<B: SizedMaterial, E: SpriteMaterialExtension> SizedMaterial for ExtendedMaterial<B, E> {
+    fn size(&self, images: &Assets<Image>) -> Option<Vec2> {
+        self.base.size(images)
+    }
+
+    fn texture(&self) -> Option<&Handle<Image>> {
+        self.base.texture()
+    }
+
+    fn required_vertex_attributes() -> SpriteVertexAttributes {
+        B::required_vertex_attributes().union(E::required_vertex_attributes())
+    }
+
+    fn vertex_colors_optional() -> bool {
+        B::vertex_colors_optional() && E::vertex_colors_optional()
+    }
+
+    // Extensions with their own vertex shader read normals as floats.
+    fn supported_vertex_packing() -> SpriteVertexPacking {
+        SpriteVertexPacking { octahedral_normals: false, ..B::supported_vertex_packing() }
+    }
+
+    fn with_texture(&self, texture: Handle<Image>) -> Option<Self> {
+        Some(Self {
+            base: self.base.with_texture(texture)?,
+            extension: self.extension.clone(),
+        })
+    }
+
+    fn with_depth_bias(&self, depth_bias: f32) -> Option<Self> {
+        Some(Self {
+            base: self.base.with_depth_bias(depth_bias)?,
+            extension: self.extension.clone(),
+        })
+    }
+
+    fn without_prepass(&self) -> Option<Self> {
+        Some(Self {
+            base: self.base.without_prepass()?,
+            extension: self.extension.clone(),
+        })
+    }
+
+    fn without_fog(&self) -> Option<Self> {
+        Some(Self {
+            base: self.base.without_fog()?,
+            extension: self.extension.clone(),
+        })
+    }
+
+    fn with_alpha_mode(&self, alpha_mode: AlphaMode) -> Option<Self> {
+        Some(Self {
+            base: self.base.with_alpha_mode(alpha_mode)?,
+            extension: self.extension.clone(),
+        })
+    }
+
+    fn is_transparent(&self) -> bool {
+        self.base.is_transparent()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::render::render_resource::{AsBindGroup, Extent3d, TextureFormat};
+
+    use crate::test_utils::{batch_vertex_count, material_test_app, test_app, textured_material};
+    use crate::*;
+
+    /// Quad count of every sprite in the app.
+    fn quad_counts(app: &mut App) -> Vec<usize> {
+        let mut sprites = app.world_mut().query::<SpriteQuery<StandardMaterial>>();
+        sprites.iter(app.world()).map(|item| item.quad_count()).collect()
+    }
+
+    #[test]
+    fn double_sided_sprites_count_two_quads() {
+        let mut app = test_app(Sprite3dPlugin::default());
+        let material = textured_material(&mut app, 16, 16);
+        app.world_mut().spawn((
+            Sprite3d { facing: Facing::Both, ..default() },
+            SpriteMaterial3d(material.clone()),
+            Sprite3dParts(vec![Sprite3dPart::default()]),
+        ));
+        app.world_mut().spawn((Sprite3d::default(), SpriteMaterial3d(material)));
+        app.update();
+        let mut counts = quad_counts(&mut app);
+        counts.sort();
+        assert_eq!(counts, vec![1, 4]);
+    }
+
+    #[test]
+    fn sprite_budget_of_zero_still_renders() {
+        let mut app = test_app(Sprite3dPlugin::default().with_budget(BatchBudget::Sprites(0)));
+        let material = textured_material(&mut app, 16, 16);
+        app.world_mut().spawn((Sprite3d::default(), SpriteMaterial3d(material.clone())));
+        app.update();
+        app.update();
+        assert_eq!(batch_vertex_count(&mut app, &material), 4);
+    }
+
+    /// Spawns a sprite fading out on group 1, which is hidden if the app has groups.
+    fn spawn_faded_sprite_of_hidden_group(app: &mut App, material: Handle<StandardMaterial>) -> Entity {
+        if let Some(mut groups) = app.world_mut().get_resource_mut::<Sprite3dGroups>() {
+            groups.hide(1);
+        }
+        let fade = Sprite3dFade::out(Duration::from_secs(1), Color::WHITE);
+        app.world_mut().spawn((Sprite3d::default(), SpriteMaterial3d(material), Sprite3dGroup(1), fade)).id()
+    }
+
+    #[test]
+    fn features_are_left_out_without_their_plugins() {
+        let mut app = test_app(Sprite3dPlugin::default());
+        let material = textured_material(&mut app, 16, 16);
+        let sprite = spawn_faded_sprite_of_hidden_group(&mut app, material.clone());
+        app.update();
+        app.update();
+        assert!(!app.world().contains_resource::<Sprite3dGroups>());
+        assert!(!app.world().contains_resource::<Sprite3dLayers>());
+        assert!(!app.world().contains_resource::<Sprite3dAnimationTime>());
+        assert!(!app.world().contains_resource::<Assets<Sprite3dClip>>());
+        assert_eq!(app.world().get::<Sprite3dFade>(sprite).unwrap().elapsed, Duration::ZERO);
+        assert_eq!(batch_vertex_count(&mut app, &material), 4);
+    }
+
+    #[test]
+    fn features_run_with_their_plugins() {
+        let mut app = test_app(Sprite3dPlugin::default());
+        app.add_plugins((Sprite3dFadePlugin, Sprite3dGroupPlugin));
+        let material = textured_material(&mut app, 16, 16);
+        let sprite = spawn_faded_sprite_of_hidden_group(&mut app, material.clone());
+        app.update();
+        app.update();
+        assert!(app.world().get::<Sprite3dFade>(sprite).unwrap().elapsed > Duration::ZERO);
+        assert_eq!(batch_vertex_count(&mut app, &material), 0);
+    }
+
+    #[test]
+    fn budgeted_sprites_leave_their_batch_for_an_unloaded_material() {
+        let mut app = test_app(Sprite3dPlugin::default().with_budget(BatchBudget::Sprites(100)));
+        let material = textured_material(&mut app, 16, 16);
+        let unloaded = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+            base_color_texture: Some(Handle::weak_from_u128(0x5eed)),
+            ..default()
+        });
+        let sprite = app.world_mut().spawn((Sprite3d::default(), SpriteMaterial3d(material.clone()))).id();
+        app.update();
+        assert_eq!(batch_vertex_count(&mut app, &material), 4);
+
+        app.world_mut().entity_mut(sprite).insert(SpriteMaterial3d(unloaded.clone()));
+        app.update();
+        assert_eq!(batch_vertex_count(&mut app, &material), 0);
+        assert_eq!(batch_vertex_count(&mut app, &unloaded), 0);
+    }
+
+    #[test]
+    fn content_hash_compares_textures_by_id() {
+        let texture = Handle::<Image>::weak_from_u128(0x7e57);
+        let material = |texture: Handle<Image>| StandardMaterial { base_color_texture: Some(texture), ..default() };
+        let strong = Assets::<Image>::default().add(Image::default());
+        assert_eq!(
+            material(texture.clone()).content_hash(),
+            material(texture.clone_weak()).content_hash(),
+        );
+        assert_eq!(
+            material(strong.clone()).content_hash(),
+            material(strong.clone_weak()).content_hash(),
+        );
+        assert_ne!(material(texture).content_hash(), material(strong).content_hash());
+    }
+
+    #[test]
+    fn content_hash_covers_parameters() {
+        let base = StandardMaterial::default();
+        assert_eq!(base.content_hash(), StandardMaterial::default().content_hash());
+        let variants = [
+            StandardMaterial { base_color: Color::srgb(1.0, 0.0, 0.0), ..default() },
+            StandardMaterial { alpha_mode: AlphaMode::Mask(0.5), ..default() },
+            StandardMaterial { alpha_mode: AlphaMode::Mask(0.25), ..default() },
+            StandardMaterial { cull_mode: None, ..default() },
+            StandardMaterial { unlit: true, ..default() },
+            StandardMaterial { base_color_channel: UvChannel::Uv1, ..default() },
+            StandardMaterial { depth_bias: 1.0, ..default() },
+        ];
+        let mut hashes: Vec<_> = variants.iter().map(|material| material.content_hash().unwrap()).collect();
+        hashes.push(base.content_hash().unwrap());
+        hashes.sort();
+        hashes.dedup();
+        assert_eq!(hashes.len(), variants.len() + 1);
+    }
+
+    /// Material whose shader always reads vertex colors.
+    #[derive(Asset, TypePath, AsBindGroup, Clone, Debug)]
+    struct VertexColorMaterial {}
+
+    impl Material for VertexColorMaterial {}
+
+    impl SizedMaterial for VertexColorMaterial {
+        fn size(&self, _images: &Assets<Image>) -> Option<Vec2> {
+            Some(Vec2::ONE)
+        }
+    }
+
+    /// Vertex color attribute and estimated bytes of the only batch of a material.
+    fn batch_colors(app: &mut App) -> (bool, usize) {
+        let mut batches = app.world_mut().query::<(&Mesh3d, &Sprite3dBatchInfo)>();
+        let (mesh, info) = batches.single(app.world());
+        let mesh = app.world().resource::<Assets<Mesh>>().get(&mesh.0).unwrap();
+        (mesh.contains_attribute(Mesh::ATTRIBUTE_COLOR), info.bytes)
+    }
+
+    #[test]
+    fn untinted_batches_drop_vertex_colors() {
+        let mut app = test_app(Sprite3dPlugin::default());
+        let material = textured_material(&mut app, 16, 16);
+        let sprite = app.world_mut().spawn((Sprite3d::default(), SpriteMaterial3d(material))).id();
+        app.update();
+        let (untinted_colors, untinted_bytes) = batch_colors(&mut app);
+        assert!(!untinted_colors);
+
+        app.world_mut().get_mut::<Sprite3d>(sprite).unwrap().color = Color::srgb(1.0, 0.0, 0.0);
+        app.update();
+        let (tinted_colors, tinted_bytes) = batch_colors(&mut app);
+        assert!(tinted_colors);
+        assert_eq!(tinted_bytes - untinted_bytes, 4 * 16);
+    }
+
+    #[test]
+    fn untinted_batches_keep_vertex_colors_the_material_needs() {
+        let mut app = material_test_app(Sprite3dPlugin::<VertexColorMaterial>::default());
+        let material = app.world_mut().resource_mut::<Assets<VertexColorMaterial>>().add(VertexColorMaterial {});
+        app.world_mut().spawn((Sprite3d::default(), SpriteMaterial3d(material)));
+        app.update();
+        let (colors, bytes) = batch_colors(&mut app);
+        assert!(colors);
+        assert_eq!(bytes, quad_bytes(VertexColorMaterial::required_vertex_attributes(), SpriteVertexPacking::NONE));
+    }
+
+    /// Vertex hash of a deterministic batch of three sprites, the second of which has an extra component, so that
+    /// it is stored in another archetype when `named`.
+    fn deterministic_vertex_hash(budget: Option<BatchBudget>, named: bool) -> u64 {
+        let mut plugin = Sprite3dPlugin::default().with_deterministic_batching();
+        plugin.budget = budget;
+        let mut app = test_app(plugin);
+        let material = textured_material(&mut app, 16, 16);
+        let colors = [Color::WHITE, Color::srgb(1.0, 0.5, 0.0), Color::srgba(0.0, 0.25, 1.0, 0.5)];
+        for (i, color) in colors.into_iter().enumerate() {
+            let mut sprite = app.world_mut().spawn((
+                Sprite3d { color, ..default() },
+                SpriteMaterial3d(material.clone()),
+                Transform::from_xyz(i as f32, 0.5 * i as f32, -(i as f32)),
+            ));
+            if named && i == 1 {
+                sprite.insert(Name::new("Sprite"));
+            }
+        }
+        app.update();
+        app.update();
+        let meshes = app.world().resource::<Assets<Mesh>>();
+        app.world().resource::<MeshBatch<StandardMaterial>>().vertex_hash(meshes)
+    }
+
+    #[test]
+    fn deterministic_batches_write_sprites_in_entity_order() {
+        let hash = deterministic_vertex_hash(None, false);
+        assert_eq!(deterministic_vertex_hash(None, true), hash);
+        assert_eq!(deterministic_vertex_hash(Some(BatchBudget::Sprites(100)), false), hash);
+        assert_eq!(deterministic_vertex_hash(Some(BatchBudget::Sprites(100)), true), hash);
+    }
+
+    #[test]
+    fn deterministic_vertex_hash_matches_golden() {
+        // Recorded from a previous run. If vertex data changes on purpose, update it, as replays need re-recording
+        assert_eq!(deterministic_vertex_hash(None, true), 1923155911689962095);
+    }
+
+    /// 64x32 image with the given format and mip levels, with zeroed data for every level.
+    fn descriptor_image(format: TextureFormat, mip_level_count: u32, data_size: usize) -> Image {
+        let mut image = Image { data: vec![0; data_size], ..default() };
+        image.texture_descriptor.size = Extent3d { width: 64, height: 32, depth_or_array_layers: 1 };
+        image.texture_descriptor.format = format;
+        image.texture_descriptor.mip_level_count = mip_level_count;
+        image
+    }
+
+    /// Width and height of the only sprite quad batched with an image.
+    fn batched_quad_size(image: Image) -> Vec2 {
+        let mut app = test_app(Sprite3dPlugin::default());
+        let image = app.world_mut().resource_mut::<Assets<Image>>().add(image);
+        let material = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+            base_color_texture: Some(image),
+            ..default()
+        });
+        app.world_mut().spawn((Sprite3d::default(), SpriteMaterial3d(material)));
+        app.update();
+        let mut batches = app.world_mut().query_filtered::<&Mesh3d, With<Sprite3dBatch<StandardMaterial>>>();
+        let mesh = batches.single(app.world()).0.clone();
+        let mesh = app.world().resource::<Assets<Mesh>>().get(&mesh).unwrap();
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            panic!("batch has no positions");
+        };
+        let (min, max) = positions.iter().fold((Vec2::MAX, Vec2::MIN), |(min, max), &[x, y, _]| {
+            (min.min(Vec2::new(x, y)), max.max(Vec2::new(x, y)))
+        });
+        max - min
+    }
+
+    #[test]
+    fn compressed_textures_size_sprites_on_their_first_frame() {
+        // BC1 stores each 4x4 block of pixels in 8 bytes
+        let image = descriptor_image(TextureFormat::Bc1RgbaUnormSrgb, 1, (64 / 4) * (32 / 4) * 8);
+        assert_eq!(texture_size(&image), Vec2::new(64.0, 32.0));
+        assert_eq!(batched_quad_size(image), Vec2::new(64.0, 32.0));
+    }
+
+    #[test]
+    fn mipmapped_textures_size_sprites_by_their_full_resolution() {
+        let level_sizes = (0..6).map(|level| (64 >> level) * (32 >> level) * 4);
+        let image = descriptor_image(TextureFormat::Rgba8UnormSrgb, 6, level_sizes.sum());
+        assert_eq!(texture_size(&image), Vec2::new(64.0, 32.0));
+        assert_eq!(batched_quad_size(image), Vec2::new(64.0, 32.0));
+    }
+
+    #[test]
+    fn sizes_are_remembered_once_images_leave_the_main_world() {
+        let mut app = test_app(Sprite3dPlugin::default());
+        let material = textured_material(&mut app, 64, 32);
+        app.world_mut().spawn((Sprite3d::default(), SpriteMaterial3d(material.clone())));
+        app.update();
+        let image = app.world().resource::<Assets<StandardMaterial>>().get(&material).unwrap().base_color_texture.clone();
+        app.world_mut().resource_mut::<Assets<Image>>().remove(&image.unwrap());
+        app.update();
+        let mesh_batch = app.world().resource::<MeshBatch<StandardMaterial>>();
+        assert_eq!(mesh_batch.last_material_size(material.id()), Some(Vec2::new(64.0, 32.0)));
+        assert_eq!(batch_vertex_count(&mut app, &material), 4);
     }
 }
@@ -0,0 +1,107 @@
+use bevy_app::prelude::*;
+use bevy_color::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::prelude::*;
+use bevy_pbr::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::Sprite3d;
+
+/// Makes a sprite light the scene, ie: for torches, lamps and glowing projectiles.
+/// A [`PointLight`] is spawned as a child of the sprite, following it around, and kept in sync with this component
+/// and the sprite's [`color`](Sprite3d::color): its color is tinted by the sprite's, and its intensity scaled by
+/// the sprite's alpha, so fading or flashing sprites dim and tint their light along with them.
+/// The light is despawned when this component is removed, or the sprite despawned.
+/// Requires the [`Sprite3dLightPlugin`].
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dLight {
+    pub color: Color,
+    /// Luminous power of the light, in lumens, as in [`PointLight::intensity`].
+    pub intensity: f32,
+    /// Distance, in world units, past which the light has no effect.
+    pub range: f32,
+}
+
+impl Default for Sprite3dLight {
+    fn default() -> Self {
+        let light = PointLight::default();
+        Self { color: Color::WHITE, intensity: light.intensity, range: light.range }
+    }
+}
+
+impl Sprite3dLight {
+    pub fn new(color: Color, intensity: f32, range: f32) -> Self {
+        Self { color, intensity, range }
+    }
+
+    /// Color of a light emitted by a sprite with the given color.
+    fn tinted_color(&self, sprite_color: Color) -> Color {
+        let (color, tint) = (LinearRgba::from(self.color), LinearRgba::from(sprite_color));
+        LinearRgba::rgb(color.red * tint.red, color.green * tint.green, color.blue * tint.blue).into()
+    }
+}
+
+/// Marks the [`PointLight`] spawned for the parent's [`Sprite3dLight`].
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Sprite3dLightSource;
+
+/// Spawns and updates the [`PointLight`]s of sprites with a [`Sprite3dLight`].
+pub struct Sprite3dLightPlugin;
+
+impl Plugin for Sprite3dLightPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Sprite3dLight>();
+        app.add_systems(PostUpdate, update_sprite_lights.before(TransformSystem::TransformPropagate));
+        app.add_observer(despawn_sprite_lights);
+    }
+}
+
+/// Spawns the lights of sprites that gained a [`Sprite3dLight`], and updates those whose sprite or light changed.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_sprite_lights(
+    mut commands: Commands,
+    sprites: Query<(Entity, &Sprite3d, &Sprite3dLight, Option<&Children>), Or<(Changed<Sprite3d>, Changed<Sprite3dLight>)>>,
+    mut point_lights: Query<&mut PointLight, With<Sprite3dLightSource>>,
+) {
+    for (entity, sprite, light, children) in &sprites {
+        let color = light.tinted_color(sprite.color);
+        let intensity = light.intensity * sprite.color.alpha();
+        let child_light = children
+            .into_iter()
+            .flatten()
+            .find(|&&child| point_lights.contains(child));
+        match child_light {
+            Some(&child) => {
+                let mut point_light = point_lights.get_mut(child).unwrap();
+                point_light.color = color;
+                point_light.intensity = intensity;
+                point_light.range = light.range;
+            },
+            None => {
+                commands.entity(entity).with_children(|parent| {
+                    parent.spawn((
+                        PointLight { color, intensity, range: light.range, ..Default::default() },
+                        Transform::default(),
+                        Sprite3dLightSource,
+                    ));
+                });
+            },
+        }
+    }
+}
+
+// Despawns the light of sprites whose Sprite3dLight gets removed, including when the sprite is despawned.
+pub(crate) fn despawn_sprite_lights(
+    trigger: Trigger<OnRemove, Sprite3dLight>,
+    mut commands: Commands,
+    sprites: Query<&Children>,
+    point_lights: Query<(), With<Sprite3dLightSource>>,
+) {
+    let Ok(children) = sprites.get(trigger.entity()) else { return };
+    for &child in children {
+        if point_lights.contains(child) {
+            commands.entity(child).try_despawn_recursive();
+        }
+    }
+}
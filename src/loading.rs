@@ -0,0 +1,136 @@
+use bevy_asset::{prelude::*, RenderAssetUsages};
+use bevy_color::Color;
+use bevy_core::Name;
+use bevy_ecs::prelude::*;
+use bevy_image::prelude::*;
+use bevy_image::ImageSampler;
+use bevy_math::Vec2;
+use bevy_pbr::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_render::prelude::*;
+use bevy_render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_render::view::{NoFrustumCulling, RenderLayers};
+use bevy_transform::prelude::*;
+
+use crate::{
+    clear_sprite_vertices, create_sprite_mesh, fit_sprite_indices, write_sprite_quad_vertices, QuadIndices, Sprite3d,
+    SpriteQuad, SpriteVertexAttributes, VertexColorSpace,
+};
+
+/// What is rendered for sprites whose material, or its texture, isn't loaded yet.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum LoadingPolicy {
+    /// Nothing is rendered until the material is loaded.
+    #[default]
+    Skip,
+    /// A magenta and black checkered quad is rendered instead, at the sprite's custom size, or the size of its rect.
+    /// Sprites with neither are skipped. Needs the [`PbrPlugin`](bevy_pbr::PbrPlugin), to render the checker with a
+    /// [`StandardMaterial`].
+    Placeholder,
+    /// The sprite is rendered as it was in the last frame its material was loaded, ie: while switching to a frame
+    /// from a texture that is still loading. Sprites that were never rendered are skipped.
+    /// With other policies, sprites batched with a [`BatchBudget`](crate::BatchBudget) leave their batch when their
+    /// material changes to one that isn't loaded.
+    LastFrame,
+}
+
+/// Sent the first frame a sprite is rendered, ie: once its material and texture are loaded.
+/// Sprites rendered as a placeholder, see [`LoadingPolicy::Placeholder`], aren't ready yet.
+#[derive(Event, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Sprite3dReady {
+    pub entity: Entity,
+}
+
+/// Quads rendered in place of sprites that are still loading, batched per render layers.
+#[derive(Default, Debug)]
+pub(crate) struct Placeholders {
+    /// Checkered material of placeholders. Placeholders aren't rendered without it.
+    pub material: Option<Handle<StandardMaterial>>,
+    meshes: Vec<(RenderLayers, Entity, Handle<Mesh>)>,
+    quads: Vec<(RenderLayers, SpriteQuad)>,
+}
+
+impl Placeholders {
+    /// Entities of the placeholder batches.
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.meshes.iter().map(|(_, entity, _)| *entity)
+    }
+
+    /// Adds the placeholder of a sprite, if its size is known.
+    pub fn push(
+        &mut self,
+        sprite: &Sprite3d,
+        sprite_transf: &GlobalTransform,
+        render_layers: &RenderLayers,
+        color_space: VertexColorSpace,
+    ) {
+        if self.material.is_none() { return };
+        let Some(size) = sprite.custom_size.or(sprite.rect.map(|rect| rect.size())) else { return };
+        let placeholder = Sprite3d {
+            color: Color::WHITE,
+            custom_size: Some(size),
+            rect: None,
+            uv_rect: None,
+            secondary_rect: None,
+            uv_offset: Vec2::ZERO,
+            uv_scale: Vec2::ONE,
+            ..sprite.clone()
+        };
+        let quad = SpriteQuad::new(&placeholder, sprite_transf, Vec2::ONE, color_space);
+        self.quads.push((render_layers.clone(), quad));
+    }
+
+    /// Writes the placeholders added since the last call to their meshes, spawning meshes for new render layers.
+    pub fn write(&mut self, mesh_assets: &mut Assets<Mesh>, quad_indices: &mut QuadIndices, commands: &mut Commands) {
+        let Some(material) = &self.material else { return };
+        for (_, _, handle) in &self.meshes {
+            if let Some(mesh) = mesh_assets.get_mut(handle) {
+                clear_sprite_vertices(mesh);
+            }
+        }
+        for (render_layers, quad) in self.quads.drain(..) {
+            let handle = match self.meshes.iter().find(|(layers, ..)| *layers == render_layers) {
+                Some((_, _, handle)) => handle,
+                None => {
+                    let handle = mesh_assets.add(create_sprite_mesh(SpriteVertexAttributes::NONE));
+                    let entity = commands.spawn((
+                        Mesh3d(handle.clone()),
+                        MeshMaterial3d(material.clone()),
+                        render_layers.clone(),
+                        NoFrustumCulling,
+                        Name::new("Sprite3d Placeholder Batch"),
+                    )).id();
+                    self.meshes.push((render_layers, entity, handle));
+                    &self.meshes.last().unwrap().2
+                },
+            };
+            if let Some(mesh) = mesh_assets.get_mut(handle) {
+                write_sprite_quad_vertices(mesh, &quad);
+            }
+        }
+        for (_, _, handle) in &self.meshes {
+            if let Some(mesh) = mesh_assets.get_mut(handle) {
+                fit_sprite_indices(mesh, quad_indices);
+            }
+        }
+    }
+}
+
+/// Unlit material with a 2x2 magenta and black checker, for placeholders.
+pub(crate) fn placeholder_material(images: &mut Assets<Image>, materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+    const MAGENTA: [u8; 4] = [255, 0, 255, 255];
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    let mut checker = Image::new(
+        Extent3d { width: 2, height: 2, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        [MAGENTA, BLACK, BLACK, MAGENTA].concat(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    checker.sampler = ImageSampler::nearest();
+    materials.add(StandardMaterial {
+        base_color_texture: Some(images.add(checker)),
+        unlit: true,
+        ..Default::default()
+    })
+}
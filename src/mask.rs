@@ -0,0 +1,102 @@
+use bevy_app::prelude::*;
+use bevy_asset::{load_internal_asset, prelude::*};
+use bevy_ecs::prelude::*;
+use bevy_math::{Mat3, Vec3};
+use bevy_pbr::prelude::*;
+use bevy_pbr::{ExtendedMaterial, MaterialExtension};
+use bevy_reflect::prelude::*;
+use bevy_render::render_resource::{AsBindGroup, Shader, ShaderRef};
+use bevy_transform::prelude::*;
+
+use crate::{Sprite3dBounds, Sprite3dSystems, SpriteMaterialExtension};
+
+const MASK_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x2c8d_71e4_0a9f_4b36_9e5d_c317_58f2_a6b1);
+
+/// Material of sprites that only render where they are seen through the quad of a mask sprite.
+pub type MaskMaterial = ExtendedMaterial<StandardMaterial, MaskExtension>;
+
+/// Makes a sprite the mask of the sprites that use a [`MaskMaterial`], ie: minimap viewports, card frames and
+/// portals. Masked sprites only render where the line of sight from the camera crosses the mask's quad, so that
+/// sprites behind a portal are revealed through it, and those in a card frame don't spill out of it.
+/// The mask sprite itself renders as usual, and can be hidden to mask with an invisible quad.
+/// Masked fragments are discarded in the main and deferred passes only, so masked sprites with an opaque material
+/// still write depth where they are masked out. Use a blended alpha mode, or [`Sprite3dNoPrepass`](crate::Sprite3dNoPrepass).
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+#[require(Sprite3dBounds)]
+pub struct Sprite3dMask {
+    /// Material of the masked sprites, whose [`MaskExtension`] follows the quad of this sprite.
+    pub material: Handle<MaskMaterial>,
+}
+
+impl Sprite3dMask {
+    pub fn new(material: Handle<MaskMaterial>) -> Self {
+        Self { material }
+    }
+}
+
+/// Extends a material with a fragment shader that discards fragments not seen through a mask's quad.
+/// Kept up to date from a [`Sprite3dMask`] sprite, or set by hand.
+#[derive(Asset, AsBindGroup, Reflect, Clone, Default, Debug)]
+#[uniform(100, Mat3)]
+pub struct MaskExtension {
+    /// World space center of the mask's quad.
+    pub center: Vec3,
+    /// Vector from the center of the mask's quad to the middle of its right edge.
+    pub right: Vec3,
+    /// Vector from the center of the mask's quad to the middle of its top edge.
+    pub up: Vec3,
+}
+
+/// Packs the mask's quad into the uniform read by the shader, as the columns of a matrix.
+impl From<&MaskExtension> for Mat3 {
+    fn from(extension: &MaskExtension) -> Self {
+        Mat3::from_cols(extension.center, extension.right, extension.up)
+    }
+}
+
+impl MaterialExtension for MaskExtension {
+    fn fragment_shader() -> ShaderRef {
+        MASK_SHADER_HANDLE.into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        MASK_SHADER_HANDLE.into()
+    }
+}
+
+impl SpriteMaterialExtension for MaskExtension {
+    fn vertex_colors_optional() -> bool {
+        true
+    }
+}
+
+/// Registers [`MaskMaterial`] and its shader, and keeps the materials of [`Sprite3dMask`]s in line with their quads.
+/// Sprites using it are batched by adding a [`Sprite3dPlugin::<MaskMaterial>`](crate::Sprite3dPlugin) as well.
+pub struct Sprite3dMaskPlugin;
+
+impl Plugin for Sprite3dMaskPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, MASK_SHADER_HANDLE, "mask.wgsl", Shader::from_wgsl);
+        app.add_plugins(MaterialPlugin::<MaskMaterial>::default());
+        app.add_systems(PostUpdate, update_mask_materials.after(Sprite3dSystems));
+    }
+}
+
+// Moves the masks of materials to the quads of their mask sprites.
+#[allow(clippy::type_complexity)]
+fn update_mask_materials(
+    masks: Query<
+        (&Sprite3dMask, &GlobalTransform, &Sprite3dBounds),
+        Or<(Changed<Sprite3dMask>, Changed<GlobalTransform>, Changed<Sprite3dBounds>)>,
+    >,
+    mut materials: ResMut<Assets<MaskMaterial>>,
+) {
+    for (mask, transform, bounds) in &masks {
+        let Some(material) = materials.get_mut(&mask.material) else { continue };
+        let transform = bounds.plane_transform(transform);
+        let half_size = bounds.rect.half_size();
+        material.extension.center = transform.transform_point(bounds.rect.center().extend(0.0));
+        material.extension.right = transform.affine().transform_vector3(Vec3::X * half_size.x);
+        material.extension.up = transform.affine().transform_vector3(Vec3::Y * half_size.y);
+    }
+}
@@ -0,0 +1,74 @@
+#import bevy_pbr::{
+    pbr_functions::alpha_discard,
+    pbr_fragment::pbr_input_from_standard_material,
+    mesh_view_bindings::view,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+}
+#endif
+
+// World space quad of the mask, as its center, and the vectors from it to the middle of its right and top edges
+@group(2) @binding(100) var<uniform> mask: mat3x3<f32>;
+
+// True if the line of sight from the camera to a point crosses the mask's quad.
+fn is_seen_through_mask(world_position: vec3<f32>) -> bool {
+    let is_orthographic = view.clip_from_view[3].w == 1.0;
+    var origin = view.world_position;
+    var direction = world_position - view.world_position;
+    if is_orthographic {
+        direction = -view.world_from_view[2].xyz;
+        origin = world_position - direction;
+    }
+    let normal = cross(mask[1], mask[2]);
+    let facing = dot(direction, normal);
+    if abs(facing) < 1e-6 {
+        return false;
+    }
+    let distance = dot(mask[0] - origin, normal) / facing;
+    if distance <= 0.0 {
+        return false;
+    }
+    let offset = origin + direction * distance - mask[0];
+    let local = vec2(
+        dot(offset, mask[1]) / dot(mask[1], mask[1]),
+        dot(offset, mask[2]) / dot(mask[2], mask[2]),
+    );
+    return all(abs(local) <= vec2(1.0));
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    if !is_seen_through_mask(in.world_position.xyz) {
+        discard;
+    }
+
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+        out.color = apply_pbr_lighting(pbr_input);
+    } else {
+        out.color = pbr_input.material.base_color;
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+    return out;
+}
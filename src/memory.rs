@@ -0,0 +1,134 @@
+use bevy_asset::UntypedAssetId;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_utils::tracing::warn;
+use bevy_utils::{HashMap, HashSet};
+
+use crate::{SpriteVertexAttributes, SpriteVertexPacking};
+
+/// Limits on the memory taken by the vertex and index data of sprite batches, ie: for web and mobile targets
+/// with tight memory ceilings. Estimated from the number of quads and the vertex attributes of batches.
+/// Going over a limit logs a warning and sends a [`Sprite3dMemoryExceeded`] event.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct MemoryBudget {
+    /// Maximum bytes across all batches of a [`Sprite3dPlugin`](crate::Sprite3dPlugin).
+    pub global: Option<usize>,
+    /// Maximum bytes across the batches of a single material.
+    pub per_material: Option<usize>,
+    /// If true, sprites farthest from the cameras are left out of batches until the sprites fit the budget.
+    /// Otherwise, every sprite is still rendered.
+    pub drop_farthest: bool,
+}
+
+impl MemoryBudget {
+    pub fn global(bytes: usize) -> Self {
+        Self { global: Some(bytes), ..Self::default() }
+    }
+
+    pub fn per_material(bytes: usize) -> Self {
+        Self { per_material: Some(bytes), ..Self::default() }
+    }
+
+    pub fn with_drop_farthest(mut self) -> Self {
+        self.drop_farthest = true;
+        self
+    }
+}
+
+/// Sent when the sprites of a [`Sprite3dPlugin`](crate::Sprite3dPlugin) go over its [`MemoryBudget`].
+/// Sent again only after they fit the budget for a frame.
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dMemoryExceeded {
+    /// Material whose batches went over [`MemoryBudget::per_material`], or None if it's the global budget.
+    pub material: Option<UntypedAssetId>,
+    /// Estimated bytes the sprites would take.
+    pub bytes: usize,
+    pub budget: usize,
+}
+
+/// Budgets currently exceeded, so that each excess is only reported once.
+#[derive(Default, Debug)]
+pub(crate) struct MemoryBudgetState {
+    global_exceeded: bool,
+    exceeded_materials: HashSet<UntypedAssetId>,
+}
+
+/// Bytes of the indices of a quad.
+pub(crate) const QUAD_INDEX_BYTES: usize = 6 * 4;
+
+/// Estimated bytes a quad takes in a batch with the given attributes and formats, indices included.
+pub(crate) fn quad_bytes(attributes: SpriteVertexAttributes, packing: SpriteVertexPacking) -> usize {
+    let uv_bytes = if packing.uvs { 4 } else { 8 };
+    let normal_bytes = if packing.normals || packing.octahedral_normals { 4 } else { 12 };
+    let vertex_bytes = 12
+        + uv_bytes
+        + if attributes.secondary_uvs { uv_bytes } else { 0 }
+        + if attributes.normals { normal_bytes } else { 0 }
+        + if attributes.colors { if packing.colors { 4 } else { 16 } } else { 0 }
+        + if attributes.sway { 8 } else { 0 }
+        + if attributes.layers { 4 } else { 0 }
+        + if attributes.dissolve { 4 } else { 0 }
+        + if attributes.palettes { 4 } else { 0 };
+    vertex_bytes * 4 + QUAD_INDEX_BYTES
+}
+
+/// Reports the budgets the sprites exceed, and if [`MemoryBudget::drop_farthest`] is set, leaves out the sprites
+/// farthest from the cameras until the rest fit.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn fit_memory_budget<T>(
+    budget: &MemoryBudget,
+    state: &mut MemoryBudgetState,
+    quad_bytes: usize,
+    sprites: &mut Vec<T>,
+    material: impl Fn(&T) -> UntypedAssetId,
+    quad_count: impl Fn(&T) -> usize,
+    distance: impl Fn(&T) -> f32,
+    events: &mut EventWriter<Sprite3dMemoryExceeded>,
+) {
+    let mut global_bytes = 0;
+    let mut material_bytes = HashMap::<UntypedAssetId, usize>::new();
+    for sprite in sprites.iter() {
+        let bytes = quad_count(sprite) * quad_bytes;
+        global_bytes += bytes;
+        *material_bytes.entry(material(sprite)).or_default() += bytes;
+    }
+
+    // Reports new excesses
+    let global_exceeded = budget.global.is_some_and(|global| global_bytes > global);
+    if let (true, false, Some(global)) = (global_exceeded, state.global_exceeded, budget.global) {
+        warn!("Sprites take an estimated {global_bytes} bytes, over their memory budget of {global} bytes");
+        events.send(Sprite3dMemoryExceeded { material: None, bytes: global_bytes, budget: global });
+    }
+    state.global_exceeded = global_exceeded;
+    let exceeded_materials: HashSet<UntypedAssetId> = match budget.per_material {
+        Some(per_material) => material_bytes
+            .iter()
+            .filter(|(_, &bytes)| bytes > per_material)
+            .map(|(&id, _)| id)
+            .collect(),
+        None => HashSet::new(),
+    };
+    for &id in exceeded_materials.difference(&state.exceeded_materials) {
+        let (bytes, budget) = (material_bytes[&id], budget.per_material.unwrap_or_default());
+        warn!("Sprites of material {id:?} take an estimated {bytes} bytes, over their memory budget of {budget} bytes");
+        events.send(Sprite3dMemoryExceeded { material: Some(id), bytes, budget });
+    }
+    let any_exceeded = global_exceeded || !exceeded_materials.is_empty();
+    state.exceeded_materials = exceeded_materials;
+    if !budget.drop_farthest || !any_exceeded { return };
+
+    // Keeps the closest sprites that fit
+    sprites.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+    let mut global_bytes = 0;
+    let mut material_bytes = HashMap::<UntypedAssetId, usize>::new();
+    sprites.retain(|sprite| {
+        let bytes = quad_count(sprite) * quad_bytes;
+        let material_bytes = material_bytes.entry(material(sprite)).or_default();
+        let fits_global = budget.global.is_none_or(|global| global_bytes + bytes <= global);
+        let fits_material = budget.per_material.is_none_or(|per_material| *material_bytes + bytes <= per_material);
+        if !fits_global || !fits_material { return false };
+        global_bytes += bytes;
+        *material_bytes += bytes;
+        true
+    });
+}
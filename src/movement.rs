@@ -0,0 +1,185 @@
+use std::f32::consts::TAU;
+
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_math::{Rect, Vec3};
+use bevy_reflect::prelude::*;
+use bevy_render::prelude::*;
+use bevy_time::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::animation::animate_sprites;
+use crate::{Sprite3d, Sprite3dAnimation, Sprite3dClip, Sprite3dSystems};
+
+/// Velocity of a sprite, in world units per second, for the components that react to its movement.
+/// Copy it from a physics engine's own velocity component. Without it, movement is measured from the change
+/// in the sprite's [`GlobalTransform`] between frames.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Default, Debug)]
+pub struct Sprite3dVelocity(pub Vec3);
+
+/// Turns a sprite towards the direction it moves in, relative to the camera rendered first,
+/// ie: so that walking characters look where they go.
+/// Movement slower than `threshold` keeps the sprite facing the same way, so that it doesn't flicker when idle.
+/// Requires the [`Sprite3dMovementPlugin`].
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+pub struct Sprite3dFaceMovement {
+    pub mode: MovementFacing,
+    /// Minimum speed, in world units per second, that turns the sprite.
+    pub threshold: f32,
+    /// Position of the sprite during the last update, to measure its movement.
+    #[reflect(ignore)]
+    last_position: Option<Vec3>,
+}
+
+impl Sprite3dFaceMovement {
+    pub fn new(mode: MovementFacing) -> Self {
+        Self { mode, threshold: 0.1, last_position: None }
+    }
+
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+/// How a [`Sprite3dFaceMovement`] sprite turns.
+#[derive(Reflect, Clone, PartialEq, Debug)]
+pub enum MovementFacing {
+    /// Sets [`Sprite3d::flip_x`] when the sprite moves towards the left of the screen.
+    /// If `faces_left` is true, the sprite's image faces left instead, and it flips when moving right.
+    FlipX { faces_left: bool },
+    /// Sets [`Sprite3d::rect`] to the frame of the direction the sprite moves in, ie: 4 or 8 directional sprites.
+    /// Frames are evenly spaced around the sprite, starting with movement towards the camera, then turning towards
+    /// the right of the screen. Overrides the rects set by animations.
+    Directions(Vec<Rect>),
+}
+
+impl MovementFacing {
+    pub fn flip_x() -> Self {
+        Self::FlipX { faces_left: false }
+    }
+}
+
+/// Switches the clip of a sprite's [`Sprite3dAnimation`] based on how fast it moves, ie: between idle, walk and run
+/// cycles. Plays the clip of the fastest state whose `min_speed` the sprite reaches.
+/// Switches go through [`Sprite3dAnimation::play`], so they crossfade if the animation has a crossfade.
+/// Requires the [`Sprite3dMovementPlugin`].
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+pub struct Sprite3dLocomotion {
+    pub states: Vec<LocomotionState>,
+    /// Position of the sprite during the last update, to measure its movement.
+    #[reflect(ignore)]
+    last_position: Option<Vec3>,
+}
+
+impl Sprite3dLocomotion {
+    /// Locomotion playing a clip when the sprite stands still.
+    pub fn new(idle: Handle<Sprite3dClip>) -> Self {
+        Self { states: vec![LocomotionState { min_speed: 0.0, clip: idle }], last_position: None }
+    }
+
+    /// Adds a state, played from a speed in world units per second.
+    pub fn with_state(mut self, min_speed: f32, clip: Handle<Sprite3dClip>) -> Self {
+        self.states.push(LocomotionState { min_speed, clip });
+        self
+    }
+
+    /// Clip of the fastest state reached at a speed.
+    pub fn clip(&self, speed: f32) -> Option<&Handle<Sprite3dClip>> {
+        self.states
+            .iter()
+            .filter(|state| speed >= state.min_speed)
+            .max_by(|a, b| a.min_speed.total_cmp(&b.min_speed))
+            .map(|state| &state.clip)
+    }
+}
+
+/// State of a [`Sprite3dLocomotion`].
+#[derive(Reflect, Clone, PartialEq, Debug)]
+pub struct LocomotionState {
+    /// Speed, in world units per second, from which the state plays.
+    pub min_speed: f32,
+    pub clip: Handle<Sprite3dClip>,
+}
+
+/// Turns [`Sprite3dFaceMovement`] sprites to where they move, and plays the clips of [`Sprite3dLocomotion`]s.
+pub struct Sprite3dMovementPlugin;
+
+impl Plugin for Sprite3dMovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, play_locomotion.before(animate_sprites));
+        app.add_systems(PostUpdate, face_movement.after(TransformSystem::TransformPropagate).before(Sprite3dSystems));
+    }
+}
+
+/// Velocity of a sprite, from its [`Sprite3dVelocity`], or measured from its change in position.
+/// Zero until the sprite's position is known for two frames.
+pub(crate) fn sprite_velocity(
+    velocity: Option<&Sprite3dVelocity>,
+    position: Vec3,
+    last_position: Option<Vec3>,
+    time: &Time,
+) -> Vec3 {
+    match (velocity, last_position) {
+        (Some(velocity), _) => velocity.0,
+        (None, Some(last_position)) if time.delta_secs() > 0.0 => (position - last_position) / time.delta_secs(),
+        (None, _) => Vec3::ZERO,
+    }
+}
+
+pub(crate) fn face_movement(
+    mut sprites: Query<(&mut Sprite3d, &mut Sprite3dFaceMovement, &GlobalTransform, Option<&Sprite3dVelocity>)>,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    time: Res<Time>,
+) {
+    let camera = cameras
+        .iter()
+        .filter(|(_, camera)| camera.is_active)
+        .min_by_key(|(_, camera)| camera.order)
+        .map(|(transform, _)| transform);
+    let right = camera.map_or(Vec3::X, |transform| transform.right().as_vec3());
+    let towards = camera.map_or(Vec3::Z, |transform| transform.back().as_vec3());
+    let (right, towards) = (right.with_y(0.0).normalize_or_zero(), towards.with_y(0.0).normalize_or_zero());
+
+    for (mut sprite, mut facing, transform, velocity) in &mut sprites {
+        let position = transform.translation();
+        let velocity = sprite_velocity(velocity, position, facing.last_position, &time);
+        facing.last_position = Some(position);
+        if velocity.length() < facing.threshold.max(f32::EPSILON) { continue };
+        let (x, y) = (velocity.dot(right), velocity.dot(towards));
+        match &facing.mode {
+            MovementFacing::FlipX { faces_left } => {
+                if x == 0.0 { continue };
+                let flip_x = (x < 0.0) != *faces_left;
+                if sprite.flip_x != flip_x {
+                    sprite.flip_x = flip_x;
+                }
+            },
+            MovementFacing::Directions(frames) => {
+                if frames.is_empty() || (x == 0.0 && y == 0.0) { continue };
+                let angle = x.atan2(y).rem_euclid(TAU);
+                let index = (angle / TAU * frames.len() as f32).round() as usize % frames.len();
+                let rect = Some(frames[index]);
+                if sprite.rect != rect {
+                    sprite.rect = rect;
+                }
+            },
+        }
+    }
+}
+
+pub(crate) fn play_locomotion(
+    mut sprites: Query<(&mut Sprite3dAnimation, &mut Sprite3dLocomotion, &GlobalTransform, Option<&Sprite3dVelocity>)>,
+    time: Res<Time>,
+) {
+    for (mut animation, mut locomotion, transform, velocity) in &mut sprites {
+        let position = transform.translation();
+        let speed = sprite_velocity(velocity, position, locomotion.last_position, &time).length();
+        locomotion.last_position = Some(position);
+        let Some(clip) = locomotion.clip(speed) else { continue };
+        if animation.clip != *clip {
+            animation.play(clip.clone());
+        }
+    }
+}
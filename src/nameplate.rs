@@ -0,0 +1,57 @@
+use bevy_ecs::prelude::*;
+use bevy_math::{Vec2, Vec3};
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::view::{nearest_view, SpriteView};
+
+/// Places a sprite relative to a target entity, facing the nearest camera, with an offset partly in screen space,
+/// ie: for nameplates and health bars that float "24 pixels above the head" at any distance.
+/// Replaces the translation and rotation of the sprite when it is rendered, keeping its scale.
+/// Sprites whose target doesn't exist render at their own transform.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dNameplate {
+    pub target: Entity,
+    /// Offset from the target's position, in world units. Unaffected by the target's rotation.
+    pub world_offset: Vec3,
+    /// Offset applied after `world_offset`, in screen pixels of the nearest camera, with y pointing up.
+    pub screen_offset: Vec2,
+}
+
+impl Sprite3dNameplate {
+    pub fn new(target: Entity) -> Self {
+        Self { target, world_offset: Vec3::ZERO, screen_offset: Vec2::ZERO }
+    }
+
+    pub fn with_world_offset(mut self, world_offset: Vec3) -> Self {
+        self.world_offset = world_offset;
+        self
+    }
+
+    pub fn with_screen_offset(mut self, screen_offset: Vec2) -> Self {
+        self.screen_offset = screen_offset;
+        self
+    }
+
+    /// Transform of the sprite, placed relative to its target for the camera nearest to it.
+    pub(crate) fn place(
+        &self,
+        sprite_transf: &GlobalTransform,
+        target_transf: &GlobalTransform,
+        views: &[SpriteView],
+    ) -> GlobalTransform {
+        let (scale, _, _) = sprite_transf.to_scale_rotation_translation();
+        let position = target_transf.translation() + self.world_offset;
+        let Some(view) = nearest_view(views, position.into()) else {
+            return Transform::from_translation(position).with_scale(scale).into();
+        };
+        let pixel_size = view.pixel_size_at(position.into()).unwrap_or_default();
+        let screen_offset = view.rotation * self.screen_offset.extend(0.0) * pixel_size;
+
+        // Sprites are visible looking down their -Z axis, like cameras, so they face the camera with the same rotation
+        Transform::from_translation(position + screen_offset)
+            .with_rotation(view.rotation)
+            .with_scale(scale)
+            .into()
+    }
+}
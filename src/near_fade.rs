@@ -0,0 +1,66 @@
+use bevy_ecs::prelude::*;
+use bevy_math::Vec3;
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::view::{nearest_view, SpriteView};
+
+/// Fades out or shrinks a sprite as the nearest camera gets close to it, ie: so that large billboards don't fill
+/// the screen when walked through.
+/// The sprite is unaffected beyond `start`, fully faded out (or shrunk to nothing) within `end`,
+/// and in between, interpolated linearly with the distance to the camera.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dNearFade {
+    /// Distance to the camera, in world units, where the sprite starts fading.
+    pub start: f32,
+    /// Distance to the camera, in world units, where the sprite is fully faded. Should be less than `start`.
+    pub end: f32,
+    pub mode: NearFadeMode,
+}
+
+impl Sprite3dNearFade {
+    pub fn new(start: f32, end: f32) -> Self {
+        Self { start, end, mode: NearFadeMode::default() }
+    }
+
+    pub fn with_mode(mut self, mode: NearFadeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// How visible a sprite is, from 0 (faded) to 1 (unaffected), for the camera nearest to it.
+    pub(crate) fn factor(&self, sprite_transf: &GlobalTransform, views: &[SpriteView]) -> f32 {
+        let position = sprite_transf.translation_vec3a();
+        let Some(view) = nearest_view(views, position) else { return 1.0 };
+        let distance = view.position.distance(position);
+        match self.start > self.end {
+            true => ((distance - self.end) / (self.start - self.end)).clamp(0.0, 1.0),
+            false => if distance >= self.start { 1.0 } else { 0.0 },
+        }
+    }
+
+    /// Transform of the sprite, shrunk around its origin if the mode is [`NearFadeMode::Shrink`].
+    pub(crate) fn apply(&self, sprite_transf: &GlobalTransform, views: &[SpriteView]) -> GlobalTransform {
+        if self.mode != NearFadeMode::Shrink { return *sprite_transf };
+        let factor = self.factor(sprite_transf, views);
+        sprite_transf.mul_transform(Transform::from_scale(Vec3::splat(factor)))
+    }
+
+    /// Alpha the sprite's colors are multiplied by, if the mode is [`NearFadeMode::Fade`].
+    pub(crate) fn alpha(&self, sprite_transf: &GlobalTransform, views: &[SpriteView]) -> f32 {
+        match self.mode {
+            NearFadeMode::Fade => self.factor(sprite_transf, views),
+            NearFadeMode::Shrink => 1.0,
+        }
+    }
+}
+
+/// How a [`Sprite3dNearFade`] sprite disappears.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum NearFadeMode {
+    /// The sprite's alpha goes down to 0. Requires a material with a blending [`AlphaMode`](bevy_render::alpha::AlphaMode).
+    #[default]
+    Fade,
+    /// The sprite shrinks down to nothing, around its origin. Works with any material.
+    Shrink,
+}
@@ -0,0 +1,65 @@
+use bevy_asset::prelude::*;
+use bevy_math::{Vec2, Vec3A};
+use bevy_reflect::prelude::*;
+use bevy_render::mesh::MeshVertexAttribute;
+use bevy_render::render_resource::{Shader, VertexFormat};
+
+pub(crate) const PACKING_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0xd4e2_5b19_0c7a_4f86_a3e1_97b6_2c05_f84d);
+
+/// Per-vertex normal of sprite batches, octahedral-encoded, when packed with
+/// [`SpriteVertexPacking::octahedral_normals`]. Shaders decode it with `octahedral_decode`, imported from
+/// `bevy_mod_sprite3d::packing`.
+pub const ATTRIBUTE_NORMAL_OCTAHEDRAL: MeshVertexAttribute =
+    MeshVertexAttribute::new("Sprite3d_NormalOctahedral", 2_140_529_364, VertexFormat::Snorm16x2);
+
+/// Compact vertex formats of sprite batches, used with [`Sprite3dPlugin::packed_vertices`](crate::Sprite3dPlugin::packed_vertices)
+/// for the attributes the material supports. Packed attributes keep their `Mesh` ids, and the GPU converts them
+/// back to the floats shaders expect, so shaders read them unchanged, with the exception of octahedral normals.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct SpriteVertexPacking {
+    /// UVs as `Unorm16x2`, which are more precise than half floats within the texture. UVs outside of it, ie:
+    /// from [`Sprite3d::uv_scale`](crate::Sprite3d::uv_scale), are clamped to its edges.
+    pub uvs: bool,
+    /// Colors as `Unorm8x4`. Channels are clamped to 0..1, so HDR tints are lost.
+    pub colors: bool,
+    /// Normals as `Snorm8x4`, with a w of 0.
+    pub normals: bool,
+    /// Normals octahedral-encoded as `Snorm16x2`, in [`ATTRIBUTE_NORMAL_OCTAHEDRAL`] instead of
+    /// `Mesh::ATTRIBUTE_NORMAL`. Takes precedence over `normals`, and needs a shader that decodes them.
+    pub octahedral_normals: bool,
+}
+
+impl SpriteVertexPacking {
+    pub const NONE: Self = Self { uvs: false, colors: false, normals: false, octahedral_normals: false };
+}
+
+/// The formats any shader reads unchanged.
+impl Default for SpriteVertexPacking {
+    fn default() -> Self {
+        Self { uvs: true, colors: true, normals: true, octahedral_normals: false }
+    }
+}
+
+pub(crate) fn pack_unorm16(uv: [f32; 2]) -> [u16; 2] {
+    uv.map(|value| (value.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16)
+}
+
+pub(crate) fn pack_unorm8(color: [f32; 4]) -> [u8; 4] {
+    color.map(|value| (value.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8)
+}
+
+pub(crate) fn pack_snorm8(normal: Vec3A) -> [i8; 4] {
+    let [x, y, z] = normal.to_array().map(|value| (value.clamp(-1.0, 1.0) * i8::MAX as f32).round() as i8);
+    [x, y, z, 0]
+}
+
+/// Octahedral encoding of a unit vector, as a point of the square from -1 to 1.
+pub(crate) fn pack_octahedral(normal: Vec3A) -> [i16; 2] {
+    let normal = normal / (normal.x.abs() + normal.y.abs() + normal.z.abs());
+    let mut encoded = Vec2::new(normal.x, normal.y);
+    if normal.z < 0.0 {
+        let folded = Vec2::ONE - Vec2::new(encoded.y.abs(), encoded.x.abs());
+        encoded = folded * Vec2::new(encoded.x.signum(), encoded.y.signum());
+    }
+    encoded.to_array().map(|value| (value.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16)
+}
@@ -0,0 +1,10 @@
+#define_import_path bevy_mod_sprite3d::packing
+
+// Unit vector from its octahedral encoding, see ATTRIBUTE_NORMAL_OCTAHEDRAL
+fn octahedral_decode(encoded: vec2<f32>) -> vec3<f32> {
+    var normal = vec3(encoded, 1.0 - abs(encoded.x) - abs(encoded.y));
+    let fold = max(-normal.z, 0.0);
+    normal.x += select(fold, -fold, normal.x >= 0.0);
+    normal.y += select(fold, -fold, normal.y >= 0.0);
+    return normalize(normal);
+}
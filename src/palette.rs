@@ -0,0 +1,105 @@
+use bevy_app::prelude::*;
+use bevy_asset::{load_internal_asset, prelude::*};
+use bevy_ecs::prelude::*;
+use bevy_image::prelude::*;
+use bevy_pbr::prelude::*;
+use bevy_pbr::{ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline};
+use bevy_reflect::prelude::*;
+use bevy_render::mesh::{MeshVertexAttribute, MeshVertexBufferLayoutRef};
+use bevy_render::prelude::*;
+use bevy_render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, Shader, ShaderRef, SpecializedMeshPipelineError, VertexFormat,
+};
+
+use crate::{SpriteMaterialExtension, SpriteVertexAttributes};
+
+const PALETTE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x93b0_4f6e_27c1_4d58_b8a3_5e0f_c41d_72a9);
+
+/// Per-vertex palette row of sprite batches.
+/// Written from [`Sprite3dPalette`] when the material requires [`SpriteVertexAttributes::palettes`].
+pub const ATTRIBUTE_PALETTE: MeshVertexAttribute =
+    MeshVertexAttribute::new("Sprite3d_Palette", 2_140_529_365, VertexFormat::Uint32);
+
+/// Row of the [`PaletteExtension::palettes`] texture a sprite is colored with, ie: its team or character variant.
+/// Written per vertex, so that sprites with different palettes still share a material and a batch.
+/// Only has an effect on sprites rendered with a [`PaletteMaterial`]. Defaults to the first row.
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+pub struct Sprite3dPalette(pub u32);
+
+/// Material of sprites whose texture holds palette indices rather than colors.
+pub type PaletteMaterial = ExtendedMaterial<StandardMaterial, PaletteExtension>;
+
+/// Extends a material with a shader that reads the red channel of its base color texture as a palette index,
+/// from 0 to 255, and colors the pixel with that column of the sprite's [`Sprite3dPalette`] row.
+/// The alpha of the base color texture is kept, and the palette color is multiplied by the material's base color
+/// and the sprite's color as usual.
+/// Indices must be read as is: load the base color texture with `is_srgb: false`, and sample it with a nearest
+/// filter, so that neighbouring indices don't blend.
+/// The palette is applied in the main pass only, and isn't supported with deferred rendering.
+#[derive(Asset, AsBindGroup, Reflect, Clone, Default, Debug)]
+pub struct PaletteExtension {
+    /// Palettes, one per row, with a color per index. Rows past the last repeat it, as do indices past the last
+    /// column.
+    #[texture(100)]
+    pub palettes: Handle<Image>,
+}
+
+impl PaletteExtension {
+    pub fn new(palettes: Handle<Image>) -> Self {
+        Self { palettes }
+    }
+}
+
+impl MaterialExtension for PaletteExtension {
+    fn vertex_shader() -> ShaderRef {
+        PALETTE_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        PALETTE_SHADER_HANDLE.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // Prepass and shadow pipelines use their own vertex shader, which doesn't read the palette attribute
+        if descriptor.vertex.shader != PALETTE_SHADER_HANDLE {
+            return Ok(());
+        }
+        let optional_attributes = [
+            Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+            Mesh::ATTRIBUTE_UV_0.at_shader_location(2),
+            Mesh::ATTRIBUTE_UV_1.at_shader_location(3),
+            Mesh::ATTRIBUTE_COLOR.at_shader_location(5),
+        ];
+        let mut attributes = vec![Mesh::ATTRIBUTE_POSITION.at_shader_location(0)];
+        attributes.extend(optional_attributes.into_iter().filter(|attribute| layout.0.contains(attribute.id)));
+        attributes.push(ATTRIBUTE_PALETTE.at_shader_location(11));
+        descriptor.vertex.buffers = vec![layout.0.get_layout(&attributes)?];
+        Ok(())
+    }
+}
+
+impl SpriteMaterialExtension for PaletteExtension {
+    fn required_vertex_attributes() -> SpriteVertexAttributes {
+        SpriteVertexAttributes { palettes: true, ..SpriteVertexAttributes::NONE }
+    }
+
+    fn vertex_colors_optional() -> bool {
+        true
+    }
+}
+
+/// Registers [`PaletteMaterial`] and its shader.
+/// Sprites using it are batched by adding a [`Sprite3dPlugin::<PaletteMaterial>`](crate::Sprite3dPlugin) as well.
+pub struct Sprite3dPalettePlugin;
+
+impl Plugin for Sprite3dPalettePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, PALETTE_SHADER_HANDLE, "palette.wgsl", Shader::from_wgsl);
+        app.add_plugins(MaterialPlugin::<PaletteMaterial>::default());
+    }
+}
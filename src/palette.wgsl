@@ -0,0 +1,140 @@
+#import bevy_pbr::{
+    mesh_functions,
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_bindings,
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+    view_transformations::position_world_to_clip,
+}
+
+// Palettes, one per row, with a color per index
+@group(2) @binding(100) var palettes: texture_2d<f32>;
+
+// Same as bevy_pbr::forward_io::Vertex, plus the palette attribute.
+// Sprite batches have no tangents, and are never skinned nor morphed.
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+#ifdef VERTEX_NORMALS
+    @location(1) normal: vec3<f32>,
+#endif
+#ifdef VERTEX_UVS_A
+    @location(2) uv: vec2<f32>,
+#endif
+#ifdef VERTEX_UVS_B
+    @location(3) uv_b: vec2<f32>,
+#endif
+#ifdef VERTEX_COLORS
+    @location(5) color: vec4<f32>,
+#endif
+    @location(11) palette: u32,
+};
+
+// Same as bevy_pbr::forward_io::VertexOutput, plus the palette row.
+struct PaletteVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+#ifdef VERTEX_UVS_A
+    @location(2) uv: vec2<f32>,
+#endif
+#ifdef VERTEX_UVS_B
+    @location(3) uv_b: vec2<f32>,
+#endif
+#ifdef VERTEX_COLORS
+    @location(5) color: vec4<f32>,
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    @location(6) @interpolate(flat) instance_index: u32,
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    @location(7) @interpolate(flat) visibility_range_dither: i32,
+#endif
+    @location(8) @interpolate(flat) palette: u32,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> PaletteVertexOutput {
+    var out: PaletteVertexOutput;
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+
+#ifdef VERTEX_NORMALS
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(vertex.normal, vertex.instance_index);
+#endif
+
+    out.world_position = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0));
+    out.position = position_world_to_clip(out.world_position.xyz);
+
+#ifdef VERTEX_UVS_A
+    out.uv = vertex.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    out.uv_b = vertex.uv_b;
+#endif
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = mesh_functions::get_visibility_range_dither_level(
+        vertex.instance_index, world_from_local[3]);
+#endif
+    out.palette = vertex.palette;
+
+    return out;
+}
+
+@fragment
+fn fragment(
+    palette_in: PaletteVertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var in: VertexOutput;
+    in.position = palette_in.position;
+    in.world_position = palette_in.world_position;
+    in.world_normal = palette_in.world_normal;
+#ifdef VERTEX_UVS_A
+    in.uv = palette_in.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    in.uv_b = palette_in.uv_b;
+#endif
+#ifdef VERTEX_COLORS
+    in.color = palette_in.color;
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    in.instance_index = palette_in.instance_index;
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    in.visibility_range_dither = palette_in.visibility_range_dither;
+#endif
+
+    // The texture holds palette indices in its red channel, which pick a color in the sprite's palette row
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    var index_sample = vec4(1.0);
+#ifdef VERTEX_UVS_A
+    index_sample = textureSample(pbr_bindings::base_color_texture, pbr_bindings::base_color_sampler, in.uv);
+#endif
+    let size = textureDimensions(palettes);
+    let index = min(u32(round(index_sample.r * 255.0)), size.x - 1u);
+    let row = min(palette_in.palette, size.y - 1u);
+    var color = textureLoad(palettes, vec2(index, row), 0) * pbr_bindings::material.base_color;
+    color.a *= index_sample.a;
+#ifdef VERTEX_COLORS
+    color *= in.color;
+#endif
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, color);
+
+    var out: FragmentOutput;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+        out.color = apply_pbr_lighting(pbr_input);
+    } else {
+        out.color = pbr_input.material.base_color;
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+
+    return out;
+}
@@ -0,0 +1,29 @@
+use bevy_ecs::prelude::*;
+use bevy_math::Vec3;
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::view::{first_view, SpriteView};
+
+/// Offsets a sprite by a fraction of the camera's position, ie: for multi-layer parallax backdrops.
+/// With a factor of 0 on an axis, the sprite stays in place, like regular sprites. With a factor of 1, it moves
+/// along with the camera, appearing infinitely far. Factors in between make distant layers scroll slower.
+/// Follows the camera rendered first, with the lowest [`Camera::order`](bevy_render::camera::Camera::order).
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Default, Debug)]
+pub struct ParallaxSprite3d {
+    /// Fraction of the camera's movement the sprite follows, per axis.
+    pub factor: Vec3,
+}
+
+impl ParallaxSprite3d {
+    pub fn new(factor: Vec3) -> Self {
+        Self { factor }
+    }
+
+    /// Transform of the sprite, offset by the camera's position.
+    pub(crate) fn apply(&self, sprite_transf: &GlobalTransform, views: &[SpriteView]) -> GlobalTransform {
+        let Some(view) = first_view(views) else { return *sprite_transf };
+        let offset = Vec3::from(view.position) * self.factor;
+        Transform::from_translation(offset).mul_transform(sprite_transf.compute_transform()).into()
+    }
+}
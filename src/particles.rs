@@ -0,0 +1,104 @@
+use bevy_color::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_math::{Quat, Rect, Vec2, Vec3, Vec3A};
+use bevy_reflect::prelude::*;
+use bevy_render::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::{MeshBatch, SizedMaterial, Sprite3d, Sprite3dQueue, SpriteMaterial3d};
+
+/// Particles simulated outside of the ECS, ie: by a custom simulation, or read back from a GPU one, rendered as
+/// sprites. Each frame, the particles of entities with this component and a [`SpriteMaterial3d`] are drawn through
+/// the [`Sprite3dQueue`] of that material, so they are batched along with regular sprites.
+/// Particles are positioned relative to the entity's transform, and drawn back to front from the nearest camera.
+/// Overwrite `particles` whenever the simulation steps, reusing its allocation.
+#[derive(Component, Reflect, Clone, PartialEq, Default, Debug)]
+#[require(Transform, Visibility)]
+pub struct Sprite3dParticles {
+    pub particles: Vec<SpriteParticle>,
+    /// If true, particles face the nearest camera. Otherwise, they face the entity's forward axis.
+    pub billboard: bool,
+}
+
+/// A single particle of [`Sprite3dParticles`].
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct SpriteParticle {
+    pub position: Vec3,
+    pub size: Vec2,
+    pub color: Color,
+    /// Rotation around the particle's facing axis, in radians.
+    pub rotation: f32,
+    /// Region of the texture to render, in pixels, ie: the current frame of an animated particle.
+    /// If None, renders the whole texture.
+    pub rect: Option<Rect>,
+}
+
+impl Sprite3dParticles {
+    pub fn new(particles: Vec<SpriteParticle>) -> Self {
+        Self { particles, billboard: true }
+    }
+
+    pub fn without_billboard(mut self) -> Self {
+        self.billboard = false;
+        self
+    }
+}
+
+impl SpriteParticle {
+    pub fn new(position: Vec3, size: Vec2, color: Color) -> Self {
+        Self { position, size, color, rotation: 0.0, rect: None }
+    }
+}
+
+// Queues the particles of visible particle entities, sorted back to front.
+pub(crate) fn queue_sprite_particles<M: SizedMaterial>(
+    particle_systems: Query<(&Sprite3dParticles, &SpriteMaterial3d<M>, &GlobalTransform, &InheritedVisibility)>,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    mut queue: ResMut<Sprite3dQueue<M>>,
+    mesh_batch: Res<MeshBatch<M>>,
+    mut order: Local<Vec<(usize, f32)>>,
+) {
+    for (particles, material, transform, visibility) in &particle_systems {
+        if !visibility.get() || particles.particles.is_empty() { continue };
+        let origin = transform.translation_vec3a();
+        let camera = cameras
+            .iter()
+            .filter(|(_, camera)| camera.is_active)
+            .map(|(camera_transf, _)| camera_transf)
+            .min_by(|a, b| {
+                let a = a.translation_vec3a().distance_squared(origin);
+                let b = b.translation_vec3a().distance_squared(origin);
+                a.total_cmp(&b)
+            });
+        let camera_position = camera.map_or(Vec3A::ZERO, GlobalTransform::translation_vec3a);
+        let up_rotation = mesh_batch.up_axis.rotation();
+        let facing = match (particles.billboard, camera) {
+            (true, Some(camera)) => camera.compute_transform().rotation,
+            _ => transform.compute_transform().rotation * up_rotation,
+        };
+        let scale = transform.compute_transform().scale;
+
+        order.clear();
+        order.extend(particles.particles.iter().enumerate().map(|(i, particle)| {
+            let position = transform.transform_point(particle.position);
+            (i, Vec3A::from(position).distance_squared(camera_position))
+        }));
+        order.sort_unstable_by(|(_, a), (_, b)| b.total_cmp(a));
+        for &(i, _) in order.iter() {
+            let particle = &particles.particles[i];
+            let sprite = Sprite3d {
+                color: particle.color,
+                custom_size: Some(particle.size),
+                rect: particle.rect,
+                ..Default::default()
+            };
+            let particle_transf = Transform {
+                translation: transform.transform_point(particle.position),
+                // Queued sprites get turned to stand along the up axis, which is undone to keep the facing
+                rotation: facing * Quat::from_rotation_z(particle.rotation) * up_rotation.inverse(),
+                scale,
+            };
+            queue.draw(sprite, particle_transf, material.0.clone());
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use bevy_ecs::prelude::*;
+use bevy_math::cubic_splines::CubicCurve;
+use bevy_math::{Mat3, Quat, Vec3};
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::view::{nearest_view, SpriteView};
+use crate::UpAxis;
+
+/// Number of samples per curve segment used to measure distances along a [`SpritePath3d`].
+const SAMPLES_PER_SEGMENT: usize = 32;
+
+/// Repeats a sprite along a curve, ie: for ropes, chains, footprints and road markings.
+/// Instead of a single quad at the entity's position, the [`Sprite3d`](crate::Sprite3d) is drawn at evenly spaced
+/// points along the curve, which is in the entity's local space.
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+pub struct SpritePath3d {
+    pub curve: CubicCurve<Vec3>,
+    /// Distance between consecutive sprites along the curve, in local units.
+    pub spacing: f32,
+    pub alignment: PathAlignment,
+}
+
+impl SpritePath3d {
+    pub fn new(curve: CubicCurve<Vec3>, spacing: f32) -> Self {
+        Self { curve, spacing, alignment: PathAlignment::default() }
+    }
+
+    pub fn with_alignment(mut self, alignment: PathAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Local positions of the sprites along the curve, along with the direction of the curve at each.
+    fn points(&self) -> Vec<(Vec3, Vec3)> {
+        let segment_count = self.curve.segments().len();
+        let sample_count = segment_count * SAMPLES_PER_SEGMENT;
+        let t_step = segment_count as f32 / sample_count as f32;
+        let mut points = Vec::new();
+        if self.spacing <= 0.0 { return points };
+
+        // Walks the curve in small steps, emitting a point every time the distance travelled reaches the spacing
+        let mut prev = self.curve.position(0.0);
+        let mut travelled = 0.0;
+        let mut next_distance = 0.0;
+        points.push((prev, self.curve.velocity(0.0)));
+        for i in 1..=sample_count {
+            let t = i as f32 * t_step;
+            let position = self.curve.position(t);
+            let step = position.distance(prev);
+            while step > 0.0 && travelled + step >= next_distance + self.spacing {
+                next_distance += self.spacing;
+                let fraction = (next_distance - travelled) / step;
+                let point_t = t - t_step * (1.0 - fraction);
+                points.push((prev.lerp(position, fraction), self.curve.velocity(point_t)));
+            }
+            travelled += step;
+            prev = position;
+        }
+        points
+    }
+
+    /// Transforms of the sprites along the curve, given the transform of the entity, already turned to stand
+    /// along `up_axis`. The curve stays in the entity's own space.
+    pub(crate) fn transforms(&self, sprite_transf: &GlobalTransform, views: &[SpriteView], up_axis: UpAxis) -> Vec<GlobalTransform> {
+        let (scale, _, _) = sprite_transf.to_scale_rotation_translation();
+        let to_sprite = up_axis.rotation().inverse();
+        self.points()
+            .into_iter()
+            .map(|(position, direction)| {
+                let local_rotation = match self.alignment {
+                    PathAlignment::Fixed | PathAlignment::Billboard => Quat::IDENTITY,
+                    PathAlignment::Tangent => tangent_rotation(to_sprite * direction),
+                };
+                let local_transf = Transform::from_translation(to_sprite * position).with_rotation(local_rotation);
+                let transf = sprite_transf.mul_transform(local_transf);
+                if self.alignment != PathAlignment::Billboard { return transf };
+                let world_position = transf.translation_vec3a();
+                let Some(nearest_view) = nearest_view(views, world_position) else { return transf };
+                let camera_position = nearest_view.position;
+                // Sprites are visible looking down their -Z axis, so -Z points away from the camera
+                let away = Vec3::from(world_position - camera_position);
+                let billboard = Transform::from_translation(world_position.into())
+                    .looking_to(away, up_axis.up())
+                    .with_scale(scale);
+                billboard.into()
+            })
+            .collect()
+    }
+}
+
+/// Orientation of the sprites along a [`SpritePath3d`].
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum PathAlignment {
+    /// Sprites keep the rotation of the entity.
+    #[default]
+    Fixed,
+    /// Sprites turn so that their x axis follows the curve.
+    Tangent,
+    /// Sprites face the nearest camera.
+    Billboard,
+}
+
+/// Rotation that turns the x axis towards a direction, keeping the z axis as close to its original direction as possible.
+fn tangent_rotation(direction: Vec3) -> Quat {
+    let x = direction.normalize_or_zero();
+    let y = Vec3::Z.cross(x).normalize_or_zero();
+    if x == Vec3::ZERO || y == Vec3::ZERO {
+        return Quat::IDENTITY;
+    }
+    Quat::from_mat3(&Mat3::from_cols(x, y, x.cross(y)))
+}
@@ -0,0 +1,157 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_gizmos::config::GizmoConfigStore;
+use bevy_gizmos::prelude::*;
+use bevy_math::{Ray3d, Vec3, Vec3A};
+use bevy_picking::backend::prelude::*;
+use bevy_picking::focus::PickingInteraction;
+use bevy_render::prelude::*;
+use bevy_render::view::RenderLayers;
+use bevy_transform::prelude::*;
+
+use crate::render_transform::SpriteRenderTransform;
+use crate::{Sprite3d, Sprite3dBounds, Sprite3dHighlight, Sprite3dSystems};
+
+/// Picking backend for sprites, so that they receive `bevy_picking` pointer events, ie: `Pointer<Click>` observers.
+/// Sprites are picked within their [`Sprite3dBounds`], which must be added to them, transparent pixels included,
+/// where they were last rendered, ie: facing the camera for billboards.
+/// Also keeps [`Sprite3dHighlight::hovered`] up to date, and draws the outlines of highlights with gizmos.
+/// Requires the `picking` feature, and bevy's `PickingPlugin` (part of `DefaultPlugins`).
+pub struct Sprite3dPickingPlugin;
+
+impl Plugin for Sprite3dPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_required_components::<Sprite3dHighlight, PickingInteraction>();
+        app.add_systems(PreUpdate, pick_sprites.in_set(PickSet::Backend));
+        app.add_systems(PreUpdate, hover_highlights.after(PickSet::Focus));
+    }
+
+    fn finish(&self, app: &mut App) {
+        if app.world().contains_resource::<GizmoConfigStore>() {
+            app.add_systems(PostUpdate, draw_highlight_outlines.after(Sprite3dSystems));
+        }
+    }
+}
+
+// Reports the sprites under each pointer, nearest first.
+#[allow(clippy::type_complexity)]
+fn pick_sprites(
+    ray_map: Res<RayMap>,
+    cameras: Query<(&Camera, Option<&RenderLayers>)>,
+    sprites: Query<(Entity, &SpriteRenderTransform, &Sprite3dBounds, &ViewVisibility, Option<&RenderLayers>), With<Sprite3d>>,
+    mut hits: EventWriter<PointerHits>,
+) {
+    let default_layers = RenderLayers::default();
+    for (&ray_id, ray) in ray_map.iter() {
+        let Ok((camera, camera_layers)) = cameras.get(ray_id.camera) else { continue };
+        if !camera.is_active { continue };
+        let camera_layers = camera_layers.unwrap_or(&default_layers);
+        let mut picks: Vec<(Entity, HitData)> = sprites
+            .iter()
+            .filter(|(_, _, bounds, visibility, layers)| {
+                visibility.get() && !bounds.rect.is_empty() && camera_layers.intersects(layers.unwrap_or(&default_layers))
+            })
+            .filter_map(|(entity, render_transform, bounds, ..)| {
+                let (distance, position, normal) = ray_hit(*ray, &render_transform.transform, bounds)?;
+                Some((entity, HitData::new(ray_id.camera, distance, Some(position), Some(normal))))
+            })
+            .collect();
+        if picks.is_empty() { continue };
+        picks.sort_unstable_by(|(_, a), (_, b)| a.depth.total_cmp(&b.depth));
+        hits.send(PointerHits::new(ray_id.pointer, picks, camera.order as f32));
+    }
+}
+
+/// Distance along a ray, position and normal where it hits the bounds of a sprite rendered with a transform.
+fn ray_hit(ray: Ray3d, transform: &GlobalTransform, bounds: &Sprite3dBounds) -> Option<(f32, Vec3, Vec3)> {
+    let affine = transform.affine();
+    let normal = Vec3::from(affine.matrix3.z_axis);
+    let facing = normal.dot(*ray.direction);
+    if facing.abs() < f32::EPSILON { return None };
+    let distance = normal.dot(Vec3::from(affine.translation) - ray.origin) / facing;
+    if distance < 0.0 { return None };
+    let position = ray.get_point(distance);
+    let local_position = affine.inverse().transform_point3a(Vec3A::from(position));
+    if !bounds.rect.contains(local_position.truncate()) { return None };
+    Some((distance, position, normal.normalize() * -facing.signum()))
+}
+
+fn hover_highlights(mut highlights: Query<(&mut Sprite3dHighlight, &PickingInteraction), Changed<PickingInteraction>>) {
+    for (mut highlight, interaction) in &mut highlights {
+        highlight.hovered = *interaction != PickingInteraction::None;
+    }
+}
+
+// Outlines sprites where they are rendered, pulse included.
+fn draw_highlight_outlines(
+    highlights: Query<(&Sprite3dHighlight, &SpriteRenderTransform, &Sprite3dBounds, &ViewVisibility)>,
+    mut gizmos: Gizmos,
+) {
+    for (highlight, render_transform, bounds, visibility) in &highlights {
+        let Some(outline) = highlight.outline.filter(|_| highlight.is_shown() && visibility.get()) else { continue };
+        let transform = render_transform.transform;
+        let rect = bounds.rect;
+        let corners = [rect.min, rect.min.with_y(rect.max.y), rect.max, rect.max.with_y(rect.min.y), rect.min];
+        gizmos.linestrip(corners.map(|corner| transform.transform_point(corner.extend(0.0))), outline);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::render_transform::SpriteRenderTransform;
+    use crate::test_utils::{test_app, textured_material};
+    use crate::*;
+
+    use super::ray_hit;
+
+    #[test]
+    fn billboards_are_picked_facing_the_camera() {
+        let mut app = test_app(Sprite3dPlugin::default());
+        let material = textured_material(&mut app, 2, 2);
+        // Seen edge-on from the camera, until it turns to face it
+        let camera_position = Vec3::new(10.0, 0.0, 0.0);
+        let camera_transform = Transform::from_translation(camera_position).looking_at(Vec3::ZERO, Vec3::Y);
+        app.world_mut().spawn((Camera::default(), camera_transform));
+        let sprite = app.world_mut().spawn((
+            Sprite3d::default(),
+            SpriteMaterial3d(material),
+            Sprite3dBillboard::default(),
+            Sprite3dBounds::default(),
+        )).id();
+        app.update();
+
+        let ray = Ray3d::new(camera_position + Vec3::Y * 0.5, Dir3::NEG_X);
+        let sprite = app.world().entity(sprite);
+        let bounds = sprite.get::<Sprite3dBounds>().unwrap();
+        assert!(ray_hit(ray, sprite.get::<GlobalTransform>().unwrap(), bounds).is_none());
+        let render_transform = sprite.get::<SpriteRenderTransform>().unwrap();
+        let (distance, position, normal) = ray_hit(ray, &render_transform.transform, bounds).unwrap();
+        assert!((distance - 10.0).abs() < 1e-4);
+        assert!(position.abs_diff_eq(Vec3::new(0.0, 0.5, 0.0), 1e-4));
+        assert!(normal.abs_diff_eq(Vec3::X, 1e-4));
+    }
+
+    #[test]
+    fn z_up_sprites_are_picked_standing_along_z() {
+        let mut app = test_app(Sprite3dPlugin::default().with_up_axis(UpAxis::Z));
+        let material = textured_material(&mut app, 2, 2);
+        let sprite = app.world_mut().spawn((Sprite3d::default(), SpriteMaterial3d(material), Sprite3dBounds::default())).id();
+        app.update();
+
+        // Standing in the XZ plane, facing -Y
+        let ray = Ray3d::new(Vec3::new(0.0, -10.0, 0.5), Dir3::Y);
+        let sprite = app.world().entity(sprite);
+        let bounds = sprite.get::<Sprite3dBounds>().unwrap();
+        assert_eq!(bounds.rotation, UpAxis::Z.rotation());
+        let render_transform = sprite.get::<SpriteRenderTransform>().unwrap();
+        let (distance, position, normal) = ray_hit(ray, &render_transform.transform, bounds).unwrap();
+        assert!((distance - 10.0).abs() < 1e-4);
+        assert!(position.abs_diff_eq(Vec3::new(0.0, 0.0, 0.5), 1e-4));
+        assert!(normal.abs_diff_eq(Vec3::NEG_Y, 1e-4));
+        let plane = bounds.plane_transform(sprite.get::<GlobalTransform>().unwrap());
+        let (plane_distance, ..) = ray_hit(ray, &plane, bounds).unwrap();
+        assert!((plane_distance - distance).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,115 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_render::camera::{Camera, CameraUpdateSystem, OrthographicProjection, Projection, ScalingMode};
+
+/// Scales an orthographic camera so that sprites render pixel-perfect, ie: for 2.5D pixel-art games.
+/// The camera is zoomed so that each texel of a sprite covers the same whole number of screen pixels, showing at
+/// least `target_height` texels vertically, and is kept that way as the window is resized.
+/// Sprites are one world unit per texel by default, see `texels_per_unit` for sprites scaled otherwise.
+/// The camera's position isn't snapped to the texel grid, which may be needed to keep moving scenes from shimmering.
+/// Requires the [`Sprite3dPixelCameraPlugin`].
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dPixelCamera {
+    /// Vertical resolution of the game, in texels.
+    pub target_height: u32,
+    /// Texels of a sprite per world unit.
+    pub texels_per_unit: f32,
+    /// If true, the zoom is rounded down to a whole number of screen pixels per texel, showing more than
+    /// `target_height` texels when the viewport isn't a multiple of it. Otherwise, exactly `target_height` texels
+    /// are shown, at the cost of uneven texels.
+    pub integer_scaling: bool,
+    /// Screen pixels per texel, computed from the viewport's physical size.
+    /// 0 until the viewport size is known.
+    zoom: f32,
+}
+
+impl Sprite3dPixelCamera {
+    pub fn new(target_height: u32) -> Self {
+        Self { target_height, texels_per_unit: 1.0, integer_scaling: true, zoom: 0.0 }
+    }
+
+    pub fn with_texels_per_unit(mut self, texels_per_unit: f32) -> Self {
+        self.texels_per_unit = texels_per_unit;
+        self
+    }
+
+    pub fn without_integer_scaling(mut self) -> Self {
+        self.integer_scaling = false;
+        self
+    }
+
+    /// Screen pixels per texel of a sprite.
+    /// 0 until the camera's viewport size is known.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Screen pixels per world unit, ie: for [`Sprite3dScreenScale`](crate::Sprite3dScreenScale) sprites and UI
+    /// placed over the scene. 0 until the camera's viewport size is known.
+    pub fn pixels_per_unit(&self) -> f32 {
+        self.zoom * self.texels_per_unit
+    }
+
+    /// Scale of an [`OrthographicProjection`] with [`ScalingMode::WindowSize`] for the zoom, given the physical
+    /// and logical height of the viewport.
+    fn projection_scale(&self, physical_height: f32, logical_height: f32) -> f32 {
+        let world_height = physical_height / self.pixels_per_unit();
+        world_height / logical_height
+    }
+}
+
+/// Scales the projection of [`Sprite3dPixelCamera`]s.
+pub struct Sprite3dPixelCameraPlugin;
+
+impl Plugin for Sprite3dPixelCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Sprite3dPixelCamera>();
+        app.add_systems(PostUpdate, scale_pixel_cameras.before(CameraUpdateSystem));
+    }
+}
+
+/// Updates the zoom and orthographic projection of pixel cameras, whenever their viewport gets resized.
+pub(crate) fn scale_pixel_cameras(
+    mut cameras: Query<(&Camera, &mut Sprite3dPixelCamera, Option<&mut Projection>, Option<&mut OrthographicProjection>)>,
+) {
+    for (camera, mut pixel_camera, projection, orthographic) in &mut cameras {
+        let (Some(physical_size), Some(logical_size)) = (camera.physical_viewport_size(), camera.logical_viewport_size()) else {
+            continue;
+        };
+        if physical_size.y == 0 || logical_size.y <= 0.0 || pixel_camera.target_height == 0 { continue };
+        let zoom = physical_size.y as f32 / pixel_camera.target_height as f32;
+        let zoom = match pixel_camera.integer_scaling {
+            true => zoom.floor().max(1.0),
+            false => zoom,
+        };
+        if pixel_camera.zoom != zoom {
+            pixel_camera.zoom = zoom;
+        }
+        if pixel_camera.texels_per_unit <= 0.0 { continue };
+        let scale = pixel_camera.projection_scale(physical_size.y as f32, logical_size.y);
+        match (projection, orthographic) {
+            (Some(mut projection), _) => {
+                let Projection::Orthographic(orthographic) = projection.bypass_change_detection() else { continue };
+                if scale_projection(orthographic, scale) {
+                    projection.set_changed();
+                }
+            },
+            (None, Some(mut orthographic)) => {
+                if scale_projection(orthographic.bypass_change_detection(), scale) {
+                    orthographic.set_changed();
+                }
+            },
+            (None, None) => {},
+        }
+    }
+}
+
+/// Sets the scale of a projection, and makes it follow the window size. Returns true if the projection changed.
+fn scale_projection(orthographic: &mut OrthographicProjection, scale: f32) -> bool {
+    let window_sized = matches!(orthographic.scaling_mode, ScalingMode::WindowSize);
+    if window_sized && orthographic.scale == scale { return false };
+    orthographic.scaling_mode = ScalingMode::WindowSize;
+    orthographic.scale = scale;
+    true
+}
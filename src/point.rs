@@ -0,0 +1,510 @@
+use bevy_app::prelude::*;
+use bevy_asset::{load_internal_asset, prelude::*};
+use bevy_color::LinearRgba;
+use bevy_core_pipeline::core_3d::Transparent3d;
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::lifetimeless::{Read, SRes};
+use bevy_ecs::system::SystemParamItem;
+use bevy_image::prelude::*;
+use bevy_math::prelude::*;
+use bevy_math::{Rect, Vec2, Vec3};
+use bevy_pbr::{MeshPipeline, MeshPipelineKey, RenderMeshInstances, SetMeshBindGroup, SetMeshViewBindGroup};
+use bevy_render::mesh::allocator::MeshAllocator;
+use bevy_render::mesh::{MeshVertexBufferLayoutRef, RenderMesh, RenderMeshBufferInfo};
+use bevy_render::prelude::*;
+use bevy_render::render_asset::RenderAssets;
+use bevy_render::render_phase::{
+    AddRenderCommand, DrawFunctions, PhaseItem, PhaseItemExtraIndex, RenderCommand, RenderCommandResult,
+    SetItemPipeline, TrackedRenderPass, ViewSortedRenderPhases,
+};
+use bevy_render::render_resource::binding_types::{sampler, texture_2d};
+use bevy_render::render_resource::*;
+use bevy_render::renderer::{RenderAdapter, RenderDevice, RenderQueue};
+use bevy_render::sync_component::SyncComponentPlugin;
+use bevy_render::sync_world::{MainEntity, RenderEntity};
+use bevy_render::texture::GpuImage;
+use bevy_render::view::{ExtractedView, NoFrustumCulling};
+use bevy_render::{Extract, ExtractSchedule, Render, RenderApp, RenderSet};
+use bevy_sprite::Anchor;
+use bevy_transform::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use crate::gpu_points::ExtractedGpuPoints;
+use crate::point_culling::{PointCulledBuffers, PointCullingPlugin};
+use crate::Sprite3dGpuPointBatch;
+
+const POINT_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x7f13_9b2e_c4d8_4a06_9e5b_31f7_d2a8_6c40);
+
+/// A sprite of a [`Sprite3dPointBatch`], facing the camera.
+/// Uploaded as-is, as a single instance, and expanded into a quad on the GPU.
+#[derive(Copy, Clone, PartialEq, Pod, Zeroable, Debug)]
+#[repr(C)]
+pub struct PointSprite {
+    /// World position of the sprite's anchor.
+    pub position: Vec3,
+    /// Rotation of the sprite around the camera's view direction, in radians.
+    pub rotation: f32,
+    pub color: LinearRgba,
+    /// Top left corner of the area of the texture displayed, in normalized coordinates.
+    pub uv_min: Vec2,
+    /// Bottom right corner of the area of the texture displayed, in normalized coordinates.
+    pub uv_max: Vec2,
+    /// World size of the sprite.
+    pub size: Vec2,
+    /// Point of the sprite placed at its position, from -0.5 to 0.5, ie: [`Anchor::as_vec`].
+    pub anchor: Vec2,
+}
+
+impl PointSprite {
+    pub fn new(position: Vec3, size: Vec2) -> Self {
+        Self {
+            position,
+            rotation: 0.0,
+            color: LinearRgba::WHITE,
+            uv_min: Vec2::ZERO,
+            uv_max: Vec2::ONE,
+            size,
+            anchor: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_color(mut self, color: impl Into<LinearRgba>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    /// Displays an area of the texture, in normalized coordinates.
+    pub fn with_uv_rect(mut self, uv_rect: Rect) -> Self {
+        self.uv_min = uv_rect.min;
+        self.uv_max = uv_rect.max;
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor.as_vec();
+        self
+    }
+}
+
+/// Sprites rendered with a lighter pipeline, where each sprite is a single instance expanded into a quad on the GPU,
+/// rather than four vertices written by the CPU, ie: for particle-like workloads with many thousands of sprites.
+/// Points always face the camera, are unlit, and blend with what's behind them. They aren't sorted, and ignore the
+/// entity's transform and [`RenderLayers`](bevy_render::view::RenderLayers). The entity can be hidden with
+/// its [`Visibility`], but isn't frustum culled.
+/// Requires the [`Sprite3dPointPlugin`].
+#[derive(Component, Clone, Debug)]
+#[require(Transform, Visibility)]
+pub struct Sprite3dPointBatch {
+    pub texture: Handle<Image>,
+    pub points: Vec<PointSprite>,
+}
+
+impl Sprite3dPointBatch {
+    pub fn new(texture: Handle<Image>) -> Self {
+        Self { texture, points: Vec::new() }
+    }
+}
+
+/// Renders [`Sprite3dPointBatch`]es.
+pub struct Sprite3dPointPlugin;
+
+impl Plugin for Sprite3dPointPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, POINT_SHADER_HANDLE, "point.wgsl", Shader::from_wgsl);
+        app.add_plugins(SyncComponentPlugin::<Sprite3dPointBatch>::default());
+        app.init_resource::<PointQuad>();
+        app.add_systems(PostUpdate, add_point_quads);
+        app.add_plugins(PointCullingPlugin);
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else { return };
+        render_app
+            .add_render_command::<Transparent3d, DrawPoints>()
+            .init_resource::<SpecializedMeshPipelines<PointPipeline>>()
+            .add_systems(ExtractSchedule, extract_points)
+            .add_systems(
+                Render,
+                (
+                    queue_points.in_set(RenderSet::QueueMeshes),
+                    prepare_point_buffers.in_set(RenderSet::PrepareResources),
+                    prepare_point_indirect_args.in_set(RenderSet::PrepareBindGroups),
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else { return };
+        render_app.init_resource::<PointPipeline>();
+    }
+}
+
+/// Unit quad expanded for each point.
+#[derive(Resource)]
+struct PointQuad(Handle<Mesh>);
+
+impl FromWorld for PointQuad {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource_mut::<Assets<Mesh>>().add(Rectangle::new(1.0, 1.0)))
+    }
+}
+
+// Gives point batches the quad they draw instances of. Frustum culling is skipped, as the quad's bounds don't
+// cover the points.
+#[allow(clippy::type_complexity)]
+fn add_point_quads(
+    mut commands: Commands,
+    point_quad: Res<PointQuad>,
+    batches: Query<Entity, (Or<(With<Sprite3dPointBatch>, With<Sprite3dGpuPointBatch>)>, Without<Mesh3d>)>,
+) {
+    for entity in &batches {
+        commands.entity(entity).insert((Mesh3d(point_quad.0.clone()), NoFrustumCulling));
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn queue_points(
+    transparent_3d_draw_functions: Res<DrawFunctions<Transparent3d>>,
+    point_pipeline: Res<PointPipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<PointPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    meshes: Res<RenderAssets<RenderMesh>>,
+    render_mesh_instances: Res<RenderMeshInstances>,
+    batches: Query<
+        (Entity, &MainEntity, Option<&ExtractedPoints>, Option<&ExtractedGpuPoints>),
+        Or<(With<ExtractedPoints>, With<ExtractedGpuPoints>)>,
+    >,
+    mut transparent_render_phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(Entity, &ExtractedView, &Msaa)>,
+) {
+    let draw_points = transparent_3d_draw_functions.read().id::<DrawPoints>();
+    for (view_entity, view, msaa) in &views {
+        let Some(transparent_phase) = transparent_render_phases.get_mut(&view_entity) else { continue };
+        let view_key = MeshPipelineKey::from_msaa_samples(msaa.samples())
+            | MeshPipelineKey::from_hdr(view.hdr)
+            | MeshPipelineKey::BLEND_ALPHA;
+        let rangefinder = view.rangefinder3d();
+        for (entity, main_entity, points, gpu_points) in &batches {
+            if points.is_some_and(|points| !points.visible || points.length == 0) { continue };
+            if gpu_points.is_some_and(|gpu_points| !gpu_points.visible) { continue };
+            let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(*main_entity) else { continue };
+            let Some(mesh) = meshes.get(mesh_instance.mesh_asset_id) else { continue };
+            let key = view_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology());
+            let Ok(pipeline) = pipelines.specialize(&pipeline_cache, &point_pipeline, key, &mesh.layout) else {
+                continue;
+            };
+            transparent_phase.add(Transparent3d {
+                entity: (entity, *main_entity),
+                pipeline,
+                draw_function: draw_points,
+                distance: rangefinder.distance_translation(&mesh_instance.translation),
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::NONE,
+            });
+        }
+    }
+}
+
+/// A [`Sprite3dPointBatch`] in the render world. Its points are only extracted when they changed, or weren't
+/// uploaded yet.
+#[derive(Component)]
+struct ExtractedPoints {
+    texture: AssetId<Image>,
+    visible: bool,
+    length: usize,
+    points: Option<Vec<PointSprite>>,
+}
+
+fn extract_points(
+    mut commands: Commands,
+    batches: Extract<Query<(RenderEntity, &ViewVisibility, Ref<Sprite3dPointBatch>)>>,
+    uploaded: Query<(), With<PointBuffers>>,
+) {
+    for (render_entity, view_visibility, batch) in &batches {
+        let uploaded = !batch.is_changed() && uploaded.contains(render_entity);
+        commands.entity(render_entity).insert(ExtractedPoints {
+            texture: batch.texture.id(),
+            visible: view_visibility.get(),
+            length: batch.points.len(),
+            points: (!uploaded).then(|| batch.points.clone()),
+        });
+    }
+}
+
+/// GPU resources of a point batch.
+#[derive(Component)]
+pub(crate) struct PointBuffers {
+    pub instances: Buffer,
+    pub length: usize,
+    pub texture_bind_group: BindGroup,
+    /// View the bind group was created for, so that it's only recreated when the texture changes.
+    pub texture_view: TextureViewId,
+}
+
+impl PointBuffers {
+    /// Writes points to the instance buffer, replacing it with one twice as large when they don't fit.
+    fn write(&mut self, points: &[PointSprite], render_device: &RenderDevice, render_queue: &RenderQueue) {
+        let size = size_of_val(points) as u64;
+        if size > self.instances.size() {
+            self.instances = point_instances_buffer(render_device, size);
+        }
+        if !points.is_empty() {
+            render_queue.write_buffer(&self.instances, 0, bytemuck::cast_slice(points));
+        }
+        self.length = points.len();
+    }
+}
+
+fn point_instances_buffer(render_device: &RenderDevice, size: u64) -> Buffer {
+    render_device.create_buffer(&BufferDescriptor {
+        label: Some("sprite3d_point_instances"),
+        size: size.next_power_of_two(),
+        usage: BufferUsages::VERTEX | BufferUsages::STORAGE | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+// Uploads the points that changed to the instance buffers of their batches, which are kept from frame to frame and
+// only grow. Empty batches don't get buffers until they have points.
+fn prepare_point_buffers(
+    mut commands: Commands,
+    mut batches: Query<(Entity, &mut ExtractedPoints, Option<&mut PointBuffers>)>,
+    point_pipeline: Res<PointPipeline>,
+    images: Res<RenderAssets<GpuImage>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    for (entity, mut extracted, buffers) in &mut batches {
+        let Some(image) = images.get(extracted.texture) else {
+            commands.entity(entity).remove::<PointBuffers>();
+            continue;
+        };
+        let texture_bind_group = || {
+            render_device.create_bind_group(
+                "sprite3d_point_texture",
+                &point_pipeline.texture_layout,
+                &BindGroupEntries::sequential((&image.texture_view, &image.sampler)),
+            )
+        };
+        let points = extracted.points.take();
+        match buffers {
+            Some(mut buffers) => {
+                if let Some(points) = points {
+                    buffers.write(&points, &render_device, &render_queue);
+                }
+                if buffers.texture_view != image.texture_view.id() {
+                    buffers.texture_bind_group = texture_bind_group();
+                    buffers.texture_view = image.texture_view.id();
+                }
+            },
+            None => {
+                let Some(points) = points.filter(|points| !points.is_empty()) else { continue };
+                let mut buffers = PointBuffers {
+                    instances: point_instances_buffer(&render_device, size_of_val(points.as_slice()) as u64),
+                    length: 0,
+                    texture_bind_group: texture_bind_group(),
+                    texture_view: image.texture_view.id(),
+                };
+                buffers.write(&points, &render_device, &render_queue);
+                commands.entity(entity).insert(buffers);
+            },
+        }
+    }
+}
+
+/// Indirect draw arguments of a point batch, written every frame, so that its instance count can change without
+/// changing the draw call. Only used when the GPU supports indirect draws.
+#[derive(Component)]
+pub(crate) struct PointIndirectArgs(pub Buffer);
+
+// Writes the draw arguments of point batches to their indirect buffers, creating them on first use.
+#[allow(clippy::too_many_arguments)]
+fn prepare_point_indirect_args(
+    mut commands: Commands,
+    batches: Query<(Entity, &MainEntity, &PointBuffers, Option<&PointIndirectArgs>)>,
+    point_pipeline: Res<PointPipeline>,
+    meshes: Res<RenderAssets<RenderMesh>>,
+    render_mesh_instances: Res<RenderMeshInstances>,
+    mesh_allocator: Res<MeshAllocator>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    if !point_pipeline.indirect { return };
+    for (entity, main_entity, buffers, indirect_args) in &batches {
+        let instance_count = buffers.length as u32;
+        let args = point_draw_args(*main_entity, instance_count, &meshes, &render_mesh_instances, &mesh_allocator);
+        let Some(args) = args else { continue };
+        match indirect_args {
+            Some(PointIndirectArgs(buffer)) if buffer.size() == args.len() as u64 => {
+                render_queue.write_buffer(buffer, 0, &args);
+            },
+            _ => {
+                let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                    label: Some("sprite3d_point_indirect_args"),
+                    contents: &args,
+                    usage: BufferUsages::INDIRECT | BufferUsages::STORAGE | BufferUsages::COPY_DST,
+                });
+                commands.entity(entity).insert(PointIndirectArgs(buffer));
+            },
+        }
+    }
+}
+
+/// Indirect draw arguments of the quad of a point batch, as bytes. None if its mesh isn't ready.
+pub(crate) fn point_draw_args(
+    main_entity: MainEntity,
+    instance_count: u32,
+    meshes: &RenderAssets<RenderMesh>,
+    render_mesh_instances: &RenderMeshInstances,
+    mesh_allocator: &MeshAllocator,
+) -> Option<Vec<u8>> {
+    let mesh_instance = render_mesh_instances.render_mesh_queue_data(main_entity)?;
+    let gpu_mesh = meshes.get(mesh_instance.mesh_asset_id)?;
+    let vertex_slice = mesh_allocator.mesh_vertex_slice(&mesh_instance.mesh_asset_id)?;
+    let args = match &gpu_mesh.buffer_info {
+        RenderMeshBufferInfo::Indexed { count, .. } => {
+            let index_slice = mesh_allocator.mesh_index_slice(&mesh_instance.mesh_asset_id)?;
+            DrawIndexedIndirectArgs {
+                index_count: *count,
+                instance_count,
+                first_index: index_slice.range.start,
+                base_vertex: vertex_slice.range.start as i32,
+                first_instance: 0,
+            }.as_bytes().to_vec()
+        },
+        RenderMeshBufferInfo::NonIndexed => DrawIndirectArgs {
+            vertex_count: vertex_slice.range.len() as u32,
+            instance_count,
+            first_vertex: vertex_slice.range.start,
+            first_instance: 0,
+        }.as_bytes().to_vec(),
+    };
+    Some(args)
+}
+
+#[derive(Resource)]
+pub(crate) struct PointPipeline {
+    mesh_pipeline: MeshPipeline,
+    pub texture_layout: BindGroupLayout,
+    /// If true, batches are drawn with [`PointIndirectArgs`]. Unsupported on WebGL 2.
+    pub indirect: bool,
+}
+
+impl FromWorld for PointPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let texture_layout = world.resource::<RenderDevice>().create_bind_group_layout(
+            "sprite3d_point_texture_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                ),
+            ),
+        );
+        let indirect = world
+            .resource::<RenderAdapter>()
+            .get_downlevel_capabilities()
+            .flags
+            .contains(DownlevelFlags::INDIRECT_EXECUTION);
+        Self { mesh_pipeline: world.resource::<MeshPipeline>().clone(), texture_layout, indirect }
+    }
+}
+
+impl SpecializedMeshPipeline for PointPipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+        descriptor.label = Some("sprite3d_point_pipeline".into());
+        descriptor.vertex.shader = POINT_SHADER_HANDLE;
+        // Locations 0 to 7 are left to the mesh attributes the mesh pipeline may bind
+        descriptor.vertex.buffers.push(VertexBufferLayout {
+            array_stride: size_of::<PointSprite>() as u64,
+            step_mode: VertexStepMode::Instance,
+            attributes: (0..4)
+                .map(|i| VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: i * VertexFormat::Float32x4.size(),
+                    shader_location: 8 + i as u32,
+                })
+                .collect(),
+        });
+        descriptor.layout.push(self.texture_layout.clone());
+        descriptor.fragment.as_mut().unwrap().shader = POINT_SHADER_HANDLE;
+        Ok(descriptor)
+    }
+}
+
+type DrawPoints = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshBindGroup<1>,
+    DrawPointInstances,
+);
+
+struct DrawPointInstances;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawPointInstances {
+    type Param = (SRes<RenderAssets<RenderMesh>>, SRes<RenderMeshInstances>, SRes<MeshAllocator>);
+    type ViewQuery = Entity;
+    type ItemQuery = (Read<PointBuffers>, Option<Read<PointIndirectArgs>>, Option<Read<PointCulledBuffers>>);
+
+    fn render<'w>(
+        item: &P,
+        view: Entity,
+        buffers: Option<(&'w PointBuffers, Option<&'w PointIndirectArgs>, Option<&'w PointCulledBuffers>)>,
+        (meshes, render_mesh_instances, mesh_allocator): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let mesh_allocator = mesh_allocator.into_inner();
+        let Some((buffers, indirect_args, culled)) = buffers.filter(|(buffers, ..)| buffers.length > 0) else {
+            return RenderCommandResult::Skip;
+        };
+        // Culled batches draw the points that passed culling for this view, counted on the GPU
+        let (instance_buffer, indirect_args) = match culled.and_then(|culled| culled.views.get(&view)) {
+            Some(culled_view) => (&culled_view.instances, Some(&culled_view.args)),
+            None => (&buffers.instances, indirect_args.map(|PointIndirectArgs(buffer)| buffer)),
+        };
+        let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(item.main_entity()) else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(gpu_mesh) = meshes.into_inner().get(mesh_instance.mesh_asset_id) else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(vertex_slice) = mesh_allocator.mesh_vertex_slice(&mesh_instance.mesh_asset_id) else {
+            return RenderCommandResult::Skip;
+        };
+        pass.set_bind_group(2, &buffers.texture_bind_group, &[]);
+        pass.set_vertex_buffer(0, vertex_slice.buffer.slice(..));
+        pass.set_vertex_buffer(1, instance_buffer.slice(..));
+        let instances = 0..buffers.length as u32;
+        match &gpu_mesh.buffer_info {
+            RenderMeshBufferInfo::Indexed { index_format, count } => {
+                let Some(index_slice) = mesh_allocator.mesh_index_slice(&mesh_instance.mesh_asset_id) else {
+                    return RenderCommandResult::Skip;
+                };
+                pass.set_index_buffer(index_slice.buffer.slice(..), 0, *index_format);
+                match indirect_args {
+                    Some(buffer) => pass.draw_indexed_indirect(buffer, 0),
+                    None => pass.draw_indexed(
+                        index_slice.range.start..(index_slice.range.start + count),
+                        vertex_slice.range.start as i32,
+                        instances,
+                    ),
+                }
+            },
+            RenderMeshBufferInfo::NonIndexed => match indirect_args {
+                Some(buffer) => pass.draw_indirect(buffer, 0),
+                None => pass.draw(vertex_slice.range, instances),
+            },
+        }
+        RenderCommandResult::Success
+    }
+}
@@ -0,0 +1,45 @@
+#import bevy_pbr::mesh_view_bindings::view
+
+@group(2) @binding(0) var point_texture: texture_2d<f32>;
+@group(2) @binding(1) var point_sampler: sampler;
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(8) i_position_rotation: vec4<f32>,
+    @location(9) i_color: vec4<f32>,
+    @location(10) i_uv_rect: vec4<f32>,
+    @location(11) i_size_anchor: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+// Expands the unit quad into a quad facing the camera, for each point.
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let size = vertex.i_size_anchor.xy;
+    let anchor = vertex.i_size_anchor.zw;
+    let corner = (vertex.position.xy - anchor) * size;
+    let rotation = vertex.i_position_rotation.w;
+    let c = cos(rotation);
+    let s = sin(rotation);
+    let rotated = vec2(corner.x * c - corner.y * s, corner.x * s + corner.y * c);
+    let right = view.world_from_view[0].xyz;
+    let up = view.world_from_view[1].xyz;
+    let world_position = vertex.i_position_rotation.xyz + right * rotated.x + up * rotated.y;
+
+    var out: VertexOutput;
+    out.clip_position = view.clip_from_world * vec4(world_position, 1.0);
+    out.uv = mix(vertex.i_uv_rect.xy, vertex.i_uv_rect.zw, vertex.uv);
+    out.color = vertex.i_color;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(point_texture, point_sampler, in.uv) * in.color;
+}
@@ -0,0 +1,41 @@
+struct PointSprite {
+    position_rotation: vec4<f32>,
+    color: vec4<f32>,
+    uv_rect: vec4<f32>,
+    size_anchor: vec4<f32>,
+};
+
+struct CullParams {
+    // Half spaces of the view frustum, with the points inside them having positive distances.
+    planes: array<vec4<f32>, 6>,
+    // Position of the view, and the distance beyond which points are culled, or a negative value if there's none.
+    view_position_max_distance: vec4<f32>,
+};
+
+@group(0) @binding(0) var<storage, read> points: array<PointSprite>;
+@group(0) @binding(1) var<storage, read_write> culled: array<PointSprite>;
+// Indirect draw arguments, with the instance count second, for both indexed and non-indexed draws.
+@group(0) @binding(2) var<storage, read_write> draw_args: array<atomic<u32>>;
+@group(0) @binding(3) var<uniform> params: CullParams;
+
+@compute @workgroup_size(64)
+fn cull_points(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= arrayLength(&points) {
+        return;
+    }
+    let point = points[id.x];
+    let position = point.position_rotation.xyz;
+    // Bounding sphere around the point's anchor, covering any rotation
+    let radius = length(point.size_anchor.xy * (abs(point.size_anchor.zw) + 0.5));
+    for (var i = 0u; i < 6u; i++) {
+        if dot(params.planes[i], vec4(position, 1.0)) + radius <= 0.0 {
+            return;
+        }
+    }
+    let max_distance = params.view_position_max_distance.w;
+    if max_distance >= 0.0 && distance(position, params.view_position_max_distance.xyz) - radius > max_distance {
+        return;
+    }
+    let slot = atomicAdd(&draw_args[1], 1u);
+    culled[slot] = point;
+}
@@ -0,0 +1,266 @@
+use bevy_app::prelude::*;
+use bevy_asset::{load_internal_asset, prelude::*};
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryItem;
+use bevy_math::Vec4;
+use bevy_pbr::RenderMeshInstances;
+use bevy_reflect::prelude::*;
+use bevy_render::extract_component::{ExtractComponent, ExtractComponentPlugin};
+use bevy_render::graph::CameraDriverLabel;
+use bevy_render::mesh::allocator::MeshAllocator;
+use bevy_render::mesh::RenderMesh;
+use bevy_render::primitives::Frustum;
+use bevy_render::render_asset::RenderAssets;
+use bevy_render::render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel};
+use bevy_render::render_resource::binding_types::{
+    storage_buffer_read_only_sized, storage_buffer_sized, uniform_buffer_sized,
+};
+use bevy_render::render_resource::*;
+use bevy_render::renderer::{RenderContext, RenderDevice, RenderQueue};
+use bevy_render::sync_world::MainEntity;
+use bevy_render::view::ExtractedView;
+use bevy_render::{Render, RenderApp, RenderSet};
+use bevy_utils::HashMap;
+use bytemuck::{Pod, Zeroable};
+
+use crate::point::{point_draw_args, PointBuffers, PointPipeline};
+use crate::PointSprite;
+
+const POINT_CULL_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0xb3e6_0d4f_92a1_47c8_a5d7_6e18_f03c_2b94);
+
+/// Points culled per compute workgroup.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Culls the points of a [`Sprite3dPointBatch`](crate::Sprite3dPointBatch) or a
+/// [`Sprite3dGpuPointBatch`](crate::Sprite3dGpuPointBatch) on the GPU, for each camera, ie: for batches too large
+/// to cull on the CPU.
+/// A compute pass tests the bounds of every point against the camera's frustum, and optionally its distance,
+/// and compacts the points that pass into a separate buffer, drawn with an instance count written by the GPU.
+/// Needs indirect draws, so it has no effect on WebGL 2.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Default, Debug)]
+pub struct Sprite3dPointCulling {
+    /// If set, points farther than this from the camera are culled as well.
+    pub max_distance: Option<f32>,
+}
+
+impl Sprite3dPointCulling {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_distance(mut self, max_distance: f32) -> Self {
+        self.max_distance = Some(max_distance);
+        self
+    }
+}
+
+impl ExtractComponent for Sprite3dPointCulling {
+    type QueryData = &'static Sprite3dPointCulling;
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self> {
+        Some(*item)
+    }
+}
+
+/// Adds the culling pass of point batches. Added by the [`Sprite3dPointPlugin`](crate::Sprite3dPointPlugin).
+pub(crate) struct PointCullingPlugin;
+
+impl Plugin for PointCullingPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, POINT_CULL_SHADER_HANDLE, "point_cull.wgsl", Shader::from_wgsl);
+        app.add_plugins(ExtractComponentPlugin::<Sprite3dPointCulling>::default());
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else { return };
+        render_app
+            .init_resource::<PointCullDispatches>()
+            .add_systems(Render, prepare_point_culling.in_set(RenderSet::PrepareBindGroups));
+        let mut render_graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        render_graph.add_node(PointCullLabel, PointCullNode);
+        render_graph.add_node_edge(PointCullLabel, CameraDriverLabel);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else { return };
+        render_app.init_resource::<PointCullPipeline>();
+    }
+}
+
+/// Points of a batch that passed culling for each view, and their indirect draw arguments, keyed by view.
+#[derive(Component, Default)]
+pub(crate) struct PointCulledBuffers {
+    pub views: HashMap<Entity, CulledView>,
+}
+
+pub(crate) struct CulledView {
+    pub instances: Buffer,
+    pub args: Buffer,
+    params: Buffer,
+}
+
+/// GPU layout of the parameters of `point_cull.wgsl`.
+#[derive(Copy, Clone, Pod, Zeroable)]
+#[repr(C)]
+struct GpuCullParams {
+    planes: [Vec4; 6],
+    view_position_max_distance: Vec4,
+}
+
+/// Bind groups of the batches to cull this frame, for each view, and how many points each has.
+#[derive(Resource, Default)]
+struct PointCullDispatches(Vec<(BindGroup, u32)>);
+
+// Resizes the culling buffers of batches to their points, resets their instance counts, and binds them
+// for each view.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn prepare_point_culling(
+    mut commands: Commands,
+    mut batches: Query<(Entity, &MainEntity, &PointBuffers, &Sprite3dPointCulling, Option<&mut PointCulledBuffers>)>,
+    views: Query<(Entity, &ExtractedView, &Frustum)>,
+    mut dispatches: ResMut<PointCullDispatches>,
+    cull_pipeline: Res<PointCullPipeline>,
+    point_pipeline: Res<PointPipeline>,
+    (meshes, render_mesh_instances, mesh_allocator): (
+        Res<RenderAssets<RenderMesh>>,
+        Res<RenderMeshInstances>,
+        Res<MeshAllocator>,
+    ),
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    dispatches.0.clear();
+    if !point_pipeline.indirect { return };
+    for (entity, main_entity, buffers, culling, culled) in &mut batches {
+        let Some(args) = point_draw_args(*main_entity, 0, &meshes, &render_mesh_instances, &mesh_allocator) else {
+            continue;
+        };
+        let mut inserted = None;
+        let culled = match culled {
+            Some(culled) => culled.into_inner(),
+            None => inserted.insert(PointCulledBuffers::default()),
+        };
+        let instances_size = (buffers.length.max(1) * size_of::<PointSprite>()) as u64;
+        culled.views.retain(|view, _| views.contains(*view));
+        for (view, extracted_view, frustum) in &views {
+            let params = GpuCullParams {
+                planes: frustum.half_spaces.map(|half_space| half_space.normal_d()),
+                view_position_max_distance: extracted_view
+                    .world_from_view
+                    .translation()
+                    .extend(culling.max_distance.unwrap_or(-1.0)),
+            };
+            let culled_view = culled.views.entry(view).or_insert_with(|| CulledView {
+                instances: culled_instances_buffer(&render_device, instances_size),
+                args: render_device.create_buffer_with_data(&BufferInitDescriptor {
+                    label: Some("sprite3d_point_culled_args"),
+                    contents: &args,
+                    usage: BufferUsages::INDIRECT | BufferUsages::STORAGE | BufferUsages::COPY_DST,
+                }),
+                params: render_device.create_buffer(&BufferDescriptor {
+                    label: Some("sprite3d_point_cull_params"),
+                    size: size_of::<GpuCullParams>() as u64,
+                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+            });
+            if culled_view.instances.size() != instances_size {
+                culled_view.instances = culled_instances_buffer(&render_device, instances_size);
+            }
+            render_queue.write_buffer(&culled_view.args, 0, &args);
+            render_queue.write_buffer(&culled_view.params, 0, bytemuck::bytes_of(&params));
+            if buffers.length == 0 { continue };
+            let bind_group = render_device.create_bind_group(
+                "sprite3d_point_cull",
+                &cull_pipeline.layout,
+                &BindGroupEntries::sequential((
+                    // Instance buffers may be larger than their points, which the shader counts from the binding
+                    BufferBinding {
+                        buffer: &buffers.instances,
+                        offset: 0,
+                        size: BufferSize::new((buffers.length * size_of::<PointSprite>()) as u64),
+                    },
+                    culled_view.instances.as_entire_binding(),
+                    culled_view.args.as_entire_binding(),
+                    culled_view.params.as_entire_binding(),
+                )),
+            );
+            dispatches.0.push((bind_group, buffers.length as u32));
+        }
+        if let Some(inserted) = inserted {
+            commands.entity(entity).insert(inserted);
+        }
+    }
+}
+
+fn culled_instances_buffer(render_device: &RenderDevice, size: u64) -> Buffer {
+    render_device.create_buffer(&BufferDescriptor {
+        label: Some("sprite3d_point_culled_instances"),
+        size,
+        usage: BufferUsages::VERTEX | BufferUsages::STORAGE,
+        mapped_at_creation: false,
+    })
+}
+
+#[derive(Resource)]
+struct PointCullPipeline {
+    layout: BindGroupLayout,
+    pipeline: CachedComputePipelineId,
+}
+
+impl FromWorld for PointCullPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let layout = world.resource::<RenderDevice>().create_bind_group_layout(
+            "sprite3d_point_cull_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    storage_buffer_read_only_sized(false, None),
+                    storage_buffer_sized(false, None),
+                    storage_buffer_sized(false, None),
+                    uniform_buffer_sized(false, None),
+                ),
+            ),
+        );
+        let pipeline = world.resource::<PipelineCache>().queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some("sprite3d_point_cull_pipeline".into()),
+            layout: vec![layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader: POINT_CULL_SHADER_HANDLE,
+            shader_defs: Vec::new(),
+            entry_point: "cull_points".into(),
+            zero_initialize_workgroup_memory: false,
+        });
+        Self { layout, pipeline }
+    }
+}
+
+#[derive(RenderLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PointCullLabel;
+
+/// Culls the points of every culled batch, for every view, once per frame before cameras render.
+/// Until the pipeline is compiled, culled batches draw nothing.
+struct PointCullNode;
+
+impl Node for PointCullNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let dispatches = world.resource::<PointCullDispatches>();
+        if dispatches.0.is_empty() { return Ok(()) };
+        let pipeline_id = world.resource::<PointCullPipeline>().pipeline;
+        let Some(pipeline) = world.resource::<PipelineCache>().get_compute_pipeline(pipeline_id) else { return Ok(()) };
+        let mut pass = render_context.command_encoder().begin_compute_pass(&ComputePassDescriptor {
+            label: Some("sprite3d_point_cull"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(pipeline);
+        for (bind_group, point_count) in &dispatches.0 {
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.dispatch_workgroups(point_count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+struct PointSprite {
+    position_rotation: vec4<f32>,
+    color: vec4<f32>,
+    uv_rect: vec4<f32>,
+    size_anchor: vec4<f32>,
+};
+
+struct PointDelta {
+    translation: vec3<f32>,
+    rotation: f32,
+    scale: f32,
+    index: u32,
+    flags: u32,
+    uv_min: vec2<f32>,
+    uv_max: vec2<f32>,
+};
+
+const DELTA_SETS_UV_RECT: u32 = 1u;
+
+@group(0) @binding(0) var<storage, read_write> points: array<PointSprite>;
+@group(0) @binding(1) var<storage, read> deltas: array<PointDelta>;
+
+// Applies one delta per invocation. Deltas are merged per point beforehand, so no two invocations write the same point.
+@compute @workgroup_size(64)
+fn apply_deltas(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= arrayLength(&deltas) {
+        return;
+    }
+    let delta = deltas[id.x];
+    if delta.index >= arrayLength(&points) {
+        return;
+    }
+    var point = points[delta.index];
+    point.position_rotation += vec4(delta.translation, delta.rotation);
+    point.size_anchor = vec4(point.size_anchor.xy * delta.scale, point.size_anchor.zw);
+    if (delta.flags & DELTA_SETS_UV_RECT) != 0u {
+        point.uv_rect = vec4(delta.uv_min, delta.uv_max);
+    }
+    points[delta.index] = point;
+}
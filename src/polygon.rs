@@ -0,0 +1,200 @@
+use bevy_asset::prelude::*;
+use bevy_color::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_image::prelude::*;
+use bevy_math::{Rect, UVec2, Vec2};
+use bevy_reflect::prelude::*;
+use bevy_utils::HashMap;
+
+use crate::{SizedMaterial, Sprite3d, SpriteMaterial3d, SpriteQuad};
+
+/// Renders a sprite as a convex polygon instead of a quad, ie: trimmed around the opaque pixels of its texture,
+/// to reduce overdraw in foliage-heavy scenes.
+/// Points are normalized over the region of the texture the sprite renders, from (0, 0) at the top left to (1, 1)
+/// at the bottom right, like UVs. They follow the sprite's flips, and can be listed in either winding order.
+/// Polygons with less than 3 points render nothing.
+#[derive(Component, Reflect, Clone, PartialEq, Default, Debug)]
+pub struct Sprite3dPolygon {
+    pub points: Vec<Vec2>,
+}
+
+impl Sprite3dPolygon {
+    pub fn new(points: Vec<Vec2>) -> Self {
+        Self { points }
+    }
+
+    /// Convex hull of the pixels of an image with an alpha above a threshold.
+    /// Only considers the pixels within `rect`, if set, and normalizes the points over it.
+    /// None if no pixel is opaque enough, or if the pixels of the image's format can't be read.
+    pub fn from_image(image: &Image, rect: Option<Rect>, alpha_threshold: f32) -> Option<Self> {
+        let image_rect = Rect::from_corners(Vec2::ZERO, image.size_f32());
+        let rect = rect.map_or(image_rect, |rect| rect.intersect(image_rect));
+        if rect.is_empty() { return None };
+        let min = rect.min.floor().as_uvec2();
+        let max = rect.max.ceil().as_uvec2();
+
+        // Only the outermost opaque pixels of each row can be on the hull
+        let mut outline = Vec::new();
+        for y in min.y..max.y {
+            let is_opaque = |x: u32| image.get_color_at(x, y).is_ok_and(|color| color.alpha() > alpha_threshold);
+            let Some(left) = (min.x..max.x).find(|&x| is_opaque(x)) else { continue };
+            let right = (left..max.x).rev().find(|&x| is_opaque(x)).unwrap_or(left);
+            let corners = [
+                UVec2::new(left, y),
+                UVec2::new(left, y + 1),
+                UVec2::new(right + 1, y),
+                UVec2::new(right + 1, y + 1),
+            ];
+            outline.extend(corners.map(|corner| (corner.as_vec2() - rect.min) / rect.size()));
+        }
+        let hull = convex_hull(outline);
+        (hull.len() >= 3).then(|| Self::new(hull))
+    }
+
+    /// Number of quads the polygon is rendered with.
+    pub(crate) fn quad_count(&self) -> usize {
+        self.points.len().saturating_sub(1) / 2
+    }
+}
+
+/// Computes a [`Sprite3dPolygon`] for a sprite once its texture is loaded, fitting the opaque pixels
+/// of the region it renders. Recomputed when the region changes, ie: between frames of an animation.
+/// Results are shared between sprites that render the same region of a texture.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dTrim {
+    /// Pixels with an alpha at or below this are trimmed.
+    pub alpha_threshold: f32,
+}
+
+impl Default for Sprite3dTrim {
+    fn default() -> Self {
+        Self { alpha_threshold: 0.01 }
+    }
+}
+
+/// Region of a texture trimmed with a given threshold, as bits so that it can be hashed.
+type TrimKey = (AssetId<Image>, Option<[u32; 4]>, u32);
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn trim_sprites<M: SizedMaterial>(
+    mut commands: Commands,
+    sprites: Query<
+        (Entity, &Sprite3d, &SpriteMaterial3d<M>, &Sprite3dTrim, Option<&Sprite3dPolygon>),
+        Or<(Changed<Sprite3d>, Changed<SpriteMaterial3d<M>>, Changed<Sprite3dTrim>, Without<Sprite3dPolygon>)>,
+    >,
+    materials: Res<Assets<M>>,
+    images: Res<Assets<Image>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut polygons: Local<HashMap<TrimKey, Option<Sprite3dPolygon>>>,
+) {
+    for event in image_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = *event {
+            polygons.retain(|(image_id, _, _), _| *image_id != id);
+        }
+    }
+    for (entity, sprite, sprite_mat_handle, trim, polygon) in &sprites {
+        let Some(texture) = materials.get(&sprite_mat_handle.0).and_then(SizedMaterial::texture) else { continue };
+        let Some(image) = images.get(texture) else { continue };
+        let rect = match (sprite.rect, sprite.uv_rect) {
+            (Some(rect), _) => Some(rect),
+            (None, uv_rect) => uv_rect.map(|uv_rect| Rect {
+                min: uv_rect.min * image.size_f32(),
+                max: uv_rect.max * image.size_f32(),
+            }),
+        };
+        let rect_bits = rect.map(|rect| [rect.min.x, rect.min.y, rect.max.x, rect.max.y].map(f32::to_bits));
+        let key = (texture.id(), rect_bits, trim.alpha_threshold.to_bits());
+        let trimmed = polygons
+            .entry(key)
+            .or_insert_with(|| Sprite3dPolygon::from_image(image, rect, trim.alpha_threshold));
+        let Some(trimmed) = trimmed else { continue };
+        if polygon != Some(trimmed) {
+            commands.entity(entity).insert(trimmed.clone());
+        }
+    }
+}
+
+/// Convex hull of a set of points, using Andrew's monotone chain algorithm.
+fn convex_hull(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    points.sort_unstable_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 { return points };
+    let is_convex = |chain: &[Vec2], point: Vec2| match chain {
+        [.., a, b] => (*b - *a).perp_dot(point - *a) > 0.0,
+        _ => true,
+    };
+    let mut lower: Vec<Vec2> = Vec::new();
+    for &point in &points {
+        while !is_convex(&lower, point) { lower.pop(); }
+        lower.push(point);
+    }
+    let mut upper: Vec<Vec2> = Vec::new();
+    for &point in points.iter().rev() {
+        while !is_convex(&upper, point) { upper.pop(); }
+        upper.push(point);
+    }
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+    lower
+}
+
+/// Splits the quad of a sprite along its polygon, if it has one.
+/// The polygon is rendered as a fan of quads sharing its first point. With an odd number of points,
+/// the last quad is degenerate (a triangle).
+pub(crate) fn polygon_quads<'a>(
+    quad: SpriteQuad,
+    sprite: &Sprite3d,
+    polygon: Option<&'a Sprite3dPolygon>,
+) -> impl Iterator<Item = SpriteQuad> + 'a {
+    let (flip_x, flip_y, flip_d) = (sprite.flip_x, sprite.flip_y, sprite.flip_d);
+    let fan = polygon.map(move |polygon| {
+        // Maps points from texture space to the space of the quad, undoing the flips applied to its UVs
+        let to_quad = move |point: Vec2| {
+            let point = if flip_d { Vec2::new(point.y, point.x) } else { point };
+            Vec2::new(
+                if flip_x { 1.0 - point.x } else { point.x },
+                if flip_y { 1.0 - point.y } else { point.y },
+            )
+        };
+        let points = &polygon.points;
+        let n = points.len();
+
+        // Front faces have a negative signed area in quad space, as its y axis points down
+        let area: f32 = (0..n).map(|i| to_quad(points[i]).perp_dot(to_quad(points[(i + 1) % n]))).sum();
+        let point = move |i: usize| match area > 0.0 {
+            true => to_quad(points[(n - i) % n]),
+            false => to_quad(points[i]),
+        };
+        (0..polygon.quad_count()).map(move |k| {
+            let i = 1 + 2 * k;
+            sub_quad(&quad, [point(0), point(i), point(i + 1), point((i + 2).min(n - 1))])
+        })
+    });
+    std::iter::once(quad)
+        .filter(move |_| polygon.is_none())
+        .chain(fan.into_iter().flatten())
+}
+
+/// Quad whose corners are bilinearly interpolated from another quad's.
+/// Points are in quad space, from (0, 0) at the top left to (1, 1) at the bottom right.
+pub(crate) fn sub_quad(quad: &SpriteQuad, points: [Vec2; 4]) -> SpriteQuad {
+    SpriteQuad {
+        positions: points.map(|point| bilerp(&quad.positions, point)),
+        uvs: points.map(|point| bilerp(&quad.uvs, point)),
+        secondary_uvs: points.map(|point| bilerp(&quad.secondary_uvs, point)),
+        normal_tilts: points.map(|point| bilerp(&quad.normal_tilts, point)),
+        sway: points.map(|point| bilerp(&quad.sway, point)),
+        ..*quad
+    }
+}
+
+/// Interpolates values of the corners of a quad (bl, br, tr, tl) at a point in quad space.
+fn bilerp<const N: usize>(corners: &[[f32; N]; 4], point: Vec2) -> [f32; N] {
+    let [bl, br, tr, tl] = corners;
+    std::array::from_fn(|i| {
+        let top = tl[i] + (tr[i] - tl[i]) * point.x;
+        let bottom = bl[i] + (br[i] - bl[i]) * point.x;
+        top + (bottom - top) * point.y
+    })
+}
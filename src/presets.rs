@@ -0,0 +1,70 @@
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_image::prelude::*;
+use bevy_pbr::prelude::*;
+use bevy_render::prelude::*;
+use bevy_sprite::TextureAtlasLayout;
+
+use crate::{SizedMaterial, Sprite3d, Sprite3dAtlas, Sprite3dBillboard, SpriteMaterial3d};
+
+/// Sprite that turns to face the nearest camera, staying upright.
+#[derive(Bundle, Clone, Debug)]
+pub struct BillboardSprite3dBundle<M: SizedMaterial = StandardMaterial> {
+    pub sprite: Sprite3d,
+    pub material: SpriteMaterial3d<M>,
+    pub billboard: Sprite3dBillboard,
+}
+
+impl<M: SizedMaterial> BillboardSprite3dBundle<M> {
+    pub fn new(material: Handle<M>) -> Self {
+        Self {
+            sprite: Sprite3d::default(),
+            material: SpriteMaterial3d(material),
+            billboard: Sprite3dBillboard::upright(),
+        }
+    }
+}
+
+/// Sprite unaffected by lighting, like a regular 2D sprite placed in a 3D scene.
+/// Use [`UnlitSprite3dBundle::material`] to make its material.
+#[derive(Bundle, Clone, Debug)]
+pub struct UnlitSprite3dBundle {
+    pub sprite: Sprite3d,
+    pub material: SpriteMaterial3d<StandardMaterial>,
+}
+
+impl UnlitSprite3dBundle {
+    pub fn new(material: Handle<StandardMaterial>) -> Self {
+        Self { sprite: Sprite3d::default(), material: SpriteMaterial3d(material) }
+    }
+
+    /// Unlit, alpha blended material showing a texture, visible from both sides.
+    pub fn material(texture: Handle<Image>) -> StandardMaterial {
+        StandardMaterial {
+            base_color_texture: Some(texture),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            cull_mode: None,
+            double_sided: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Sprite showing a region of a sprite sheet, selected by index in a [`TextureAtlasLayout`].
+#[derive(Bundle, Clone, Debug)]
+pub struct AtlasSprite3dBundle<M: SizedMaterial = StandardMaterial> {
+    pub sprite: Sprite3d,
+    pub material: SpriteMaterial3d<M>,
+    pub atlas: Sprite3dAtlas,
+}
+
+impl<M: SizedMaterial> AtlasSprite3dBundle<M> {
+    pub fn new(material: Handle<M>, layout: Handle<TextureAtlasLayout>, index: usize) -> Self {
+        Self {
+            sprite: Sprite3d::default(),
+            material: SpriteMaterial3d(material),
+            atlas: Sprite3dAtlas::new(layout, index),
+        }
+    }
+}
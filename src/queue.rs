@@ -0,0 +1,48 @@
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_pbr::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::{SizedMaterial, Sprite3d};
+
+/// Draws sprites for a single frame, without spawning entities, ie: for debug markers, damage numbers and editor handles.
+/// Queued sprites are batched along with sprite entities that share their material, the next time
+/// [`Sprite3dSystems`](crate::Sprite3dSystems) runs, and the queue is emptied afterwards.
+/// Queued sprites are always visible, and on the default render layer.
+#[derive(Resource, Debug)]
+pub struct Sprite3dQueue<M: SizedMaterial = StandardMaterial> {
+    pub(crate) sprites: Vec<QueuedSprite<M>>,
+}
+
+impl<M: SizedMaterial> Default for Sprite3dQueue<M> {
+    fn default() -> Self {
+        Self { sprites: Vec::new() }
+    }
+}
+
+impl<M: SizedMaterial> Sprite3dQueue<M> {
+    pub fn draw(&mut self, sprite: Sprite3d, transform: impl Into<GlobalTransform>, material: Handle<M>) {
+        self.sprites.push(QueuedSprite { sprite, transform: transform.into(), material });
+    }
+
+    /// Number of sprites queued for the next frame.
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// Removes all queued sprites.
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct QueuedSprite<M: SizedMaterial> {
+    pub sprite: Sprite3d,
+    pub transform: GlobalTransform,
+    pub material: Handle<M>,
+}
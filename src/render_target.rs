@@ -0,0 +1,74 @@
+use bevy_asset::{prelude::*, RenderAssetUsages};
+use bevy_ecs::prelude::*;
+use bevy_image::prelude::*;
+use bevy_math::{UVec2, Vec2};
+use bevy_reflect::prelude::*;
+use bevy_render::camera::RenderTarget;
+use bevy_render::prelude::*;
+use bevy_render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+
+use crate::{SizedMaterial, Sprite3d, SpriteMaterial3d};
+
+/// Displays what a camera renders on a sprite, ie: security camera monitors, portals and picture-in-picture
+/// panels. The camera should render to an image, ie: one made with [`render_target_image`].
+/// The sprite's material is replaced with a copy textured with the camera's image, made with
+/// [`SizedMaterial::with_texture`], whenever the camera's target changes.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+#[require(Sprite3d)]
+pub struct Sprite3dRenderTarget {
+    pub camera: Entity,
+    /// Width of the sprite in world units, with its height following the aspect ratio of the camera's image as it
+    /// is resized. If None, the sprite is the size of the image in pixels, like any other sprite.
+    pub width: Option<f32>,
+}
+
+impl Sprite3dRenderTarget {
+    pub fn new(camera: Entity) -> Self {
+        Self { camera, width: None }
+    }
+
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.width = Some(width);
+        self
+    }
+}
+
+/// Blank image that a camera can render to, and sprites can sample, for a [`Sprite3dRenderTarget`].
+pub fn render_target_image(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d { width: size.x, height: size.y, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+// Textures the materials of render target sprites with the images of their cameras, and sizes the sprites
+// after them.
+pub(crate) fn sync_render_targets<M: SizedMaterial>(
+    mut sprites: Query<(&Sprite3dRenderTarget, &mut Sprite3d, &mut SpriteMaterial3d<M>)>,
+    cameras: Query<&Camera>,
+    mut materials: ResMut<Assets<M>>,
+    images: Res<Assets<Image>>,
+) {
+    for (render_target, mut sprite, mut material) in &mut sprites {
+        let Ok(camera) = cameras.get(render_target.camera) else { continue };
+        let RenderTarget::Image(image) = &camera.target else { continue };
+        let Some(sprite_mat) = materials.get(&material.0) else { continue };
+        if sprite_mat.texture() != Some(image) {
+            let Some(textured) = sprite_mat.with_texture(image.clone()) else { continue };
+            material.0 = materials.add(textured);
+        }
+        let Some(width) = render_target.width else { continue };
+        let Some(image_size) = images.get(image).map(Image::size_f32) else { continue };
+        if image_size.x <= 0.0 { continue };
+        let custom_size = Some(Vec2::new(width, width * image_size.y / image_size.x));
+        if sprite.custom_size != custom_size {
+            sprite.custom_size = custom_size;
+        }
+    }
+}
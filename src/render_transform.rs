@@ -0,0 +1,188 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use bevy_render::camera::{Camera, OrthographicProjection, Projection};
+use bevy_render::view::VisibilityRange;
+use bevy_time::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::interpolation::interpolate_transform;
+use crate::layers::ResolvedSprite3dLayer;
+use crate::view::{visibility_range_alpha, SpriteView};
+use crate::{
+    MeshBatch, ParallaxSprite3d, PreviousTransform, SizedMaterial, Sprite3dBillboard, Sprite3dHighlight,
+    Sprite3dNameplate, Sprite3dNearFade, Sprite3dScreenScale, Sprite3dSky, SpriteMaterial3d, UpAxis,
+};
+
+/// Transform and fade a sprite is rendered with, which may differ from its [`GlobalTransform`] as sprites adapt
+/// to the cameras and to fixed timesteps.
+/// Computed before batching by [`update_render_transforms`], and only written when it changes, so that sprites
+/// that adapt to the cameras aren't regenerated every frame while the cameras stand still.
+/// Picking and highlight outlines read it too, so that they match what is rendered.
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub(crate) struct SpriteRenderTransform {
+    pub transform: GlobalTransform,
+    /// Alpha the sprite's colors are multiplied by, from its [`Sprite3dNearFade`].
+    pub near_alpha: f32,
+    /// How visible the sprite is within its [`VisibilityRange`]. Sprites out of range, at 0, aren't rendered.
+    pub range_alpha: f32,
+}
+
+impl Default for SpriteRenderTransform {
+    fn default() -> Self {
+        Self { transform: GlobalTransform::IDENTITY, near_alpha: 1.0, range_alpha: 1.0 }
+    }
+}
+
+/// Components that make a sprite render elsewhere than its [`GlobalTransform`].
+#[derive(QueryData)]
+pub(crate) struct RenderTransformQuery {
+    global_transform: &'static GlobalTransform,
+    transform: &'static Transform,
+    previous_transform: Option<&'static PreviousTransform>,
+    nameplate: Option<&'static Sprite3dNameplate>,
+    sky: Option<&'static Sprite3dSky>,
+    parallax: Option<&'static ParallaxSprite3d>,
+    billboard: Option<&'static Sprite3dBillboard>,
+    layer: Option<&'static ResolvedSprite3dLayer>,
+    screen_scale: Option<&'static Sprite3dScreenScale>,
+    near_fade: Option<&'static Sprite3dNearFade>,
+    highlight: Option<&'static Sprite3dHighlight>,
+    visibility_range: Option<&'static VisibilityRange>,
+}
+
+impl RenderTransformQueryItem<'_> {
+    /// Interpolated between fixed timesteps if the sprite has a [`PreviousTransform`], turned to stand along
+    /// `up_axis`, placed relative to its target or the camera if it has a [`Sprite3dNameplate`], [`Sprite3dSky`] or
+    /// [`ParallaxSprite3d`], turned towards the camera if it has a [`Sprite3dBillboard`], and scaled if it has a
+    /// [`Sprite3dScreenScale`], a shrinking [`Sprite3dNearFade`] or a pulsing [`Sprite3dHighlight`].
+    fn render_transform(
+        &self,
+        overstep: f32,
+        views: &[SpriteView],
+        transforms: &Query<&GlobalTransform>,
+        up_axis: UpAxis,
+    ) -> SpriteRenderTransform {
+        let sprite_transf = match self.previous_transform {
+            Some(previous) => interpolate_transform(self.global_transform, self.transform, previous, overstep),
+            None => *self.global_transform,
+        };
+        let mut sprite_transf = sprite_transf.mul_transform(Transform::from_rotation(up_axis.rotation()));
+        if let Some(nameplate) = self.nameplate {
+            if let Ok(target_transf) = transforms.get(nameplate.target) {
+                sprite_transf = nameplate.place(&sprite_transf, target_transf, views);
+            }
+        }
+        if let Some(sky) = self.sky {
+            sprite_transf = sky.place(&sprite_transf, views);
+        }
+        if let Some(parallax) = self.parallax {
+            sprite_transf = parallax.apply(&sprite_transf, views);
+        }
+        let layer_billboard = self.layer.and_then(|layer| layer.0.billboard);
+        if let Some(billboard) = self.billboard.copied().or(layer_billboard) {
+            sprite_transf = billboard.apply(&sprite_transf, views, up_axis);
+        }
+        if let Some(screen_scale) = self.screen_scale {
+            sprite_transf = screen_scale.apply(&sprite_transf, views);
+        }
+        if let Some(near_fade) = self.near_fade {
+            sprite_transf = near_fade.apply(&sprite_transf, views);
+        }
+        if let Some(highlight) = self.highlight {
+            sprite_transf = highlight.apply_transform(&sprite_transf);
+        }
+        SpriteRenderTransform {
+            transform: sprite_transf,
+            near_alpha: self.near_fade.map_or(1.0, |near_fade| near_fade.alpha(&sprite_transf, views)),
+            range_alpha: self.visibility_range.map_or(1.0, |range| {
+                visibility_range_alpha(range, self.global_transform.translation_vec3a(), views)
+            }),
+        }
+    }
+}
+
+/// Updates the render transforms of the sprites of a material, for the cameras as of this frame.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_render_transforms<M: SizedMaterial>(
+    mut sprites: Query<(RenderTransformQuery, &mut SpriteRenderTransform), With<SpriteMaterial3d<M>>>,
+    cameras: Query<(&GlobalTransform, &Camera, Option<&Projection>, Option<&OrthographicProjection>)>,
+    transforms: Query<&GlobalTransform>,
+    mesh_batch: Res<MeshBatch<M>>,
+    fixed_time: Option<Res<Time<Fixed>>>,
+) {
+    let overstep = fixed_time.map(|time| time.overstep_fraction()).unwrap_or(1.0);
+    let views: Vec<SpriteView> = cameras
+        .iter()
+        .filter(|(_, camera, _, _)| camera.is_active)
+        .map(|(transform, camera, projection, orthographic)| SpriteView::new(transform, camera, projection, orthographic))
+        .collect();
+    for (item, mut render_transform) in &mut sprites {
+        render_transform.set_if_neq(item.render_transform(overstep, &views, &transforms, mesh_batch.up_axis));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::test_utils::{test_app, textured_material};
+    use crate::*;
+
+    use super::SpriteRenderTransform;
+
+    /// App with a camera looking down -Z, and billboarded sprites along the X axis.
+    fn billboard_app(plugin: Sprite3dPlugin<StandardMaterial>, sprites: usize) -> (App, Entity, Vec<Entity>) {
+        let mut app = test_app(plugin);
+        let material = textured_material(&mut app, 16, 16);
+        let camera = app.world_mut().spawn((Camera::default(), Transform::from_xyz(4.0, 0.0, 10.0))).id();
+        let sprites = (0..sprites)
+            .map(|i| {
+                app.world_mut().spawn((
+                    Sprite3d::default(),
+                    SpriteMaterial3d(material.clone()),
+                    Sprite3dBillboard::default(),
+                    Transform::from_xyz(i as f32, 0.0, 0.0),
+                )).id()
+            })
+            .collect();
+        (app, camera, sprites)
+    }
+
+    /// Render transforms changed in the last update.
+    #[derive(Resource, Default)]
+    struct ChangedRenderTransforms(usize);
+
+    fn count_changed_render_transforms(
+        render_transforms: Query<(), Changed<SpriteRenderTransform>>,
+        mut changed: ResMut<ChangedRenderTransforms>,
+    ) {
+        changed.0 = render_transforms.iter().count();
+    }
+
+    #[test]
+    fn render_transforms_only_change_with_the_view() {
+        let (mut app, camera, sprites) = billboard_app(Sprite3dPlugin::default(), 1);
+        app.init_resource::<ChangedRenderTransforms>();
+        app.add_systems(PostUpdate, count_changed_render_transforms.after(Sprite3dSystems));
+        app.update();
+        let render_transform = app.world().get::<SpriteRenderTransform>(sprites[0]).unwrap();
+        assert_ne!(render_transform.transform.rotation(), Quat::IDENTITY);
+        app.update();
+        assert_eq!(app.world().resource::<ChangedRenderTransforms>().0, 0);
+
+        app.world_mut().get_mut::<Transform>(camera).unwrap().translation.x = -4.0;
+        app.update();
+        assert_eq!(app.world().resource::<ChangedRenderTransforms>().0, 1);
+    }
+
+    #[test]
+    fn static_billboards_leave_the_budget_to_changed_sprites() {
+        let (mut app, _, _) = billboard_app(Sprite3dPlugin::default().with_budget(BatchBudget::Sprites(1)), 3);
+        for _ in 0..4 {
+            app.update();
+        }
+        let mesh_batch = app.world().resource::<MeshBatch<StandardMaterial>>();
+        assert!(mesh_batch.pending.is_empty());
+        assert_eq!(mesh_batch.cache.len(), 3);
+    }
+}
@@ -0,0 +1,36 @@
+use bevy_ecs::prelude::*;
+use bevy_math::Vec3;
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::view::{nearest_view, SpriteView};
+
+/// Keeps a sprite at a constant size on screen, regardless of its distance to the camera,
+/// ie: for gizmo icons, waypoints and map pins.
+/// The sprite is scaled so that each unit of its size covers `pixels_per_unit` screen pixels of the nearest camera.
+/// A 32x32 image, or a `custom_size` of 32x32, covers 32x32 pixels with the default of 1.
+/// The scale of the sprite's transform still applies on top.
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dScreenScale {
+    pub pixels_per_unit: f32,
+}
+
+impl Sprite3dScreenScale {
+    pub fn new(pixels_per_unit: f32) -> Self {
+        Self { pixels_per_unit }
+    }
+
+    /// Transform of a sprite, rescaled around its origin for the camera nearest to it.
+    pub(crate) fn apply(&self, sprite_transf: &GlobalTransform, views: &[SpriteView]) -> GlobalTransform {
+        let position = sprite_transf.translation_vec3a();
+        let pixel_size = nearest_view(views, position).and_then(|view| view.pixel_size_at(position));
+        let Some(pixel_size) = pixel_size else { return *sprite_transf };
+        sprite_transf.mul_transform(Transform::from_scale(Vec3::splat(pixel_size * self.pixels_per_unit)))
+    }
+}
+
+impl Default for Sprite3dScreenScale {
+    fn default() -> Self {
+        Self { pixels_per_unit: 1.0 }
+    }
+}
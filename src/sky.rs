@@ -0,0 +1,50 @@
+use bevy_ecs::prelude::*;
+use bevy_math::Vec3;
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::view::{first_view, SpriteView};
+
+/// Depth bias of sky batches, so that they are drawn before other batches.
+/// Small enough in depth buffer units not to clip sky sprites.
+pub(crate) const SKY_DEPTH_BIAS: f32 = -10_000.0;
+
+/// Renders a sprite behind everything else, at an effectively infinite distance, ie: for the sun, the moon and
+/// distant mountains.
+/// The sprite's transform is relative to the camera rendered first, with the lowest
+/// [`Camera::order`](bevy_render::camera::Camera::order), as if the camera were at the origin: the sprite follows
+/// the camera around, and never gets closer. When rendered, it is pushed out to `distance` from the camera,
+/// and scaled up accordingly, so that it keeps its apparent size.
+/// Sky sprites are rendered in separate batches, drawn before other batches, using a copy of the material
+/// made with [`SizedMaterial::without_fog`](crate::SizedMaterial::without_fog).
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dSky {
+    /// Distance from the camera sky sprites are rendered at. Should be beyond the rest of the scene,
+    /// but within the far plane of the camera (1000 by default), so that the sprites aren't culled.
+    pub distance: f32,
+}
+
+impl Sprite3dSky {
+    pub fn new(distance: f32) -> Self {
+        Self { distance }
+    }
+
+    /// Transform of the sprite, pushed out from the camera.
+    pub(crate) fn place(&self, sprite_transf: &GlobalTransform, views: &[SpriteView]) -> GlobalTransform {
+        let Some(view) = first_view(views) else { return *sprite_transf };
+        let (scale, rotation, translation) = sprite_transf.to_scale_rotation_translation();
+        let length = translation.length();
+        if length <= 0.0 { return *sprite_transf };
+        let factor = self.distance / length;
+        Transform::from_translation(Vec3::from(view.position) + translation * factor)
+            .with_rotation(rotation)
+            .with_scale(scale * factor)
+            .into()
+    }
+}
+
+impl Default for Sprite3dSky {
+    fn default() -> Self {
+        Self { distance: 900.0 }
+    }
+}
@@ -0,0 +1,44 @@
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_render::prelude::*;
+use bevy_render::view::VisibleEntities;
+use bevy_utils::HashSet;
+
+use crate::{SizedMaterial, Sprite3dBatch};
+
+/// Gives a camera its own copies of transparent batches, with their sprites sorted back-to-front from the camera,
+/// ie: for portrait renderers and mirrors that look at sprites from another side than the main camera.
+/// Other cameras keep rendering the shared batches, whose sprites aren't sorted.
+/// Only batches whose material reports [`SizedMaterial::is_transparent`] are copied. Copies take as much memory
+/// as the batches they copy, so this is best kept to cameras that need it.
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct Sprite3dSortedView;
+
+/// Marks a batch entity that copies another batch for a single [`Sprite3dSortedView`] camera.
+/// It has the same [`Sprite3dBatch`] as the batch it copies.
+#[derive(Component, Reflect, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Sprite3dViewBatch {
+    /// Camera the copy is rendered by.
+    pub view: Entity,
+    /// Batch entity the copy was made from.
+    pub source: Entity,
+}
+
+/// Makes cameras render their own copies of batches instead of the batches they copy, and hides copies from
+/// other cameras.
+pub(crate) fn filter_view_batches<M: SizedMaterial>(
+    mut views: Query<(Entity, &mut VisibleEntities), With<Camera>>,
+    view_batches: Query<&Sprite3dViewBatch, With<Sprite3dBatch<M>>>,
+) {
+    if view_batches.is_empty() { return };
+    let copied: HashSet<(Entity, Entity)> = view_batches
+        .iter()
+        .map(|view_batch| (view_batch.source, view_batch.view))
+        .collect();
+    for (view, mut visible_entities) in &mut views {
+        visible_entities.get_mut::<With<Mesh3d>>().retain(|&entity| match view_batches.get(entity) {
+            Ok(view_batch) => view_batch.view == view,
+            Err(_) => !copied.contains(&(entity, view)),
+        });
+    }
+}
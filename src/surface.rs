@@ -0,0 +1,99 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_math::{Mat3, Quat, Vec3};
+use bevy_reflect::prelude::*;
+use bevy_transform::prelude::*;
+
+/// Orients a sprite to a surface, ie: a normal from a raycast hit, so decals and plants snap onto sloped terrain.
+/// The rotation of the sprite's [`Transform`] is overwritten whenever this component changes.
+/// Directions are in the space of the sprite's parent, which is world space for sprites without one.
+/// Rotations are for sprites built upright along Y. With [`UpAxis::Z`](crate::UpAxis::Z), sprites are turned by
+/// [`UpAxis::rotation`](crate::UpAxis::rotation) on top of them.
+/// Requires the [`Sprite3dSurfacePlugin`].
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dSurface {
+    /// Normal of the surface the sprite sits on.
+    pub normal: Vec3,
+    pub alignment: SurfaceAlignment,
+    /// Optional up vector constraint.
+    /// For [`SurfaceAlignment::Flat`], the direction the top of the sprite points towards, as closely as the surface allows.
+    /// Defaults to [`Vec3::Y`].
+    /// For [`SurfaceAlignment::Upright`], the direction the sprite stands along instead of the normal, ie: for plants
+    /// that grow straight up on slopes. The sprite still faces away from the surface.
+    pub up: Option<Vec3>,
+}
+
+impl Sprite3dSurface {
+    pub fn flat(normal: Vec3) -> Self {
+        Self { normal, alignment: SurfaceAlignment::Flat, up: None }
+    }
+
+    pub fn upright(normal: Vec3) -> Self {
+        Self { normal, alignment: SurfaceAlignment::Upright, up: None }
+    }
+
+    pub fn with_up(mut self, up: Vec3) -> Self {
+        self.up = Some(up);
+        self
+    }
+
+    /// Rotation of a sprite sitting on the surface.
+    /// None if the directions are degenerate (zero-length, or parallel where they need not be).
+    pub fn rotation(&self) -> Option<Quat> {
+        let normal = self.normal.try_normalize()?;
+        match self.alignment {
+            SurfaceAlignment::Flat => {
+                let up = self.up.unwrap_or(Vec3::Y);
+                // Falls back to another reference when the surface faces straight up
+                let up = match up.reject_from(normal).try_normalize() {
+                    Some(up) => up,
+                    None => Vec3::NEG_Z.reject_from(normal).try_normalize()?,
+                };
+                Some(rotation_from_axes(up.cross(normal), up, normal))
+            },
+            SurfaceAlignment::Upright => {
+                let up = match self.up {
+                    Some(up) => up.try_normalize()?,
+                    None => normal,
+                };
+                // Faces away from the surface, or towards +Z on flat ground
+                let front = match normal.reject_from(up).try_normalize() {
+                    Some(front) => front,
+                    None => Vec3::Z.reject_from(up).try_normalize()?,
+                };
+                Some(rotation_from_axes(up.cross(front), up, front))
+            },
+        }
+    }
+}
+
+/// How a sprite sits on a [`Sprite3dSurface`].
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum SurfaceAlignment {
+    /// Lies flat against the surface, facing out of it. Suited for decals.
+    #[default]
+    Flat,
+    /// Stands on the surface, along its normal. Suited for plants and props.
+    Upright,
+}
+
+fn rotation_from_axes(x: Vec3, y: Vec3, z: Vec3) -> Quat {
+    Quat::from_mat3(&Mat3::from_cols(x, y, z))
+}
+
+/// Turns sprites to their [`Sprite3dSurface`].
+pub struct Sprite3dSurfacePlugin;
+
+impl Plugin for Sprite3dSurfacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, align_to_surfaces.before(TransformSystem::TransformPropagate));
+    }
+}
+
+pub(crate) fn align_to_surfaces(mut sprites: Query<(&Sprite3dSurface, &mut Transform), Changed<Sprite3dSurface>>) {
+    for (surface, mut transf) in &mut sprites {
+        if let Some(rotation) = surface.rotation() {
+            transf.rotation = rotation;
+        }
+    }
+}
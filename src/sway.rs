@@ -0,0 +1,112 @@
+use bevy_app::prelude::*;
+use bevy_asset::{load_internal_asset, prelude::*};
+use bevy_ecs::prelude::*;
+use bevy_math::{Vec3, Vec4};
+use bevy_pbr::prelude::*;
+use bevy_pbr::{ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline};
+use bevy_reflect::prelude::*;
+use bevy_render::mesh::{MeshVertexAttribute, MeshVertexBufferLayoutRef};
+use bevy_render::prelude::*;
+use bevy_render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, Shader, ShaderRef, SpecializedMeshPipelineError, VertexFormat,
+};
+
+use crate::{SpriteMaterialExtension, SpriteVertexAttributes};
+
+const SWAY_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x5f3a_9c1e_7b24_4d8a_a0c6_2e91_d4b7_3f05);
+
+/// Per-vertex sway of sprite batches: strength (zero on the bottom vertices) and phase.
+/// Written for [`Sprite3dSway`] when the material requires [`SpriteVertexAttributes::sway`].
+pub const ATTRIBUTE_SWAY: MeshVertexAttribute =
+    MeshVertexAttribute::new("Sprite3d_Sway", 2_140_529_361, VertexFormat::Float32x2);
+
+/// Makes the top of a sprite sway back and forth, ie: grass and trees in the wind.
+/// Animated entirely in the vertex shader, so swaying sprites don't need their batch rebuilt every frame.
+/// Only has an effect on sprites rendered with a [`SwayMaterial`].
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Default, Debug)]
+pub struct Sprite3dSway {
+    /// How far the top of the sprite moves, in world units, along [`SwayExtension::direction`].
+    pub strength: f32,
+    /// Offset of the sway cycle in radians, so that neighboring sprites don't move in lockstep.
+    pub phase: f32,
+}
+
+/// Material of sprites that sway in the wind.
+pub type SwayMaterial = ExtendedMaterial<StandardMaterial, SwayExtension>;
+
+/// Extends a material with a vertex shader that bends the top vertices of [`Sprite3dSway`] sprites over time.
+/// The sway is applied in the main pass only, so shadows and prepass outputs remain still.
+#[derive(Asset, AsBindGroup, Reflect, Clone, Debug)]
+#[uniform(100, Vec4)]
+pub struct SwayExtension {
+    /// World space direction of the wind, scaled by each sprite's [`Sprite3dSway::strength`].
+    pub direction: Vec3,
+    /// Speed of the sway cycle, in radians per second.
+    pub frequency: f32,
+}
+
+/// Packs the extension's settings into the uniform read by the shader.
+impl From<&SwayExtension> for Vec4 {
+    fn from(extension: &SwayExtension) -> Self {
+        extension.direction.extend(extension.frequency)
+    }
+}
+
+impl Default for SwayExtension {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::X,
+            frequency: 2.0,
+        }
+    }
+}
+
+impl MaterialExtension for SwayExtension {
+    fn vertex_shader() -> ShaderRef {
+        SWAY_SHADER_HANDLE.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // Prepass and shadow pipelines use their own vertex shader, which doesn't read the sway attribute
+        if descriptor.vertex.shader != SWAY_SHADER_HANDLE {
+            return Ok(());
+        }
+        let optional_attributes = [
+            Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+            Mesh::ATTRIBUTE_UV_0.at_shader_location(2),
+            Mesh::ATTRIBUTE_UV_1.at_shader_location(3),
+            Mesh::ATTRIBUTE_COLOR.at_shader_location(5),
+        ];
+        let mut attributes = vec![Mesh::ATTRIBUTE_POSITION.at_shader_location(0)];
+        attributes.extend(optional_attributes.into_iter().filter(|attribute| layout.0.contains(attribute.id)));
+        attributes.push(ATTRIBUTE_SWAY.at_shader_location(8));
+        descriptor.vertex.buffers = vec![layout.0.get_layout(&attributes)?];
+        Ok(())
+    }
+}
+
+impl SpriteMaterialExtension for SwayExtension {
+    fn required_vertex_attributes() -> SpriteVertexAttributes {
+        SpriteVertexAttributes { sway: true, ..SpriteVertexAttributes::NONE }
+    }
+
+    fn vertex_colors_optional() -> bool {
+        true
+    }
+}
+
+/// Registers [`SwayMaterial`] and its shader.
+/// Sprites using it are batched by adding a [`Sprite3dPlugin::<SwayMaterial>`](crate::Sprite3dPlugin) as well.
+pub struct Sprite3dSwayPlugin;
+
+impl Plugin for Sprite3dSwayPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, SWAY_SHADER_HANDLE, "sway.wgsl", Shader::from_wgsl);
+        app.add_plugins(MaterialPlugin::<SwayMaterial>::default());
+    }
+}
@@ -0,0 +1,32 @@
+//! Headless app setup shared by the crate's unit tests, integration tests and benchmarks. Not part of the public API.
+
+use bevy_app::prelude::*;
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::HierarchyPlugin;
+use bevy_image::Image;
+use bevy_pbr::StandardMaterial;
+use bevy_render::prelude::*;
+use bevy_transform::TransformPlugin;
+
+use crate::{SizedMaterial, Sprite3d, Sprite3dPlugin, Sprite3dSystems};
+
+/// Adds the given plugin to an app, along with the assets it batches sprites into, and marks every sprite visible.
+/// The app still needs Bevy's `MinimalPlugins`, or its own task pools and time.
+pub fn add_headless_batching<M: SizedMaterial>(app: &mut App, plugin: Sprite3dPlugin<M>) {
+    app.add_plugins((AssetPlugin::default(), TransformPlugin, HierarchyPlugin, plugin));
+    app.init_asset::<Image>();
+    app.init_asset::<Mesh>();
+    app.init_asset::<StandardMaterial>();
+    if !app.world().contains_resource::<Assets<M>>() {
+        app.init_asset::<M>();
+    }
+    app.add_systems(PostUpdate, mark_visible.before(Sprite3dSystems));
+}
+
+/// Stands in for Bevy's visibility checks, which need cameras and the render plugins.
+fn mark_visible(mut visibilities: Query<&mut ViewVisibility, With<Sprite3d>>) {
+    for mut visibility in &mut visibilities {
+        visibility.set();
+    }
+}
@@ -0,0 +1,84 @@
+//! Headless app helpers shared by the unit tests of the crate's modules.
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::test_support::add_headless_batching;
+use crate::{SizedMaterial, Sprite3dBatch, Sprite3dPlugin};
+
+/// Headless app batching sprites with the given plugin, where every sprite is visible.
+pub(crate) fn test_app(plugin: Sprite3dPlugin<StandardMaterial>) -> App {
+    material_test_app(plugin)
+}
+
+/// Same as [`test_app`], for sprites of other materials.
+pub(crate) fn material_test_app<M: SizedMaterial>(plugin: Sprite3dPlugin<M>) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    add_headless_batching(&mut app, plugin);
+    app
+}
+
+pub(crate) fn white_image(width: u32, height: u32) -> Image {
+    Image::new_fill(
+        Extent3d { width, height, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[255, 255, 255, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+/// Material textured with a white image of the given size.
+pub(crate) fn textured_material(app: &mut App, width: u32, height: u32) -> Handle<StandardMaterial> {
+    let image = app.world_mut().resource_mut::<Assets<Image>>().add(white_image(width, height));
+    app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+        base_color_texture: Some(image),
+        ..default()
+    })
+}
+
+/// Number of vertices in the batch of each material.
+pub(crate) fn batch_vertex_counts(app: &mut App) -> Vec<(AssetId<StandardMaterial>, usize)> {
+    let mut batches = app.world_mut().query::<(&Sprite3dBatch<StandardMaterial>, &Mesh3d)>();
+    let meshes = app.world().resource::<Assets<Mesh>>();
+    batches
+        .iter(app.world())
+        .map(|(batch, mesh)| (batch.material, meshes.get(&mesh.0).map_or(0, Mesh::count_vertices)))
+        .collect()
+}
+
+/// Number of vertices in the batch of a material, or 0 if it has none.
+pub(crate) fn batch_vertex_count(app: &mut App, material: &Handle<StandardMaterial>) -> usize {
+    batch_vertex_counts(app)
+        .into_iter()
+        .filter(|(id, _)| *id == material.id())
+        .map(|(_, count)| count)
+        .sum()
+}
+
+/// Headless app loading assets from a directory of `tests/fixtures`.
+#[cfg(any(feature = "tiled", feature = "aseprite", feature = "animated-image"))]
+pub(crate) fn fixture_app(directory: &str) -> App {
+    let mut app = App::new();
+    let asset_plugin = AssetPlugin { file_path: format!("tests/fixtures/{directory}"), ..default() };
+    app.add_plugins((MinimalPlugins, asset_plugin));
+    app.init_asset::<Image>();
+    app
+}
+
+/// Loads an asset of a [`fixture_app`], updating the app until it's loaded. Panics if it fails to load.
+#[cfg(any(feature = "tiled", feature = "aseprite", feature = "animated-image"))]
+pub(crate) fn load_fixture<A: Asset>(app: &mut App, path: &str) -> Handle<A> {
+    let handle = app.world().resource::<AssetServer>().load(path.to_string());
+    for _ in 0..1000 {
+        app.update();
+        match app.world().resource::<AssetServer>().get_load_state(&handle) {
+            Some(bevy::asset::LoadState::Loaded) => return handle,
+            Some(bevy::asset::LoadState::Failed(err)) => panic!("could not load {path}: {err}"),
+            _ => std::thread::sleep(std::time::Duration::from_millis(1)),
+        }
+    }
+    panic!("{path} took too long to load");
+}
@@ -0,0 +1,122 @@
+use bevy_app::prelude::*;
+use bevy_asset::{load_internal_asset, prelude::*};
+use bevy_image::prelude::*;
+use bevy_math::Vec2;
+use bevy_pbr::prelude::*;
+use bevy_pbr::{MaterialPipeline, MaterialPipelineKey};
+use bevy_reflect::prelude::*;
+use bevy_render::mesh::{MeshVertexAttribute, MeshVertexBufferLayoutRef};
+use bevy_render::prelude::*;
+use bevy_render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, Shader, ShaderRef, SpecializedMeshPipelineError, VertexFormat,
+};
+
+use crate::{texture_size, SizedMaterial, SpriteVertexAttributes};
+
+const TEXTURE_ARRAY_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x2c84_e7d1_5a96_4f3b_b812_7d0e_c35a_9e61);
+
+/// Per-vertex layer of the texture array sampled by sprite batches.
+/// Written from [`Sprite3d::layer`](crate::Sprite3d::layer) when the material requires
+/// [`SpriteVertexAttributes::layers`].
+pub const ATTRIBUTE_LAYER: MeshVertexAttribute =
+    MeshVertexAttribute::new("Sprite3d_Layer", 2_140_529_362, VertexFormat::Uint32);
+
+/// Unlit material sampling a `texture_2d_array`, at the layer of each sprite.
+/// Lets sprites from many sprite sheets of the same size share a single batch, one sheet per layer.
+/// Sprites are tinted by their color. With [`AlphaMode::Mask`], pixels with an alpha below its cutoff are discarded.
+/// Prepasses and shadows are disabled.
+#[derive(Asset, AsBindGroup, Reflect, Clone, Debug)]
+#[uniform(2, f32)]
+pub struct TextureArrayMaterial {
+    /// Image with multiple layers, ie: reinterpreted with [`Image::reinterpret_stacked_2d_as_array`].
+    #[texture(0, dimension = "2d_array")]
+    #[sampler(1)]
+    pub texture: Handle<Image>,
+    pub alpha_mode: AlphaMode,
+}
+
+impl TextureArrayMaterial {
+    pub fn new(texture: Handle<Image>) -> Self {
+        Self { texture, alpha_mode: AlphaMode::Blend }
+    }
+}
+
+/// Alpha cutoff read by the shader, only used with [`AlphaMode::Mask`].
+impl From<&TextureArrayMaterial> for f32 {
+    fn from(material: &TextureArrayMaterial) -> Self {
+        match material.alpha_mode {
+            AlphaMode::Mask(cutoff) => cutoff,
+            _ => 0.5,
+        }
+    }
+}
+
+impl Material for TextureArrayMaterial {
+    fn vertex_shader() -> ShaderRef {
+        TEXTURE_ARRAY_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        TEXTURE_ARRAY_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let mut attributes = vec![
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_UV_0.at_shader_location(2),
+        ];
+        if layout.0.contains(Mesh::ATTRIBUTE_COLOR) {
+            attributes.push(Mesh::ATTRIBUTE_COLOR.at_shader_location(5));
+        }
+        attributes.push(ATTRIBUTE_LAYER.at_shader_location(9));
+        descriptor.vertex.buffers = vec![layout.0.get_layout(&attributes)?];
+        Ok(())
+    }
+}
+
+impl SizedMaterial for TextureArrayMaterial {
+    /// Size of a single layer of the texture.
+    fn size(&self, images: &Assets<Image>) -> Option<Vec2> {
+        images.get(&self.texture).map(texture_size)
+    }
+
+    fn texture(&self) -> Option<&Handle<Image>> {
+        Some(&self.texture)
+    }
+
+    fn required_vertex_attributes() -> SpriteVertexAttributes {
+        SpriteVertexAttributes { colors: true, layers: true, ..SpriteVertexAttributes::NONE }
+    }
+
+    fn vertex_colors_optional() -> bool {
+        true
+    }
+
+    fn with_texture(&self, texture: Handle<Image>) -> Option<Self> {
+        Some(Self { texture, ..self.clone() })
+    }
+}
+
+/// Registers [`TextureArrayMaterial`] and its shader.
+/// Sprites using it are batched by adding a [`Sprite3dPlugin::<TextureArrayMaterial>`](crate::Sprite3dPlugin) as well.
+pub struct Sprite3dTextureArrayPlugin;
+
+impl Plugin for Sprite3dTextureArrayPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, TEXTURE_ARRAY_SHADER_HANDLE, "texture_array.wgsl", Shader::from_wgsl);
+        app.add_plugins(MaterialPlugin::<TextureArrayMaterial> {
+            prepass_enabled: false,
+            shadows_enabled: false,
+            ..Default::default()
+        });
+    }
+}
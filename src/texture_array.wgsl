@@ -0,0 +1,53 @@
+#import bevy_pbr::{
+    mesh_functions,
+    view_transformations::position_world_to_clip,
+}
+
+@group(2) @binding(0) var array_texture: texture_2d_array<f32>;
+@group(2) @binding(1) var array_sampler: sampler;
+@group(2) @binding(2) var<uniform> alpha_cutoff: f32;
+
+// Sprite batches using a texture array have no normals nor secondary UVs
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+#ifdef VERTEX_COLORS
+    @location(5) color: vec4<f32>,
+#endif
+    @location(9) layer: u32,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) @interpolate(flat) layer: u32,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    let world_position = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0));
+    out.position = position_world_to_clip(world_position.xyz);
+    out.uv = vertex.uv;
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#else
+    out.color = vec4<f32>(1.0);
+#endif
+    out.layer = vertex.layer;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(array_texture, array_sampler, in.uv, in.layer) * in.color;
+#ifdef MAY_DISCARD
+    if color.a < alpha_cutoff {
+        discard;
+    }
+#endif
+    return color;
+}
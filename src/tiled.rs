@@ -0,0 +1,545 @@
+use std::io::Read;
+
+use base64::Engine;
+use bevy_app::prelude::*;
+use bevy_asset::io::Reader;
+use bevy_asset::{prelude::*, AssetLoader, AssetPath, LoadContext, ParseAssetPathError, ReadAssetBytesError};
+use bevy_color::prelude::*;
+use bevy_core::Name;
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::prelude::*;
+use bevy_image::prelude::*;
+use bevy_math::{UVec2, Vec2};
+use bevy_pbr::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_render::alpha::AlphaMode;
+use bevy_render::prelude::*;
+use bevy_transform::prelude::*;
+use bevy_utils::HashMap;
+
+use crate::{spawn_tile_sprites, Sprite3dTileLayer, SpriteMaterial3d, Tile3d, TileAtlas};
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const GID_FLAGS: u32 = 0xF000_0000;
+
+/// Loads Tiled maps (`.tmx`), and spawns the tile layers of [`Sprite3dTiledMap`]s as batched sprites.
+/// Requires a [`Sprite3dPlugin<StandardMaterial>`](crate::Sprite3dPlugin) to render them.
+pub struct TiledMapPlugin;
+
+impl Plugin for TiledMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TiledMap>();
+        app.register_asset_loader(TiledMapLoader);
+        app.add_systems(PostUpdate, spawn_tiled_maps.before(spawn_tile_sprites::<StandardMaterial>));
+    }
+}
+
+/// Tile layers of a map loaded from a Tiled `.tmx` file.
+/// Orthogonal, finite maps are supported, with tilesets made of a single image (embedded, or external `.tsx` files).
+#[derive(Asset, TypePath, Debug)]
+pub struct TiledMap {
+    /// Number of columns and rows of the map.
+    pub grid_size: UVec2,
+    /// Size of a tile, in pixels.
+    pub tile_size: UVec2,
+    pub tilesets: Vec<TiledTileset>,
+    /// Tile layers, in the order they're drawn. Layers inside groups are flattened, with the visibility, opacity and
+    /// offset of their groups combined into theirs.
+    pub layers: Vec<TiledLayer>,
+}
+
+impl TiledMap {
+    /// Tileset a global tile id belongs to, and the tile it refers to. None for empty cells.
+    pub fn tile(&self, gid: u32) -> Option<(usize, Tile3d)> {
+        let id = gid & !GID_FLAGS;
+        if id == 0 { return None };
+        let tileset_index = self.tilesets.iter().rposition(|tileset| tileset.first_gid <= id)?;
+        let tile = Tile3d {
+            flip_x: gid & FLIPPED_HORIZONTALLY != 0,
+            flip_y: gid & FLIPPED_VERTICALLY != 0,
+            flip_d: gid & FLIPPED_DIAGONALLY != 0,
+            ..Tile3d::new(id - self.tilesets[tileset_index].first_gid)
+        };
+        Some((tileset_index, tile))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TiledTileset {
+    pub name: String,
+    /// Global id of the first tile of the tileset.
+    pub first_gid: u32,
+    pub image: Handle<Image>,
+    pub atlas: TileAtlas,
+}
+
+#[derive(Clone, Debug)]
+pub struct TiledLayer {
+    pub name: String,
+    pub visible: bool,
+    pub opacity: f32,
+    /// Offset of the layer, in pixels, with y pointing down like in Tiled.
+    pub offset: Vec2,
+    /// Global tile ids, flip flags included, in row-major order. 0 for empty cells.
+    pub tiles: Vec<u32>,
+}
+
+/// Spawns the tile layers of a [`TiledMap`] as children, once it's loaded.
+/// Each layer becomes a [`Sprite3dTileLayer`] per tileset it uses, marked with [`Sprite3dTiledMapLayer`].
+/// Layers are respawned when this component or the map changes.
+#[derive(Component, Reflect, Clone, Default, Debug)]
+#[require(Transform, Visibility)]
+pub struct Sprite3dTiledMap {
+    pub map: Handle<TiledMap>,
+    /// Size of a tile, in world units. Defaults to the map's tile size in pixels.
+    pub tile_size: Option<Vec2>,
+    /// Distance along z between consecutive layers, so that later layers render in front of earlier ones.
+    pub layer_spacing: f32,
+    /// Z of layers by name, overriding `layer_spacing`.
+    pub layer_z: HashMap<String, f32>,
+    /// Materials of layers by name. Their texture should be the image of the layer's tileset.
+    /// Other layers get a [`StandardMaterial`] using the tileset's image, alpha masked, or blended if the layer is translucent.
+    pub layer_materials: HashMap<String, Handle<StandardMaterial>>,
+}
+
+impl Sprite3dTiledMap {
+    pub fn new(map: Handle<TiledMap>) -> Self {
+        Self { map, layer_spacing: 0.01, ..Default::default() }
+    }
+
+    pub fn with_tile_size(mut self, tile_size: Vec2) -> Self {
+        self.tile_size = Some(tile_size);
+        self
+    }
+
+    pub fn with_layer_spacing(mut self, layer_spacing: f32) -> Self {
+        self.layer_spacing = layer_spacing;
+        self
+    }
+
+    pub fn with_layer_z(mut self, layer: impl Into<String>, z: f32) -> Self {
+        self.layer_z.insert(layer.into(), z);
+        self
+    }
+
+    pub fn with_layer_material(mut self, layer: impl Into<String>, material: Handle<StandardMaterial>) -> Self {
+        self.layer_materials.insert(layer.into(), material);
+        self
+    }
+}
+
+/// Marks a tile layer spawned for a [`Sprite3dTiledMap`].
+#[derive(Component, Reflect, Clone, PartialEq, Eq, Debug)]
+pub struct Sprite3dTiledMapLayer {
+    /// Name of the layer in the map.
+    pub name: String,
+    /// Index of the tileset in [`TiledMap::tilesets`].
+    pub tileset: usize,
+}
+
+fn spawn_tiled_maps(
+    mut commands: Commands,
+    tiled_maps: Query<(Entity, Ref<Sprite3dTiledMap>, Option<&Children>)>,
+    spawned_layers: Query<(), With<Sprite3dTiledMapLayer>>,
+    mut map_events: EventReader<AssetEvent<TiledMap>>,
+    map_assets: Res<Assets<TiledMap>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let changed_maps: Vec<AssetId<TiledMap>> = map_events
+        .read()
+        .filter_map(|event| match *event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(id),
+            _ => None,
+        })
+        .collect();
+    for (entity, tiled_map, children) in &tiled_maps {
+        if !tiled_map.is_changed() && !changed_maps.contains(&tiled_map.map.id()) { continue };
+        let Some(map) = map_assets.get(&tiled_map.map) else { continue };
+        for &child in children.into_iter().flatten() {
+            if spawned_layers.contains(child) {
+                commands.entity(child).despawn_recursive();
+            }
+        }
+
+        let tile_size = tiled_map.tile_size.unwrap_or(map.tile_size.as_vec2());
+        let pixel_scale = tile_size / map.tile_size.as_vec2();
+        let mut default_materials = HashMap::new();
+        for (layer_index, layer) in map.layers.iter().enumerate() {
+            if !layer.visible { continue };
+            let z = tiled_map.layer_z
+                .get(&layer.name)
+                .copied()
+                .unwrap_or(layer_index as f32 * tiled_map.layer_spacing);
+            let offset = Vec2::new(layer.offset.x, -layer.offset.y) * pixel_scale;
+
+            // Splits the layer by tileset, as each tile layer renders with a single texture
+            let mut tile_layers: HashMap<usize, Sprite3dTileLayer> = HashMap::new();
+            for (i, &gid) in layer.tiles.iter().enumerate() {
+                let Some((tileset_index, tile)) = map.tile(gid) else { continue };
+                let tile_layer = tile_layers.entry(tileset_index).or_insert_with(|| {
+                    Sprite3dTileLayer::new(map.grid_size, tile_size, map.tilesets[tileset_index].atlas)
+                });
+                tile_layer.tiles[i] = Some(Tile3d {
+                    color: Color::WHITE.with_alpha(layer.opacity),
+                    ..tile
+                });
+            }
+
+            for (tileset_index, tile_layer) in tile_layers {
+                let tileset = &map.tilesets[tileset_index];
+                let material = match tiled_map.layer_materials.get(&layer.name) {
+                    Some(material) => material.clone(),
+                    None => default_materials
+                        .entry((tileset_index, layer.opacity < 1.0))
+                        .or_insert_with(|| materials.add(StandardMaterial {
+                            base_color_texture: Some(tileset.image.clone()),
+                            alpha_mode: match layer.opacity < 1.0 {
+                                true => AlphaMode::Blend,
+                                false => AlphaMode::Mask(0.5),
+                            },
+                            perceptual_roughness: 1.0,
+                            ..Default::default()
+                        }))
+                        .clone(),
+                };
+                let layer_entity = commands.spawn((
+                    tile_layer,
+                    SpriteMaterial3d(material),
+                    Transform::from_translation(offset.extend(z)),
+                    Name::new(format!("Tiled Layer ({}, {})", layer.name, tileset.name)),
+                    Sprite3dTiledMapLayer { name: layer.name.clone(), tileset: tileset_index },
+                )).id();
+                commands.entity(entity).add_child(layer_entity);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct TiledMapLoader;
+
+impl AssetLoader for TiledMapLoader {
+    type Asset = TiledMap;
+    type Settings = ();
+    type Error = TiledMapLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<TiledMap, TiledMapLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let text = String::from_utf8(bytes).map_err(|_| TiledMapLoaderError::invalid("map is not valid UTF-8"))?;
+        let doc = roxmltree::Document::parse(&text)?;
+        let map_node = doc.root_element();
+        if !map_node.has_tag_name("map") {
+            return Err(TiledMapLoaderError::invalid("root element is not a <map>"));
+        }
+        if map_node.attribute("orientation").is_some_and(|orientation| orientation != "orthogonal") {
+            return Err(TiledMapLoaderError::invalid("only orthogonal maps are supported"));
+        }
+        if map_node.attribute("infinite") == Some("1") {
+            return Err(TiledMapLoaderError::invalid("infinite maps are not supported"));
+        }
+        let grid_size = UVec2::new(parse_attribute(map_node, "width")?, parse_attribute(map_node, "height")?);
+        let cell_count = (grid_size.x as usize)
+            .checked_mul(grid_size.y as usize)
+            .ok_or_else(|| TiledMapLoaderError::invalid("map is too large"))?;
+        let tile_size = UVec2::new(parse_attribute(map_node, "tilewidth")?, parse_attribute(map_node, "tileheight")?);
+        let map_path = load_context.asset_path().clone();
+
+        let mut tilesets = Vec::new();
+        for tileset_node in map_node.children().filter(|node| node.has_tag_name("tileset")) {
+            let first_gid = parse_attribute(tileset_node, "firstgid")?;
+            let tileset = match tileset_node.attribute("source") {
+                // External tileset, with paths relative to its own file
+                Some(source) => {
+                    let tileset_path = map_path.resolve_embed(source)?;
+                    let tileset_bytes = load_context.read_asset_bytes(&tileset_path).await?;
+                    let tileset_text = String::from_utf8(tileset_bytes)
+                        .map_err(|_| TiledMapLoaderError::invalid("tileset is not valid UTF-8"))?;
+                    let tileset_doc = roxmltree::Document::parse(&tileset_text)?;
+                    parse_tileset(tileset_doc.root_element(), first_gid, &tileset_path, load_context)?
+                },
+                None => parse_tileset(tileset_node, first_gid, &map_path, load_context)?,
+            };
+            tilesets.push(tileset);
+        }
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        let mut layers = Vec::new();
+        parse_layers(map_node, LayerAttributes::ROOT, cell_count, &mut layers)?;
+
+        Ok(TiledMap { grid_size, tile_size, tilesets, layers })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx"]
+    }
+}
+
+/// Visibility, opacity and offset of a layer or group, combined with those of the groups it's in.
+#[derive(Copy, Clone)]
+struct LayerAttributes {
+    visible: bool,
+    opacity: f32,
+    offset: Vec2,
+}
+
+impl LayerAttributes {
+    const ROOT: Self = Self { visible: true, opacity: 1.0, offset: Vec2::ZERO };
+
+    /// Attributes of a child of the layer or group these are the attributes of.
+    fn child(self, node: roxmltree::Node) -> Result<Self, TiledMapLoaderError> {
+        Ok(Self {
+            visible: self.visible && node.attribute("visible") != Some("0"),
+            opacity: self.opacity * parse_optional_attribute(node, "opacity")?.unwrap_or(1.0),
+            offset: self.offset + Vec2::new(
+                parse_optional_attribute(node, "offsetx")?.unwrap_or(0.0),
+                parse_optional_attribute(node, "offsety")?.unwrap_or(0.0),
+            ),
+        })
+    }
+}
+
+/// Tile layers of a map or group, with those of nested groups, in the order they're drawn.
+fn parse_layers(
+    node: roxmltree::Node,
+    attributes: LayerAttributes,
+    cell_count: usize,
+    layers: &mut Vec<TiledLayer>,
+) -> Result<(), TiledMapLoaderError> {
+    for child in node.children() {
+        if child.has_tag_name("group") {
+            parse_layers(child, attributes.child(child)?, cell_count, layers)?;
+        }
+        if !child.has_tag_name("layer") { continue };
+        let data_node = child
+            .children()
+            .find(|node| node.has_tag_name("data"))
+            .ok_or_else(|| TiledMapLoaderError::invalid("layer has no <data>"))?;
+        let mut tiles = parse_layer_data(data_node)?;
+        tiles.resize(cell_count, 0);
+        let LayerAttributes { visible, opacity, offset } = attributes.child(child)?;
+        layers.push(TiledLayer {
+            name: child.attribute("name").unwrap_or_default().to_string(),
+            visible,
+            opacity,
+            offset,
+            tiles,
+        });
+    }
+    Ok(())
+}
+
+fn parse_tileset(
+    node: roxmltree::Node,
+    first_gid: u32,
+    path: &AssetPath,
+    load_context: &mut LoadContext,
+) -> Result<TiledTileset, TiledMapLoaderError> {
+    let image_node = node
+        .children()
+        .find(|node| node.has_tag_name("image"))
+        .ok_or_else(|| TiledMapLoaderError::invalid("only tilesets made of a single image are supported"))?;
+    let image_source = image_node
+        .attribute("source")
+        .ok_or_else(|| TiledMapLoaderError::invalid("tileset image has no source"))?;
+    let tile_size = UVec2::new(parse_attribute(node, "tilewidth")?, parse_attribute(node, "tileheight")?);
+    Ok(TiledTileset {
+        name: node.attribute("name").unwrap_or_default().to_string(),
+        first_gid,
+        image: load_context.load(path.resolve_embed(image_source)?),
+        atlas: TileAtlas {
+            tile_size,
+            columns: parse_attribute(node, "columns")?,
+            margin: parse_optional_attribute(node, "margin")?.unwrap_or(0),
+            spacing: parse_optional_attribute(node, "spacing")?.unwrap_or(0),
+        },
+    })
+}
+
+/// Global tile ids of a layer's `<data>`, in any of the encodings Tiled writes.
+fn parse_layer_data(node: roxmltree::Node) -> Result<Vec<u32>, TiledMapLoaderError> {
+    let text = node.text().unwrap_or_default().trim();
+    match (node.attribute("encoding"), node.attribute("compression")) {
+        (Some("csv"), _) => text
+            .split(',')
+            .map(|gid| gid.trim().parse().map_err(|_| TiledMapLoaderError::invalid("invalid tile id")))
+            .collect(),
+        (Some("base64"), compression) => {
+            let compressed = base64::engine::general_purpose::STANDARD
+                .decode(text)
+                .map_err(|_| TiledMapLoaderError::invalid("invalid base64 layer data"))?;
+            let mut bytes = Vec::new();
+            match compression {
+                None => bytes = compressed,
+                Some("zlib") => { flate2::read::ZlibDecoder::new(&compressed[..]).read_to_end(&mut bytes)?; },
+                Some("gzip") => { flate2::read::GzDecoder::new(&compressed[..]).read_to_end(&mut bytes)?; },
+                Some(other) => return Err(TiledMapLoaderError::invalid(format!("unsupported compression: {other}"))),
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        },
+        (None, _) => node
+            .children()
+            .filter(|node| node.has_tag_name("tile"))
+            .map(|tile_node| Ok(parse_optional_attribute(tile_node, "gid")?.unwrap_or(0)))
+            .collect(),
+        (Some(other), _) => Err(TiledMapLoaderError::invalid(format!("unsupported encoding: {other}"))),
+    }
+}
+
+fn parse_attribute<T: std::str::FromStr>(node: roxmltree::Node, name: &str) -> Result<T, TiledMapLoaderError> {
+    parse_optional_attribute(node, name)?
+        .ok_or_else(|| TiledMapLoaderError::invalid(format!("missing attribute: {name}")))
+}
+
+fn parse_optional_attribute<T: std::str::FromStr>(
+    node: roxmltree::Node,
+    name: &str,
+) -> Result<Option<T>, TiledMapLoaderError> {
+    node.attribute(name)
+        .map(|value| value.parse().map_err(|_| TiledMapLoaderError::invalid(format!("invalid attribute: {name}"))))
+        .transpose()
+}
+
+/// Error while loading a [`TiledMap`].
+#[derive(Debug)]
+pub enum TiledMapLoaderError {
+    Io(std::io::Error),
+    ReadAssetBytes(ReadAssetBytesError),
+    AssetPath(ParseAssetPathError),
+    Xml(roxmltree::Error),
+    /// The map is malformed, or uses a feature that isn't supported.
+    Invalid(String),
+}
+
+impl TiledMapLoaderError {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self::Invalid(message.into())
+    }
+}
+
+impl std::fmt::Display for TiledMapLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read Tiled map: {err}"),
+            Self::ReadAssetBytes(err) => write!(f, "could not read Tiled tileset: {err}"),
+            Self::AssetPath(err) => write!(f, "invalid path in Tiled map: {err}"),
+            Self::Xml(err) => write!(f, "invalid Tiled XML: {err}"),
+            Self::Invalid(message) => write!(f, "invalid Tiled map: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TiledMapLoaderError {}
+
+impl From<std::io::Error> for TiledMapLoaderError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ReadAssetBytesError> for TiledMapLoaderError {
+    fn from(err: ReadAssetBytesError) -> Self {
+        Self::ReadAssetBytes(err)
+    }
+}
+
+impl From<ParseAssetPathError> for TiledMapLoaderError {
+    fn from(err: ParseAssetPathError) -> Self {
+        Self::AssetPath(err)
+    }
+}
+
+impl From<roxmltree::Error> for TiledMapLoaderError {
+    fn from(err: roxmltree::Error) -> Self {
+        Self::Xml(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::test_utils::{fixture_app, load_fixture};
+
+    /// Loads a map from the Tiled fixtures.
+    fn load_map(path: &str) -> TiledMap {
+        let mut app = fixture_app("tiled");
+        app.add_plugins(TiledMapPlugin);
+        app.init_asset::<StandardMaterial>();
+        let handle = load_fixture::<TiledMap>(&mut app, path);
+        app.world_mut().resource_mut::<Assets<TiledMap>>().remove(&handle).unwrap()
+    }
+
+    const TILES: [u32; 6] = [1, 2, 0, 0, FLIPPED_HORIZONTALLY | 2, 1];
+
+    #[test]
+    fn layer_data_is_decoded_in_every_encoding() {
+        for path in ["csv.tmx", "base64.tmx", "zlib.tmx", "gzip.tmx"] {
+            let map = load_map(path);
+            assert_eq!(map.grid_size, UVec2::new(3, 2));
+            assert_eq!(map.tile_size, UVec2::new(16, 16));
+            assert_eq!(map.layers.len(), 1, "{path}");
+            assert_eq!(map.layers[0].name, "ground");
+            assert_eq!(map.layers[0].tiles, TILES, "{path}");
+            assert_eq!(map.tilesets[0].atlas, TileAtlas::new(UVec2::splat(16), 2));
+        }
+    }
+
+    #[test]
+    fn external_tilesets_are_loaded() {
+        let map = load_map("external.tmx");
+        assert_eq!(map.layers[0].tiles, TILES);
+        let tileset = &map.tilesets[0];
+        assert_eq!(tileset.name, "external");
+        assert_eq!(tileset.first_gid, 1);
+        assert_eq!(tileset.atlas, TileAtlas { tile_size: UVec2::splat(16), columns: 2, margin: 2, spacing: 1 });
+    }
+
+    #[test]
+    fn groups_are_combined_into_their_layers() {
+        let map = load_map("groups.tmx");
+        let layers: Vec<(&str, bool, f32, Vec2)> = map.layers
+            .iter()
+            .map(|layer| (layer.name.as_str(), layer.visible, layer.opacity, layer.offset))
+            .collect();
+        assert_eq!(layers, vec![
+            ("top", true, 1.0, Vec2::new(1.0, 2.0)),
+            ("inner", true, 0.25, Vec2::new(5.0, 9.0)),
+            ("nested", false, 0.5, Vec2::new(4.0, 8.0)),
+        ]);
+    }
+
+    #[test]
+    fn tile_flags_are_decoded() {
+        let tileset = |first_gid| TiledTileset {
+            name: String::new(),
+            first_gid,
+            image: Handle::default(),
+            atlas: TileAtlas::new(UVec2::splat(16), 4),
+        };
+        let map = TiledMap {
+            grid_size: UVec2::ONE,
+            tile_size: UVec2::splat(16),
+            tilesets: vec![tileset(1), tileset(5)],
+            layers: Vec::new(),
+        };
+        assert_eq!(map.tile(0), None);
+        assert_eq!(map.tile(FLIPPED_VERTICALLY), None);
+        assert_eq!(map.tile(4), Some((0, Tile3d::new(3))));
+        let flipped = map.tile(6 | FLIPPED_HORIZONTALLY | FLIPPED_DIAGONALLY);
+        assert_eq!(flipped, Some((1, Tile3d { flip_x: true, flip_d: true, ..Tile3d::new(1) })));
+        let flipped = map.tile(5 | FLIPPED_VERTICALLY | 0x1000_0000);
+        assert_eq!(flipped, Some((1, Tile3d { flip_y: true, ..Tile3d::new(0) })));
+    }
+}
@@ -0,0 +1,238 @@
+use bevy_color::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::QueryData;
+use bevy_hierarchy::prelude::*;
+use bevy_math::{Rect, UVec2, Vec2};
+use bevy_reflect::prelude::*;
+use bevy_render::prelude::*;
+use bevy_transform::prelude::*;
+
+use crate::{SizedMaterial, Sprite3d, SpriteMaterial3d};
+
+/// Grid of tiles, rendered as batched sprites using the [`SpriteMaterial3d`] of the layer's entity.
+/// Each tile is spawned as a child [`Sprite3d`] entity, with a [`Sprite3dTile`] marker.
+/// The grid starts at the layer's origin, with columns going towards +X and rows going towards -Y, like 2D tilemaps.
+/// Rotate the layer's entity to lay it on a floor, or against a wall.
+#[derive(Component, Reflect, Clone, PartialEq, Debug)]
+#[require(Transform, Visibility)]
+pub struct Sprite3dTileLayer {
+    /// Number of columns and rows of the grid.
+    pub grid_size: UVec2,
+    /// Size of a tile, in world units.
+    pub tile_size: Vec2,
+    pub atlas: TileAtlas,
+    /// Tiles in row-major order, starting at the top left. None for empty cells.
+    pub tiles: Vec<Option<Tile3d>>,
+}
+
+impl Sprite3dTileLayer {
+    /// Empty layer. Grids with more cells than a `usize` can count are clamped to the rows that fit.
+    pub fn new(grid_size: UVec2, tile_size: Vec2, atlas: TileAtlas) -> Self {
+        let max_rows = usize::MAX / (grid_size.x as usize).max(1);
+        let grid_size = UVec2::new(grid_size.x, grid_size.y.min(u32::try_from(max_rows).unwrap_or(u32::MAX)));
+        Self {
+            grid_size,
+            tile_size,
+            atlas,
+            tiles: vec![None; cell_count(grid_size).unwrap_or_default()],
+        }
+    }
+
+    pub fn get(&self, position: UVec2) -> Option<&Tile3d> {
+        self.index(position).and_then(|i| self.tiles.get(i)?.as_ref())
+    }
+
+    /// Places a tile in a cell, or empties it. Does nothing if the position is out of the grid.
+    pub fn set(&mut self, position: UVec2, tile: Option<Tile3d>) {
+        let Some(i) = self.index(position) else { return };
+        if let Some(cell) = self.tiles.get_mut(i) {
+            *cell = tile;
+        }
+    }
+
+    fn index(&self, position: UVec2) -> Option<usize> {
+        let in_grid = position.x < self.grid_size.x && position.y < self.grid_size.y;
+        in_grid.then(|| position.y as usize * self.grid_size.x as usize + position.x as usize)
+    }
+
+    /// Position of the cell at an index of `tiles`. None if the index is out of the grid.
+    pub fn position(&self, index: usize) -> Option<UVec2> {
+        let in_grid = cell_count(self.grid_size).is_some_and(|cells| index < cells);
+        let columns = self.grid_size.x as usize;
+        in_grid.then(|| UVec2::new((index % columns) as u32, (index / columns) as u32))
+    }
+
+    /// Local position of the center of a cell.
+    pub fn cell_center(&self, position: UVec2) -> Vec2 {
+        let cell = position.as_vec2() + 0.5;
+        Vec2::new(cell.x, -cell.y) * self.tile_size
+    }
+}
+
+/// Number of cells in a grid, or None if it doesn't fit in a usize.
+fn cell_count(grid_size: UVec2) -> Option<usize> {
+    (grid_size.x as usize).checked_mul(grid_size.y as usize)
+}
+
+/// Layout of the tiles in a layer's texture.
+#[derive(Reflect, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TileAtlas {
+    /// Size of a tile, in pixels.
+    pub tile_size: UVec2,
+    /// Number of tiles per row of the texture.
+    pub columns: u32,
+    /// Pixels around the tiles, on the edges of the texture.
+    pub margin: u32,
+    /// Pixels between adjacent tiles.
+    pub spacing: u32,
+}
+
+impl TileAtlas {
+    pub fn new(tile_size: UVec2, columns: u32) -> Self {
+        Self { tile_size, columns, margin: 0, spacing: 0 }
+    }
+
+    /// Region of the texture covered by a tile, in pixels.
+    pub fn rect(&self, index: u32) -> Rect {
+        let columns = self.columns.max(1);
+        let cell = UVec2::new(index % columns, index / columns);
+        let min = UVec2::splat(self.margin) + cell * (self.tile_size + self.spacing);
+        Rect::from_corners(min.as_vec2(), (min + self.tile_size).as_vec2())
+    }
+}
+
+/// A single tile of a [`Sprite3dTileLayer`].
+#[derive(Reflect, Copy, Clone, PartialEq, Debug)]
+pub struct Tile3d {
+    /// Index of the tile in the layer's [`TileAtlas`].
+    pub index: u32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Diagonal flip, see [`Sprite3d::flip_d`].
+    pub flip_d: bool,
+    pub color: Color,
+}
+
+impl Tile3d {
+    pub fn new(index: u32) -> Self {
+        Self { index, flip_x: false, flip_y: false, flip_d: false, color: Color::WHITE }
+    }
+}
+
+/// Marks a sprite spawned for a tile of the parent [`Sprite3dTileLayer`].
+#[derive(Component, Reflect, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Sprite3dTile {
+    /// Position of the tile in the grid.
+    pub position: UVec2,
+}
+
+#[derive(QueryData)]
+pub(crate) struct TileLayerQuery<M: SizedMaterial> {
+    entity: Entity,
+    layer: &'static Sprite3dTileLayer,
+    material: &'static SpriteMaterial3d<M>,
+    children: Option<&'static Children>,
+}
+
+/// Respawns the tile sprites of layers that changed.
+#[allow(clippy::type_complexity)]
+pub(crate) fn spawn_tile_sprites<M: SizedMaterial>(
+    mut commands: Commands,
+    layers: Query<TileLayerQuery<M>, Or<(Changed<Sprite3dTileLayer>, Changed<SpriteMaterial3d<M>>)>>,
+    tiles: Query<(), With<Sprite3dTile>>,
+) {
+    for item in &layers {
+        let layer = item.layer;
+        for &child in item.children.into_iter().flatten() {
+            if tiles.contains(child) {
+                commands.entity(child).despawn_recursive();
+            }
+        }
+        commands.entity(item.entity).with_children(|parent| {
+            for (i, tile) in layer.tiles.iter().enumerate() {
+                // Tiles past the end of the grid, ie: all of them if it has no columns, are left out
+                let Some(position) = layer.position(i) else { break };
+                let Some(tile) = tile else { continue };
+                parent.spawn((
+                    Sprite3d {
+                        color: tile.color,
+                        flip_x: tile.flip_x,
+                        flip_y: tile.flip_y,
+                        flip_d: tile.flip_d,
+                        custom_size: Some(layer.tile_size),
+                        rect: Some(layer.atlas.rect(tile.index)),
+                        ..Default::default()
+                    },
+                    SpriteMaterial3d(item.material.0.clone()),
+                    Transform::from_translation(layer.cell_center(position).extend(0.0)),
+                    Sprite3dTile { position },
+                ));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::render::render_asset::RenderAssetUsages;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use crate::*;
+
+    /// Headless app, with a material textured with a white image of the given size.
+    fn app_with_material(width: u32, height: u32) -> (App, Handle<StandardMaterial>) {
+        let mut app = App::new();
+        let plugin = Sprite3dPlugin::<StandardMaterial>::default();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin, plugin));
+        app.init_asset::<Image>();
+        app.init_asset::<Mesh>();
+        app.init_asset::<StandardMaterial>();
+        let image = app.world_mut().resource_mut::<Assets<Image>>().add(Image::new_fill(
+            Extent3d { width, height, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            &[255, 255, 255, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::all(),
+        ));
+        let material = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+            base_color_texture: Some(image),
+            ..default()
+        });
+        (app, material)
+    }
+
+    fn tile_positions(app: &mut App) -> Vec<UVec2> {
+        let mut tiles = app.world_mut().query::<&Sprite3dTile>();
+        let mut positions: Vec<UVec2> = tiles.iter(app.world()).map(|tile| tile.position).collect();
+        positions.sort_by_key(|position| (position.y, position.x));
+        positions
+    }
+
+    #[test]
+    fn tiles_are_spawned_in_their_cells() {
+        let (mut app, material) = app_with_material(32, 16);
+        let mut layer = Sprite3dTileLayer::new(UVec2::new(3, 2), Vec2::ONE, TileAtlas::new(UVec2::splat(16), 2));
+        layer.set(UVec2::new(2, 0), Some(Tile3d::new(1)));
+        layer.set(UVec2::new(0, 1), Some(Tile3d::new(0)));
+        layer.set(UVec2::new(3, 1), Some(Tile3d::new(0)));
+        app.world_mut().spawn((layer, SpriteMaterial3d(material)));
+        app.update();
+
+        assert_eq!(tile_positions(&mut app), vec![UVec2::new(2, 0), UVec2::new(0, 1)]);
+    }
+
+    #[test]
+    fn layers_without_columns_spawn_no_tiles() {
+        let (mut app, material) = app_with_material(16, 16);
+        let layer = Sprite3dTileLayer {
+            tiles: vec![Some(Tile3d::new(0)); 2],
+            ..Sprite3dTileLayer::new(UVec2::new(0, 2), Vec2::ONE, TileAtlas::new(UVec2::splat(16), 1))
+        };
+        assert_eq!(layer.position(0), None);
+        app.world_mut().spawn((layer, SpriteMaterial3d(material)));
+        app.update();
+
+        assert!(tile_positions(&mut app).is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::prelude::*;
+
+/// World axis sprites stand along, set with [`Sprite3dPlugin::with_up_axis`](crate::Sprite3dPlugin::with_up_axis).
+/// Sprites are built in the XY plane of their transform, with the top of their image towards +Y and visible looking
+/// down their -Z axis. In Z-up worlds, ie: scenes from Blender or CAD tools, this basis is turned so the top of the
+/// image points towards +Z instead, and the sprite is visible looking down +Y, so that sprites with an identity
+/// rotation stand upright rather than lie on their sides.
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum UpAxis {
+    #[default]
+    Y,
+    Z,
+}
+
+impl UpAxis {
+    /// Direction of the axis, in world space.
+    pub fn up(self) -> Vec3 {
+        match self {
+            Self::Y => Vec3::Y,
+            Self::Z => Vec3::Z,
+        }
+    }
+
+    /// Rotation from the Y-up basis sprites are built in, to the basis of this axis.
+    pub fn rotation(self) -> Quat {
+        match self {
+            Self::Y => Quat::IDENTITY,
+            Self::Z => Quat::from_rotation_x(FRAC_PI_2),
+        }
+    }
+}
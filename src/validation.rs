@@ -0,0 +1,50 @@
+use std::borrow::Cow;
+
+use bevy_math::{Rect, Vec2};
+use bevy_reflect::prelude::*;
+
+use crate::Sprite3d;
+
+/// How sprites are rendered when their [`Sprite3d::rect`] doesn't fit their texture, which would otherwise
+/// sample wrapped or garbled texels. Either way, a [`Sprite3dWarning`](crate::Sprite3dWarning) is sent about them.
+/// Only applies to the sprite's own rect, not to those of its [`Sprite3dParts`](crate::Sprite3dParts).
+#[derive(Reflect, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum RectValidation {
+    /// Sprites are rendered with their rect as-is.
+    #[default]
+    Warn,
+    /// Rects reaching outside of the texture are clamped to it. Inverted rects, and rects entirely outside of the
+    /// texture, aren't rendered.
+    Clamp,
+    /// Sprites with inverted rects, or rects reaching outside of the texture, aren't rendered.
+    Reject,
+}
+
+impl RectValidation {
+    /// The sprite to render in place of the given one, or None if it isn't rendered.
+    pub(crate) fn validate<'a>(self, sprite: &'a Sprite3d, texture_size: Vec2) -> Option<Cow<'a, Sprite3d>> {
+        let Some(rect) = sprite.rect else { return Some(Cow::Borrowed(sprite)) };
+        if self == Self::Warn || (!is_inverted(rect) && fits(rect, texture_size)) {
+            return Some(Cow::Borrowed(sprite));
+        }
+        if self == Self::Reject || is_inverted(rect) {
+            return None;
+        }
+        let clamped = Rect {
+            min: rect.min.clamp(Vec2::ZERO, texture_size),
+            max: rect.max.clamp(Vec2::ZERO, texture_size),
+        };
+        if clamped.is_empty() { return None };
+        Some(Cow::Owned(Sprite3d { rect: Some(clamped), ..sprite.clone() }))
+    }
+}
+
+/// True if the minimum of a rect is past its maximum, on either axis.
+pub(crate) fn is_inverted(rect: Rect) -> bool {
+    rect.min.cmpgt(rect.max).any()
+}
+
+/// True if a rect is within a texture of the given size.
+pub(crate) fn fits(rect: Rect, texture_size: Vec2) -> bool {
+    rect.min.cmpge(Vec2::ZERO).all() && rect.max.cmple(texture_size).all()
+}
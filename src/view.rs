@@ -0,0 +1,90 @@
+use std::ops::Range;
+
+use bevy_math::{Quat, Vec3A};
+use bevy_render::camera::{Camera, OrthographicProjection, Projection};
+use bevy_render::view::VisibilityRange;
+use bevy_transform::prelude::*;
+
+/// A camera, as seen by sprites that adapt to the camera they are rendered for.
+/// Batches are shared by all cameras, so such sprites adapt to the camera nearest to them.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct SpriteView {
+    pub position: Vec3A,
+    pub rotation: Quat,
+    pub forward: Vec3A,
+    /// World size of a screen pixel, at a depth of 1 for perspective cameras.
+    /// None if the camera's viewport size isn't known yet.
+    pub pixel_size: Option<f32>,
+    pub perspective: bool,
+    /// [`Camera::order`].
+    pub order: isize,
+}
+
+impl SpriteView {
+    pub fn new(
+        transform: &GlobalTransform,
+        camera: &Camera,
+        projection: Option<&Projection>,
+        orthographic: Option<&OrthographicProjection>,
+    ) -> Self {
+        let viewport_height = camera.logical_viewport_size().map(|size| size.y).filter(|&height| height > 0.0);
+        let (pixel_size, perspective) = match (projection, orthographic) {
+            (Some(Projection::Perspective(perspective)), _) => {
+                let pixel_size = viewport_height.map(|height| 2.0 * (perspective.fov * 0.5).tan() / height);
+                (pixel_size, true)
+            },
+            (Some(Projection::Orthographic(orthographic)), _) | (None, Some(orthographic)) => {
+                let pixel_size = viewport_height.map(|height| orthographic.area.height() / height);
+                (pixel_size, false)
+            },
+            (None, None) => (None, false),
+        };
+        Self {
+            position: transform.translation_vec3a(),
+            rotation: transform.rotation(),
+            forward: transform.forward().as_vec3().into(),
+            pixel_size,
+            perspective,
+            order: camera.order,
+        }
+    }
+
+    /// World size of a screen pixel, at a position.
+    pub fn pixel_size_at(&self, position: Vec3A) -> Option<f32> {
+        let pixel_size = self.pixel_size?;
+        match self.perspective {
+            true => Some(pixel_size * (position - self.position).dot(self.forward).max(f32::EPSILON)),
+            false => Some(pixel_size),
+        }
+    }
+}
+
+/// View rendered first, usually the main camera.
+pub(crate) fn first_view(views: &[SpriteView]) -> Option<&SpriteView> {
+    views.iter().min_by_key(|view| view.order)
+}
+
+/// View nearest to a position.
+pub(crate) fn nearest_view(views: &[SpriteView], position: Vec3A) -> Option<&SpriteView> {
+    views
+        .iter()
+        .min_by(|a, b| a.position.distance_squared(position).total_cmp(&b.position.distance_squared(position)))
+}
+
+/// Squared distance from a position to the nearest view. Infinite if there are no views.
+pub(crate) fn nearest_distance_squared(views: &[SpriteView], position: Vec3A) -> f32 {
+    views.iter().map(|view| view.position.distance_squared(position)).fold(f32::INFINITY, f32::min)
+}
+
+/// How visible a sprite at a position is within its [`VisibilityRange`], from 0 (out of range) to 1 (fully visible),
+/// for the camera nearest to it. Crossfades linearly through the start and end margins of the range.
+pub(crate) fn visibility_range_alpha(range: &VisibilityRange, position: Vec3A, views: &[SpriteView]) -> f32 {
+    let Some(view) = nearest_view(views, position) else { return 1.0 };
+    let distance = view.position.distance(position);
+    if range.is_culled(distance) { return 0.0 };
+    let ramp = |margin: &Range<f32>| match margin.end > margin.start {
+        true => ((distance - margin.start) / (margin.end - margin.start)).clamp(0.0, 1.0),
+        false => if distance >= margin.start { 1.0 } else { 0.0 },
+    };
+    ramp(&range.start_margin) * (1.0 - ramp(&range.end_margin))
+}
@@ -0,0 +1,122 @@
+use std::marker::PhantomData;
+use std::mem::{discriminant, Discriminant};
+
+use bevy_asset::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_image::prelude::*;
+use bevy_math::{Rect, Vec2};
+use bevy_transform::prelude::*;
+use bevy_utils::tracing::warn;
+use bevy_utils::{HashMap, HashSet};
+
+use crate::validation::{fits, is_inverted};
+use crate::{MeshBatch, SizedMaterial, Sprite3d, SpriteMaterial3d};
+
+/// Sent when a sprite can't be rendered as intended, so that problems show up in logs and editors, rather than
+/// sprites silently not appearing. Each warning is sent once, and again only if it goes away and comes back.
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct Sprite3dWarning {
+    pub entity: Entity,
+    pub kind: Sprite3dWarningKind,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Sprite3dWarningKind {
+    /// The sprite's material has no texture to size it with, so the sprite is never rendered.
+    MissingTexture,
+    /// The sprite's material, or its texture, still isn't loaded after this many frames.
+    /// Sent after [`Sprite3dPlugin::load_warning_frames`](crate::Sprite3dPlugin::load_warning_frames).
+    NotLoaded { frames: u32 },
+    /// The sprite's rect reaches outside of its texture, ie: a typo in atlas coordinates.
+    /// See [`RectValidation`](crate::RectValidation) for how such sprites are rendered.
+    RectOutOfBounds { rect: Rect, texture_size: Vec2 },
+    /// The minimum of the sprite's rect is past its maximum.
+    InvertedRect { rect: Rect },
+    /// The sprite's custom size or rect has no area, so it renders nothing.
+    ZeroSize,
+    /// The sprite's global transform has NaN or infinite values.
+    NonFiniteTransform,
+}
+
+/// Warnings last sent for the sprites of a [`Sprite3dPlugin`](crate::Sprite3dPlugin), and how long their assets
+/// have been loading.
+#[derive(Resource)]
+pub(crate) struct SpriteWarningState<M: SizedMaterial> {
+    load_warning_frames: u32,
+    reported: HashSet<(Entity, Discriminant<Sprite3dWarningKind>)>,
+    loading_frames: HashMap<Entity, u32>,
+    marker: PhantomData<M>,
+}
+
+impl<M: SizedMaterial> SpriteWarningState<M> {
+    pub fn new(load_warning_frames: u32) -> Self {
+        Self {
+            load_warning_frames,
+            reported: HashSet::default(),
+            loading_frames: HashMap::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+// Finds sprites that can't render as intended, and reports the ones that weren't already reported.
+pub(crate) fn check_sprite_warnings<M: SizedMaterial>(
+    sprites: Query<(Entity, &Sprite3d, &SpriteMaterial3d<M>, &GlobalTransform)>,
+    mesh_batch: Res<MeshBatch<M>>,
+    materials: Res<Assets<M>>,
+    images: Res<Assets<Image>>,
+    mut state: ResMut<SpriteWarningState<M>>,
+    mut warnings: EventWriter<Sprite3dWarning>,
+) {
+    let state = &mut *state;
+    let mut current = Vec::new();
+    let mut loading_frames = HashMap::default();
+    for (entity, sprite, material, transform) in &sprites {
+        if !transform.affine().is_finite() {
+            current.push(Sprite3dWarning { entity, kind: Sprite3dWarningKind::NonFiniteTransform });
+        }
+        let is_empty = |size: Vec2| size.x == 0.0 || size.y == 0.0;
+        if sprite.custom_size.is_some_and(is_empty) || sprite.rect.is_some_and(|rect| is_empty(rect.size())) {
+            current.push(Sprite3dWarning { entity, kind: Sprite3dWarningKind::ZeroSize });
+        }
+        let loaded_material = materials.get(&material.0);
+        let size = loaded_material
+            .and_then(|sprite_mat| sprite_mat.size(&images))
+            .or_else(|| mesh_batch.last_material_size(material.0.id()));
+        match (loaded_material, size) {
+            (Some(sprite_mat), None) if sprite_mat.texture().is_none() => {
+                current.push(Sprite3dWarning { entity, kind: Sprite3dWarningKind::MissingTexture });
+            },
+            (_, None) => {
+                let frames = state.loading_frames.get(&entity).copied().unwrap_or(0) + 1;
+                loading_frames.insert(entity, frames);
+                if frames >= state.load_warning_frames {
+                    current.push(Sprite3dWarning { entity, kind: Sprite3dWarningKind::NotLoaded { frames } });
+                }
+            },
+            (_, Some(texture_size)) => {
+                let Some(rect) = sprite.rect else { continue };
+                if is_inverted(rect) {
+                    current.push(Sprite3dWarning { entity, kind: Sprite3dWarningKind::InvertedRect { rect } });
+                } else if !fits(rect, texture_size) {
+                    current.push(Sprite3dWarning {
+                        entity,
+                        kind: Sprite3dWarningKind::RectOutOfBounds { rect, texture_size },
+                    });
+                }
+            },
+        }
+    }
+    state.loading_frames = loading_frames;
+
+    let mut reported = HashSet::default();
+    for warning in current {
+        let key = (warning.entity, discriminant(&warning.kind));
+        if !state.reported.contains(&key) {
+            warn!("Sprite {} can't be rendered as intended: {:?}", warning.entity, warning.kind);
+            warnings.send(warning);
+        }
+        reported.insert(key);
+    }
+    state.reported = reported;
+}
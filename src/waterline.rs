@@ -0,0 +1,92 @@
+use bevy_app::prelude::*;
+use bevy_asset::{load_internal_asset, prelude::*};
+use bevy_math::{Mat3, Vec3};
+use bevy_pbr::prelude::*;
+use bevy_pbr::{ExtendedMaterial, MaterialExtension};
+use bevy_reflect::prelude::*;
+use bevy_render::render_resource::{AsBindGroup, Shader, ShaderRef};
+
+use crate::SpriteMaterialExtension;
+
+const WATERLINE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x91b4_0e6d_3c58_47f2_b1a9_6d0e_84c2_5f17);
+
+/// Material of sprites that dissolve below a world space plane, ie: partially submerged billboards.
+pub type WaterlineMaterial = ExtendedMaterial<StandardMaterial, WaterlineExtension>;
+
+/// Extends a material with a fragment shader that discards fragments below a plane, per pixel.
+/// Unlike a [`Sprite3dClipRect`](crate::Sprite3dClipRect), which cuts quads while batching, the plane is shared
+/// by every sprite of the material, and can be moved without rebuilding batches, ie: rising and falling tides.
+/// Fragments are discarded in the main and deferred passes only, so sprites with an opaque material still write
+/// depth below the plane. Use a blended alpha mode, or [`Sprite3dNoPrepass`](crate::Sprite3dNoPrepass).
+#[derive(Asset, AsBindGroup, Reflect, Clone, Debug)]
+#[uniform(100, Mat3)]
+pub struct WaterlineExtension {
+    /// Any point on the plane, ie: on the water's surface.
+    pub point: Vec3,
+    /// Points towards the side of the plane that is rendered. Doesn't need to be normalized.
+    pub normal: Vec3,
+    /// Distance above the plane, in world units, over which sprites dissolve in a dithered pattern.
+    /// Zero for a hard cut.
+    pub fade: f32,
+}
+
+/// Packs the extension's settings into the uniform read by the shader, as the columns of a matrix.
+impl From<&WaterlineExtension> for Mat3 {
+    fn from(extension: &WaterlineExtension) -> Self {
+        Mat3::from_cols(extension.point, extension.normal.normalize_or_zero(), Vec3::X * extension.fade)
+    }
+}
+
+impl Default for WaterlineExtension {
+    fn default() -> Self {
+        Self {
+            point: Vec3::ZERO,
+            normal: Vec3::Y,
+            fade: 0.0,
+        }
+    }
+}
+
+impl WaterlineExtension {
+    /// Dissolves sprites below a height.
+    pub fn new(height: f32) -> Self {
+        Self { point: Vec3::Y * height, ..Self::default() }
+    }
+
+    pub fn with_normal(mut self, normal: Vec3) -> Self {
+        self.normal = normal;
+        self
+    }
+
+    pub fn with_fade(mut self, fade: f32) -> Self {
+        self.fade = fade;
+        self
+    }
+}
+
+impl MaterialExtension for WaterlineExtension {
+    fn fragment_shader() -> ShaderRef {
+        WATERLINE_SHADER_HANDLE.into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        WATERLINE_SHADER_HANDLE.into()
+    }
+}
+
+impl SpriteMaterialExtension for WaterlineExtension {
+    fn vertex_colors_optional() -> bool {
+        true
+    }
+}
+
+/// Registers [`WaterlineMaterial`] and its shader.
+/// Sprites using it are batched by adding a [`Sprite3dPlugin::<WaterlineMaterial>`](crate::Sprite3dPlugin) as well.
+pub struct Sprite3dWaterlinePlugin;
+
+impl Plugin for Sprite3dWaterlinePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, WATERLINE_SHADER_HANDLE, "waterline.wgsl", Shader::from_wgsl);
+        app.add_plugins(MaterialPlugin::<WaterlineMaterial>::default());
+    }
+}
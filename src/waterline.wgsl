@@ -0,0 +1,62 @@
+#import bevy_pbr::{
+    pbr_functions::alpha_discard,
+    pbr_fragment::pbr_input_from_standard_material,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+}
+#endif
+
+// Point on the plane, the plane's normal, and the fade distance in x
+@group(2) @binding(100) var<uniform> waterline: mat3x3<f32>;
+
+// True if a fragment is dissolved, being below the plane, or within its fade distance and dithered out.
+fn is_below_waterline(world_position: vec3<f32>, frag_coord: vec2<f32>) -> bool {
+    let height = dot(world_position - waterline[0], waterline[1]);
+    let fade = waterline[2].x;
+    if height < 0.0 {
+        return true;
+    }
+    if height >= fade {
+        return false;
+    }
+    // Interleaved gradient noise, so that the dithering is stable from frame to frame
+    let noise = fract(52.9829189 * fract(dot(frag_coord, vec2(0.06711056, 0.00583715))));
+    return noise > height / fade;
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    if is_below_waterline(in.world_position.xyz, in.position.xy) {
+        discard;
+    }
+
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+        out.color = apply_pbr_lighting(pbr_input);
+    } else {
+        out.color = pbr_input.material.base_color;
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+    return out;
+}
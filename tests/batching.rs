@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_mod_sprite3d::{
+    BatchBudget, MeshBatch, Sprite3d, Sprite3dBatchInfo, Sprite3dBillboard, Sprite3dPlugin, SpriteMaterial3d,
+};
+use bevy_mod_sprite3d::test_support::add_headless_batching;
+
+/// Builds a headless app batching sprites with the given plugin, with a camera looking down -Z.
+fn create_app(plugin: Sprite3dPlugin<StandardMaterial>) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    add_headless_batching(&mut app, plugin);
+    app.world_mut().spawn((Camera::default(), Transform::from_xyz(0.0, 2.0, 10.0)));
+    app
+}
+
+fn add_material(app: &mut App) -> Handle<StandardMaterial> {
+    let image = app.world_mut().resource_mut::<Assets<Image>>().add(Image::new_fill(
+        Extent3d { width: 16, height: 16, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[255, 255, 255, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    ));
+    app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+        base_color_texture: Some(image),
+        ..default()
+    })
+}
+
+fn spawn_sprites(app: &mut App, material: &Handle<StandardMaterial>, count: usize) {
+    for i in 0..count {
+        app.world_mut().spawn((
+            Sprite3d::default(),
+            SpriteMaterial3d(material.clone()),
+            Sprite3dBillboard::default(),
+            Transform::from_xyz(i as f32, 0.0, 0.0),
+        ));
+    }
+}
+
+/// Meshes modified since the last call.
+fn modified_meshes(app: &mut App) -> usize {
+    let events = app.world().resource::<Events<AssetEvent<Mesh>>>();
+    let mut reader = events.get_cursor();
+    let modified = reader.read(events).filter(|event| matches!(event, AssetEvent::Modified { .. })).count();
+    app.world_mut().resource_mut::<Events<AssetEvent<Mesh>>>().clear();
+    modified
+}
+
+#[test]
+fn sprites_share_one_batch_per_material() {
+    let mut app = create_app(Sprite3dPlugin::default());
+    let (first, second) = (add_material(&mut app), add_material(&mut app));
+    spawn_sprites(&mut app, &first, 2);
+    spawn_sprites(&mut app, &second, 3);
+    app.update();
+
+    let mut batches = app.world_mut().query::<&Sprite3dBatchInfo>();
+    let mut counts: Vec<(usize, usize)> = batches.iter(app.world()).map(|info| (info.sprites, info.quads)).collect();
+    counts.sort();
+    assert_eq!(counts, vec![(2, 2), (3, 3)]);
+}
+
+#[test]
+fn budgeted_batches_are_left_alone_while_nothing_changes() {
+    let mut app = create_app(Sprite3dPlugin::default().with_budget(BatchBudget::Sprites(2)));
+    let material = add_material(&mut app);
+    spawn_sprites(&mut app, &material, 5);
+    for _ in 0..4 {
+        app.update();
+    }
+    modified_meshes(&mut app);
+    app.update();
+    assert_eq!(modified_meshes(&mut app), 0);
+
+    let mut cameras = app.world_mut().query_filtered::<&mut Transform, With<Camera>>();
+    cameras.single_mut(app.world_mut()).translation.x = 5.0;
+    app.update();
+    assert!(modified_meshes(&mut app) > 0);
+}
+
+#[test]
+fn deterministic_runs_write_identical_vertices() {
+    let vertex_hash = || {
+        let mut app = create_app(Sprite3dPlugin::default().with_deterministic_batching());
+        let material = add_material(&mut app);
+        spawn_sprites(&mut app, &material, 8);
+        app.update();
+        let meshes = app.world().resource::<Assets<Mesh>>();
+        app.world().resource::<MeshBatch<StandardMaterial>>().vertex_hash(meshes)
+    };
+    assert_eq!(vertex_hash(), vertex_hash());
+}
@@ -0,0 +1,4 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="external" tilewidth="16" tileheight="16" spacing="1" margin="2" tilecount="2" columns="2">
+ <image source="tileset.png" width="32" height="16"/>
+</tileset>